@@ -0,0 +1,315 @@
+pub mod lowering;
+pub mod visitor;
+
+use crate::{logger::Loggable, source_map::Span};
+
+use self::visitor::{walk_expr, walk_stmt, walk_var, Visitor};
+
+/**
+ * The abstract syntax tree of a program, lowered from its parse tree (see `lowering`).
+ * Unlike the symbol table, it is built without the semantic actions, so it exists for any syntactically valid program.
+ */
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub functions: Vec<FuncDecl>,
+    pub declarations: Vec<VarDecl>,
+    pub statements: Vec<Stmt>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Type {
+    Int,
+    Double,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct FuncDecl {
+    pub name: Ident,
+    pub return_type: Type,
+    pub params: Vec<VarDecl>,
+    pub declarations: Vec<VarDecl>,
+    pub body: Vec<Stmt>,
+    pub span: Span,
+}
+
+/**
+ * A variable, parameter or array declaration. The span is the one of the variable, after its type.
+ */
+#[derive(Debug, Clone)]
+pub struct VarDecl {
+    pub name: Ident,
+    pub var_type: Type,
+    pub array_size: Option<Expr>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum Stmt {
+    Assign {
+        target: Var,
+        value: Expr,
+        span: Span,
+    },
+    If {
+        condition: Expr,
+        then_branch: Vec<Stmt>,
+        else_branch: Option<Vec<Stmt>>,
+        span: Span,
+    },
+    While {
+        condition: Expr,
+        body: Vec<Stmt>,
+        span: Span,
+    },
+    Print {
+        value: Expr,
+        span: Span,
+    },
+    Return {
+        value: Expr,
+        span: Span,
+    },
+}
+
+impl Stmt {
+    pub fn get_span(&self) -> Span {
+        match self {
+            Stmt::Assign { span, .. }
+            | Stmt::If { span, .. }
+            | Stmt::While { span, .. }
+            | Stmt::Print { span, .. }
+            | Stmt::Return { span, .. } => *span,
+        }
+    }
+}
+
+/**
+ * A variable, or an element of an array if it is indexed.
+ */
+#[derive(Debug, Clone)]
+pub struct Var {
+    pub name: Ident,
+    pub index: Option<Box<Expr>>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum ExprKind {
+    Int(u32),
+    Double(f64),
+    Var(Var),
+    Call(Ident, Vec<Expr>),
+    // An explicit conversion, `int(...)` or `double(...)`
+    Cast(Type, Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Lt,
+    Gt,
+    Eq,
+    Lte,
+    Gte,
+    Neq,
+    And,
+    Or,
+}
+
+impl Type {
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Type::Int => "int",
+            Type::Double => "double",
+        }
+    }
+}
+
+impl UnaryOp {
+    pub fn get_symbol(&self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "-",
+            UnaryOp::Not => "not",
+        }
+    }
+}
+
+impl BinaryOp {
+    pub fn get_symbol(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+            BinaryOp::Lt => "<",
+            BinaryOp::Gt => ">",
+            BinaryOp::Eq => "==",
+            BinaryOp::Lte => "<=",
+            BinaryOp::Gte => ">=",
+            BinaryOp::Neq => "<>",
+            BinaryOp::And => "and",
+            BinaryOp::Or => "or",
+        }
+    }
+}
+
+/**
+ * Writes the tree one node per line, indented by depth.
+ */
+struct AstLogger {
+    message: String,
+    depth: usize,
+}
+
+impl AstLogger {
+    fn add_line(&mut self, line: String) {
+        self.message.push_str(&"\t".repeat(self.depth));
+        self.message.push_str(&line);
+        self.message.push('\n');
+    }
+
+    fn add_var_decls(&mut self, title: &str, declarations: &[VarDecl]) {
+        if declarations.is_empty() {
+            return;
+        }
+        self.add_line(String::from(title));
+        self.depth += 1;
+        for decl in declarations {
+            self.visit_var_decl(decl);
+        }
+        self.depth -= 1;
+    }
+
+    fn add_stmts(&mut self, title: &str, statements: &[Stmt]) {
+        self.add_line(String::from(title));
+        self.depth += 1;
+        for stmt in statements {
+            self.visit_stmt(stmt);
+        }
+        self.depth -= 1;
+    }
+}
+
+impl Visitor for AstLogger {
+    fn visit_program(&mut self, program: &Program) {
+        for func in program.functions.iter() {
+            self.visit_func_decl(func);
+        }
+        self.add_var_decls("declarations", &program.declarations);
+        self.add_stmts("statements", &program.statements);
+    }
+
+    fn visit_func_decl(&mut self, func: &FuncDecl) {
+        self.add_line(format!("def {} {}", func.return_type.get_name(), func.name.name));
+        self.depth += 1;
+        self.add_var_decls("params", &func.params);
+        self.add_var_decls("declarations", &func.declarations);
+        self.add_stmts("body", &func.body);
+        self.depth -= 1;
+    }
+
+    fn visit_var_decl(&mut self, decl: &VarDecl) {
+        self.add_line(format!("{} {}", decl.var_type.get_name(), decl.name.name));
+        if let Some(size) = &decl.array_size {
+            self.depth += 1;
+            self.visit_expr(size);
+            self.depth -= 1;
+        }
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::If { condition, then_branch, else_branch, .. } => {
+                self.add_line(String::from("if"));
+                self.depth += 1;
+                self.visit_expr(condition);
+                self.add_stmts("then", then_branch);
+                if let Some(else_branch) = else_branch {
+                    self.add_stmts("else", else_branch);
+                }
+                self.depth -= 1;
+            },
+            Stmt::While { condition, body, .. } => {
+                self.add_line(String::from("while"));
+                self.depth += 1;
+                self.visit_expr(condition);
+                self.add_stmts("do", body);
+                self.depth -= 1;
+            },
+            _ => {
+                self.add_line(String::from(match stmt {
+                    Stmt::Assign { .. } => "=",
+                    Stmt::Print { .. } => "print",
+                    _ => "return",
+                }));
+                self.depth += 1;
+                walk_stmt(self, stmt);
+                self.depth -= 1;
+            },
+        }
+    }
+
+    fn visit_var(&mut self, var: &Var) {
+        self.add_line(var.name.name.clone());
+        self.depth += 1;
+        walk_var(self, var);
+        self.depth -= 1;
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Int(value) => self.add_line(value.to_string()),
+            ExprKind::Double(value) => self.add_line(value.to_string()),
+            ExprKind::Var(_) => {},
+            ExprKind::Call(name, _) => self.add_line(format!("{}()", name.name)),
+            ExprKind::Cast(target, _) => self.add_line(format!("{}()", target.get_name())),
+            ExprKind::Unary(op, _) => self.add_line(String::from(op.get_symbol())),
+            ExprKind::Binary(op, _, _) => self.add_line(String::from(op.get_symbol())),
+        }
+        let is_var = matches!(expr.kind, ExprKind::Var(_));
+        if !is_var {
+            self.depth += 1;
+        }
+        walk_expr(self, expr);
+        if !is_var {
+            self.depth -= 1;
+        }
+    }
+}
+
+impl Loggable for Program {
+    fn to_log_message(&self) -> String {
+        let mut logger = AstLogger {
+            message: String::new(),
+            depth: 0,
+        };
+        logger.visit_program(self);
+        logger.message
+    }
+}
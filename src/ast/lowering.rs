@@ -0,0 +1,355 @@
+use crate::{
+    lexical_analysis::Token,
+    source_map::Span,
+    syntax_semantic_analysis::{
+        non_terminals::NonTerminal,
+        parse_tree::{ParseSymbol, ParseTree},
+    },
+};
+
+use super::{BinaryOp, Expr, ExprKind, FuncDecl, Ident, Program, Stmt, Type, UnaryOp, Var, VarDecl};
+
+/**
+ * Lower the parse tree of a program to its AST.
+ * The tree must come from a successful parse: a node that doesn't match the grammar is a bug and panics.
+ *
+ * The grammar is right recursive, but binary operators are left associative in the AST (`a - b - c` is `(a - b) - c`).
+ * Empty statements and parentheses are left out.
+ */
+pub fn lower_parse_tree(tree: &ParseTree) -> Program {
+    let lowering = Lowering { tree };
+    lowering.lower_program(0)
+}
+
+struct Lowering<'a> {
+    tree: &'a ParseTree,
+}
+
+impl Lowering<'_> {
+    fn children(&self, node: usize) -> &[usize] {
+        &self.tree.get_node(node).children
+    }
+
+    fn span(&self, node: usize) -> Span {
+        self.tree.get_node(node).span
+    }
+
+    fn token(&self, node: usize) -> &Token {
+        match &self.tree.get_node(node).symbol {
+            ParseSymbol::Terminal(token) => token,
+            ParseSymbol::NonTerminal(non_terminal) => panic!("expected a token, found <{}>", non_terminal.get_name()),
+        }
+    }
+
+    fn non_terminal(&self, node: usize) -> Option<NonTerminal> {
+        match &self.tree.get_node(node).symbol {
+            ParseSymbol::NonTerminal(non_terminal) => Some(*non_terminal),
+            ParseSymbol::Terminal(_) => None,
+        }
+    }
+
+    /**
+     * Follow a right recursive list (`<a> ::= <item> <a2>` with `<a2> ::= sep <a> | e`),
+     * calling `f` on every node of the list until it is empty.
+     */
+    fn for_each_in_list(&self, mut node: usize, mut f: impl FnMut(usize)) {
+        loop {
+            let children = self.children(node);
+            match children.len() {
+                0 => return,
+                // <item> <a2>
+                2 if self.non_terminal(children[0]).is_some() => {
+                    f(children[0]);
+                    node = children[1];
+                },
+                // sep <a>
+                _ => node = *children.last().unwrap(),
+            }
+        }
+    }
+
+    fn lower_program(&self, node: usize) -> Program {
+        // <fdecls> <declarations_seq> .
+        let children = self.children(node);
+        let mut functions = Vec::new();
+        let mut fdecls = children[0];
+        while let [fdec, _, next] = self.children(fdecls) {
+            functions.push(self.lower_func_decl(*fdec));
+            fdecls = *next;
+        }
+        let (declarations, statements) = self.lower_declarations_seq(children[1]);
+
+        Program {
+            functions,
+            declarations,
+            statements,
+            span: self.span(node),
+        }
+    }
+
+    fn lower_func_decl(&self, node: usize) -> FuncDecl {
+        // def <type> <fname> ( <params> ) <declarations_seq> fed
+        let children = self.children(node);
+        let mut params = Vec::new();
+        self.for_each_in_list(children[4], |type_var| {
+            let type_var = self.children(type_var);
+            params.push(self.lower_var_decl(type_var[1], self.lower_type(type_var[0])));
+        });
+        let (declarations, body) = self.lower_declarations_seq(children[6]);
+
+        FuncDecl {
+            name: self.lower_id(self.children(children[2])[0]),
+            return_type: self.lower_type(children[1]),
+            params,
+            declarations,
+            body,
+            span: self.span(node),
+        }
+    }
+
+    fn lower_declarations_seq(&self, node: usize) -> (Vec<VarDecl>, Vec<Stmt>) {
+        // <declarations> <statement_seq>
+        let children = self.children(node);
+        let mut declarations = Vec::new();
+        let mut declarations_node = children[0];
+        while let [decl, _, next] = self.children(declarations_node) {
+            // <type> <varlist>
+            let decl = self.children(*decl);
+            let var_type = self.lower_type(decl[0]);
+            self.for_each_in_list(decl[1], |var| declarations.push(self.lower_var_decl(var, var_type)));
+            declarations_node = *next;
+        }
+        (declarations, self.lower_statement_seq(children[1]))
+    }
+
+    fn lower_type(&self, node: usize) -> Type {
+        match self.token(self.children(node)[0]) {
+            Token::Kdouble => Type::Double,
+            _ => Type::Int,
+        }
+    }
+
+    fn lower_var_decl(&self, node: usize, var_type: Type) -> VarDecl {
+        let var = self.lower_var(node);
+        VarDecl {
+            name: var.name,
+            var_type,
+            array_size: var.index.map(|size| *size),
+            span: var.span,
+        }
+    }
+
+    fn lower_statement_seq(&self, node: usize) -> Vec<Stmt> {
+        let mut statements = Vec::new();
+        self.for_each_in_list(node, |statement| {
+            if let Some(statement) = self.lower_statement(statement) {
+                statements.push(statement);
+            }
+        });
+        statements
+    }
+
+    fn lower_statement(&self, node: usize) -> Option<Stmt> {
+        let children = self.children(node);
+        let span = self.span(node);
+        let statement = match children {
+            // Empty statement
+            [] => return None,
+            [var, _, bexpr] => Stmt::Assign {
+                target: self.lower_var(*var),
+                value: self.lower_expr(*bexpr),
+                span,
+            },
+            [if_node] => {
+                // if <bexpr> then <statement_seq> <else> fi
+                let children = self.children(*if_node);
+                let else_branch = match self.children(children[4]) {
+                    [_, statement_seq] => Some(self.lower_statement_seq(*statement_seq)),
+                    _ => None,
+                };
+                Stmt::If {
+                    condition: self.lower_expr(children[1]),
+                    then_branch: self.lower_statement_seq(children[3]),
+                    else_branch,
+                    span,
+                }
+            },
+            // while <bexpr> do <statement_seq> od
+            [_, bexpr, _, statement_seq, _] => Stmt::While {
+                condition: self.lower_expr(*bexpr),
+                body: self.lower_statement_seq(*statement_seq),
+                span,
+            },
+            [built_in, bexpr] => {
+                let value = self.lower_expr(*bexpr);
+                match self.token(self.children(*built_in)[0]) {
+                    Token::Kprint => Stmt::Print { value, span },
+                    _ => Stmt::Return { value, span },
+                }
+            },
+            _ => panic!("invalid <statement> in the parse tree"),
+        };
+        Some(statement)
+    }
+
+    fn lower_var(&self, node: usize) -> Var {
+        // <id> <var2>
+        let children = self.children(node);
+        let index = match self.children(children[1]) {
+            [_, bexpr, _] => Some(Box::new(self.lower_expr(*bexpr))),
+            _ => None,
+        };
+        Var {
+            name: self.lower_id(children[0]),
+            index,
+            span: self.span(node),
+        }
+    }
+
+    fn lower_id(&self, node: usize) -> Ident {
+        let identifier = self.children(node)[0];
+        match self.token(identifier) {
+            Token::Identifier(name) => Ident {
+                name: name.clone(),
+                span: self.span(identifier),
+            },
+            token => panic!("expected an identifier, found `{}`", token),
+        }
+    }
+
+    /**
+     * Lower any expression node, from `<bexpr>` down to `<factor>`.
+     */
+    fn lower_expr(&self, node: usize) -> Expr {
+        let non_terminal = self.non_terminal(node).unwrap();
+        let children = self.children(node);
+        match non_terminal {
+            NonTerminal::Bexpr | NonTerminal::Bterm | NonTerminal::Expr | NonTerminal::Term => {
+                // The first operand, then a chain of `op <same non terminal>` that is folded to the left
+                let mut expr = self.lower_expr(children[0]);
+                let mut rest = children[1];
+                while let [op, operand] = self.children(rest) {
+                    // <same non terminal> ::= <operand> <rest>
+                    let operand_children = self.children(*operand);
+                    let right = self.lower_expr(operand_children[0]);
+                    expr = self.binary(self.lower_binary_op(*op), expr, right);
+                    rest = operand_children[1];
+                }
+                expr
+            },
+            NonTerminal::Bfactor => {
+                // not <bfactor>
+                if self.non_terminal(children[0]).is_none() {
+                    return self.unary(UnaryOp::Not, self.lower_expr(children[1]), node);
+                }
+                // <expr> <bfactor2>
+                let left = self.lower_expr(children[0]);
+                match self.children(children[1]) {
+                    [comp, expr] => self.binary(self.lower_binary_op(*comp), left, self.lower_expr(*expr)),
+                    _ => left,
+                }
+            },
+            NonTerminal::NegFactor => match children {
+                [_, factor] => self.unary(UnaryOp::Neg, self.lower_expr(*factor), node),
+                _ => self.lower_expr(children[0]),
+            },
+            NonTerminal::Factor => self.lower_factor(node),
+            _ => panic!("expected an expression, found <{}>", non_terminal.get_name()),
+        }
+    }
+
+    fn lower_factor(&self, node: usize) -> Expr {
+        let children = self.children(node);
+        let span = self.span(node);
+        match children {
+            // int ( <bexpr> ) or double ( <bexpr> )
+            [type_keyword, _, bexpr, _] => {
+                let target = match self.token(*type_keyword) {
+                    Token::Kdouble => Type::Double,
+                    _ => Type::Int,
+                };
+                Expr {
+                    kind: ExprKind::Cast(target, Box::new(self.lower_expr(*bexpr))),
+                    span,
+                }
+            },
+            // ( <bexpr> )
+            [_, bexpr, _] => Expr {
+                span,
+                ..self.lower_expr(*bexpr)
+            },
+            // <id> <factor2>
+            [id, factor2] => {
+                let name = self.lower_id(*id);
+                let kind = match self.children(*factor2) {
+                    // ( <exprseq> )
+                    [_, exprseq, _] => {
+                        let mut args = Vec::new();
+                        self.for_each_in_list(*exprseq, |bexpr| args.push(self.lower_expr(bexpr)));
+                        ExprKind::Call(name, args)
+                    },
+                    // <var2>
+                    [var2] => {
+                        let index = match self.children(*var2) {
+                            [_, bexpr, _] => Some(Box::new(self.lower_expr(*bexpr))),
+                            _ => None,
+                        };
+                        ExprKind::Var(Var { name, index, span })
+                    },
+                    _ => panic!("invalid <factor2> in the parse tree"),
+                };
+                Expr { kind, span }
+            },
+            // <number>
+            [number] => {
+                let kind = match self.token(self.children(*number)[0]) {
+                    Token::Tint(value) => ExprKind::Int(*value),
+                    Token::Tdouble(value) => ExprKind::Double(*value),
+                    token => panic!("expected a number, found `{}`", token),
+                };
+                Expr { kind, span }
+            },
+            _ => panic!("invalid <factor> in the parse tree"),
+        }
+    }
+
+    /**
+     * Get the operator of an operator token, or of a `<comp>` node.
+     */
+    fn lower_binary_op(&self, node: usize) -> BinaryOp {
+        let token = match self.non_terminal(node) {
+            Some(_) => self.token(self.children(node)[0]),
+            None => self.token(node),
+        };
+        match token {
+            Token::Oplus => BinaryOp::Add,
+            Token::Ominus => BinaryOp::Sub,
+            Token::Omultiply => BinaryOp::Mul,
+            Token::Odivide => BinaryOp::Div,
+            Token::Omod => BinaryOp::Mod,
+            Token::Olt => BinaryOp::Lt,
+            Token::Ogt => BinaryOp::Gt,
+            Token::Oequal => BinaryOp::Eq,
+            Token::Olte => BinaryOp::Lte,
+            Token::Ogte => BinaryOp::Gte,
+            Token::Onot => BinaryOp::Neq,
+            Token::Kand => BinaryOp::And,
+            Token::Kor => BinaryOp::Or,
+            token => panic!("expected a binary operator, found `{}`", token),
+        }
+    }
+
+    fn binary(&self, op: BinaryOp, left: Expr, right: Expr) -> Expr {
+        Expr {
+            span: left.span.to(&right.span),
+            kind: ExprKind::Binary(op, Box::new(left), Box::new(right)),
+        }
+    }
+
+    fn unary(&self, op: UnaryOp, operand: Expr, node: usize) -> Expr {
+        Expr {
+            span: self.span(node),
+            kind: ExprKind::Unary(op, Box::new(operand)),
+        }
+    }
+}
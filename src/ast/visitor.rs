@@ -0,0 +1,109 @@
+use super::{Expr, ExprKind, FuncDecl, Program, Stmt, Var, VarDecl};
+
+/**
+ * Traverse an AST. Every method visits the children of its node by default, through the matching `walk_*` function,
+ * so an implementation only overrides the nodes it cares about (calling `walk_*` itself to keep going deeper).
+ */
+pub trait Visitor {
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    fn visit_func_decl(&mut self, func: &FuncDecl) {
+        walk_func_decl(self, func);
+    }
+
+    fn visit_var_decl(&mut self, decl: &VarDecl) {
+        walk_var_decl(self, decl);
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_var(&mut self, var: &Var) {
+        walk_var(self, var);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for func in program.functions.iter() {
+        visitor.visit_func_decl(func);
+    }
+    for decl in program.declarations.iter() {
+        visitor.visit_var_decl(decl);
+    }
+    for stmt in program.statements.iter() {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_func_decl<V: Visitor + ?Sized>(visitor: &mut V, func: &FuncDecl) {
+    for param in func.params.iter() {
+        visitor.visit_var_decl(param);
+    }
+    for decl in func.declarations.iter() {
+        visitor.visit_var_decl(decl);
+    }
+    for stmt in func.body.iter() {
+        visitor.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_var_decl<V: Visitor + ?Sized>(visitor: &mut V, decl: &VarDecl) {
+    if let Some(size) = &decl.array_size {
+        visitor.visit_expr(size);
+    }
+}
+
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match stmt {
+        Stmt::Assign { target, value, .. } => {
+            visitor.visit_var(target);
+            visitor.visit_expr(value);
+        },
+        Stmt::If { condition, then_branch, else_branch, .. } => {
+            visitor.visit_expr(condition);
+            for stmt in then_branch.iter() {
+                visitor.visit_stmt(stmt);
+            }
+            for stmt in else_branch.iter().flatten() {
+                visitor.visit_stmt(stmt);
+            }
+        },
+        Stmt::While { condition, body, .. } => {
+            visitor.visit_expr(condition);
+            for stmt in body.iter() {
+                visitor.visit_stmt(stmt);
+            }
+        },
+        Stmt::Print { value, .. } | Stmt::Return { value, .. } => visitor.visit_expr(value),
+    }
+}
+
+pub fn walk_var<V: Visitor + ?Sized>(visitor: &mut V, var: &Var) {
+    if let Some(index) = &var.index {
+        visitor.visit_expr(index);
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match &expr.kind {
+        ExprKind::Int(_) | ExprKind::Double(_) => {},
+        ExprKind::Var(var) => visitor.visit_var(var),
+        ExprKind::Call(_, args) => {
+            for arg in args.iter() {
+                visitor.visit_expr(arg);
+            }
+        },
+        ExprKind::Cast(_, operand) | ExprKind::Unary(_, operand) => visitor.visit_expr(operand),
+        ExprKind::Binary(_, left, right) => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        },
+    }
+}
@@ -0,0 +1,369 @@
+use std::{fmt::{self, Display, Formatter}, path::Path};
+
+use ezsharp_compiler::{
+    intermediate_code_generation::layout::TargetLayout,
+    syntax_semantic_analysis::{
+        grammar_dump::GrammarFormat,
+        lints::{Lint, LintLevel},
+    },
+};
+
+pub const USAGE: &str = "\
+Usage: ezsharp_compiler [OPTIONS] <FILE>
+       ezsharp_compiler run [OPTIONS] <FILE>
+       ezsharp_compiler --dump-grammar <FORMAT> [-o <PATH>]
+
+Compiles an EZSharp program. Use - as the file to read the program from stdin.
+With run, the program is compiled and then run by the 3-TAC interpreter, printing its output. Only
+errors, warnings and the program's output are printed, and no outputs are written unless asked for
+with --emit.
+A file ending in .tac is read as a 3-TAC program instead of being compiled, along with the frame map
+next to it (the file with a .frames extension) if there is one. It can be run, or written back out.
+
+Options:
+    --emit <KINDS>           Comma separated list of outputs to write: tokens, symbols, ast,
+                             statement-trees, dot, tac, asm
+                             (default is tokens,symbols,tac)
+    --stop-after <PHASE>     Stop after a phase: lexical, syntax, semantic or tac (default is tac)
+    -o, --output <PATH>      Write the 3-TAC program to PATH, relative to the current directory
+                             (default is <log-folder>/o.tac), and its frame map next to it (o.frames)
+    --data-layout <LAYOUT>   Sizes and alignments in bytes of the types the frames are laid out with,
+                             as type=size[:align] pairs (default is int=4:4,double=8:8)
+    --log-folder <PATH>      Folder for the tokens, symbols, ast, dot and error logs (default is logs)
+    --error-format <FORMAT>  How errors are reported: human or json (default is human)
+    --color <WHEN>           Colour errors: auto, always or never (default is auto)
+    -A, --allow <LINTS>      Don't report the warnings of a comma separated list of lints (or all of them)
+    -W, --warn <LINTS>       Report the warnings of the lints as warnings (the default, except for
+                             shadowing and unchecked-array-index)
+    -D, --deny <LINTS>       Report the warnings of the lints as errors
+                             Lints: unreachable-code, uninitialized-variable, unused-variable,
+                             unused-parameter, unused-function, shadowing, constant-condition,
+                             unchecked-array-index, all
+    --dump-grammar <FORMAT>  Write the LL(1) table and the FIRST and FOLLOW sets of the grammar
+                             as csv, markdown or html, to stdout or the -o path, instead of compiling
+    -q, --quiet              Only print errors and warnings
+    -v, --verbose            Print where each output is written
+    -h, --help               Print this message
+    -V, --version            Print the compiler version
+
+Exit codes:
+    0   Success
+    1   Lexical errors
+    2   Syntax errors, in the program or in a .tac file
+    3   Semantic errors
+    4   Internal compiler error
+    5   Runtime error (with run)
+    64  Invalid command-line usage";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EmitKind {
+    Tokens,
+    Symbols,
+    Ast,
+    StatementTrees,
+    Dot,
+    Tac,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Phase {
+    Lexical,
+    Syntax,
+    Semantic,
+    Tac,
+}
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Phase::Lexical => write!(f, "lexical"),
+            Phase::Syntax => write!(f, "syntax"),
+            Phase::Semantic => write!(f, "semantic"),
+            Phase::Tac => write!(f, "tac"),
+        }
+    }
+}
+
+impl Display for EmitKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            EmitKind::Tokens => write!(f, "tokens"),
+            EmitKind::Symbols => write!(f, "symbols"),
+            EmitKind::Ast => write!(f, "ast"),
+            EmitKind::StatementTrees => write!(f, "statement-trees"),
+            EmitKind::Dot => write!(f, "dot"),
+            EmitKind::Tac => write!(f, "tac"),
+        }
+    }
+}
+
+impl EmitKind {
+    /**
+     * Get the phase that produces the output.
+     */
+    pub fn get_phase(&self) -> Phase {
+        match self {
+            EmitKind::Tokens => Phase::Lexical,
+            EmitKind::Symbols | EmitKind::Ast | EmitKind::StatementTrees | EmitKind::Dot => Phase::Semantic,
+            EmitKind::Tac => Phase::Tac,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ErrorFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ColorMode {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+#[derive(Debug)]
+pub struct CliOptions {
+    pub filename: String,
+    pub emit: Vec<EmitKind>,
+    pub stop_after: Phase,
+    pub output_file: Option<String>,
+    pub log_folder: String,
+    pub error_format: ErrorFormat,
+    pub color: ColorMode,
+    pub verbosity: Verbosity,
+    // Whether errors and warnings are logged to the log folder, which they aren't with run
+    pub log_errors: bool,
+    // In the order they were given, so a later option overrides an earlier one
+    pub lint_levels: Vec<(Lint, LintLevel)>,
+    pub target_layout: TargetLayout,
+}
+
+impl CliOptions {
+    pub fn emits(&self, kind: EmitKind) -> bool {
+        self.emit.contains(&kind)
+    }
+
+    /**
+     * Whether errors and warnings are written to their logs, as well as printed.
+     * JSON errors are only printed.
+     */
+    pub fn logs_errors(&self) -> bool {
+        self.log_errors && self.error_format == ErrorFormat::Human
+    }
+
+    /**
+     * Get the path of a log file in the log folder.
+     */
+    pub fn get_log_path(&self, filename: &str) -> String {
+        format!("{}/{}", self.log_folder, filename)
+    }
+
+    pub fn get_output_path(&self) -> String {
+        match &self.output_file {
+            Some(output_file) => output_file.clone(),
+            None => self.get_log_path("o.tac"),
+        }
+    }
+
+    /**
+     * Get the path of the frame map, the output path with its extension replaced by `.frames`.
+     */
+    pub fn get_frame_map_path(&self) -> String {
+        Path::new(&self.get_output_path()).with_extension("frames").to_string_lossy().into_owned()
+    }
+}
+
+pub enum CliCommand {
+    Compile(CliOptions),
+    Run(CliOptions),
+    DumpGrammar(GrammarFormat, Option<String>),
+    Help,
+    Version,
+}
+
+/**
+ * Parse the command-line arguments (without the program name).
+ * Options that take a value accept both `--option value` and `--option=value`.
+ */
+pub fn parse_args<I: Iterator<Item = String>>(args: I) -> Result<CliCommand, String> {
+    let mut args = args.peekable();
+    let run = args.next_if(|arg| arg == "run").is_some();
+
+    let mut filename: Option<String> = None;
+    let mut emit: Option<Vec<EmitKind>> = None;
+    let mut stop_after: Option<Phase> = None;
+    let mut output_file: Option<String> = None;
+    let mut log_folder = String::from("logs");
+    let mut error_format = ErrorFormat::Human;
+    let mut color = ColorMode::Auto;
+    // A program that is run prints its own output
+    let mut verbosity = if run { Verbosity::Quiet } else { Verbosity::Normal };
+    let mut dump_grammar: Option<GrammarFormat> = None;
+    let mut lint_levels: Vec<(Lint, LintLevel)> = Vec::new();
+    let mut target_layout = TargetLayout::new();
+
+    while let Some(arg) = args.next() {
+        // Split --option=value into the option and its value
+        let (option, inline_value) = match arg.split_once('=') {
+            Some((option, value)) if arg.starts_with("--") => (option.to_string(), Some(value.to_string())),
+            _ => (arg.clone(), None),
+        };
+        let mut get_value = || -> Result<String, String> {
+            match inline_value.clone() {
+                Some(value) => Ok(value),
+                None => args.next().ok_or(format!("Missing value for {}", option)),
+            }
+        };
+
+        match option.as_str() {
+            "-h" | "--help" => return Ok(CliCommand::Help),
+            "-V" | "--version" => return Ok(CliCommand::Version),
+            "-q" | "--quiet" => verbosity = Verbosity::Quiet,
+            "-v" | "--verbose" => verbosity = Verbosity::Verbose,
+            "--emit" => emit = Some(parse_emit_kinds(&get_value()?)?),
+            "--stop-after" => stop_after = Some(parse_phase(&get_value()?)?),
+            "-o" | "--output" => output_file = Some(get_value()?),
+            "--log-folder" => log_folder = get_value()?,
+            "--data-layout" => target_layout = TargetLayout::parse(&get_value()?)?,
+            "-A" | "--allow" => lint_levels.extend(parse_lints(&get_value()?, LintLevel::Allow)?),
+            "-W" | "--warn" => lint_levels.extend(parse_lints(&get_value()?, LintLevel::Warn)?),
+            "-D" | "--deny" => lint_levels.extend(parse_lints(&get_value()?, LintLevel::Deny)?),
+            "--error-format" => {
+                error_format = match get_value()?.as_str() {
+                    "human" => ErrorFormat::Human,
+                    "json" => ErrorFormat::Json,
+                    format => return Err(format!("Unknown error format {}, expected human or json", format)),
+                };
+            },
+            "--dump-grammar" => {
+                dump_grammar = Some(match get_value()?.as_str() {
+                    "csv" => GrammarFormat::Csv,
+                    "markdown" | "md" => GrammarFormat::Markdown,
+                    "html" => GrammarFormat::Html,
+                    format => return Err(format!("Unknown grammar format {}, expected csv, markdown or html", format)),
+                });
+            },
+            "--color" => {
+                color = match get_value()?.as_str() {
+                    "auto" => ColorMode::Auto,
+                    "always" => ColorMode::Always,
+                    "never" => ColorMode::Never,
+                    mode => return Err(format!("Unknown color mode {}, expected auto, always or never", mode)),
+                };
+            },
+            _ if option.starts_with('-') && option != "-" => return Err(format!("Unknown option {}", option)),
+            _ => {
+                if filename.is_some() {
+                    return Err(format!("Unexpected argument {}, only one input file can be compiled", arg));
+                }
+                filename = Some(arg);
+            },
+        }
+    }
+
+    // Dumping the grammar doesn't compile anything
+    if let Some(format) = dump_grammar {
+        if run {
+            return Err(String::from("Cannot dump the grammar with run"));
+        }
+        if let Some(filename) = filename {
+            return Err(format!("Unexpected argument {}, --dump-grammar does not compile a file", filename));
+        }
+        return Ok(CliCommand::DumpGrammar(format, output_file));
+    }
+
+    let filename = filename.ok_or("No input file provided")?;
+
+    // A program is compiled to the 3-TAC to be run
+    if run && stop_after.is_some() {
+        return Err(String::from("Cannot use --stop-after with run, the program is compiled up to the 3-TAC to run it"));
+    }
+    let stop_after = stop_after.unwrap_or(Phase::Tac);
+
+    // Only emit the outputs of the phases that are run
+    let emit = match emit {
+        Some(emit) => {
+            if let Some(kind) = emit.iter().find(|kind| kind.get_phase() > stop_after) {
+                return Err(format!("Cannot emit {} when stopping after the {} phase", kind, stop_after));
+            }
+            emit
+        },
+        None if run => Vec::new(),
+        None => [EmitKind::Tokens, EmitKind::Symbols, EmitKind::Tac]
+            .into_iter()
+            .filter(|kind| kind.get_phase() <= stop_after)
+            .collect(),
+    };
+
+    let options = CliOptions {
+        filename,
+        emit,
+        stop_after,
+        output_file,
+        log_folder,
+        error_format,
+        color,
+        verbosity,
+        log_errors: !run,
+        lint_levels,
+        target_layout,
+    };
+    Ok(if run { CliCommand::Run(options) } else { CliCommand::Compile(options) })
+}
+
+fn parse_emit_kinds(kinds: &str) -> Result<Vec<EmitKind>, String> {
+    let mut emit = Vec::new();
+    for kind in kinds.split(',').map(|kind| kind.trim()).filter(|kind| !kind.is_empty()) {
+        let kind = match kind {
+            "tokens" => EmitKind::Tokens,
+            "symbols" => EmitKind::Symbols,
+            "ast" => EmitKind::Ast,
+            "statement-trees" => EmitKind::StatementTrees,
+            "dot" => EmitKind::Dot,
+            "tac" => EmitKind::Tac,
+            "asm" => return Err(String::from("Cannot emit asm, assembly code generation is not supported yet")),
+            kind => return Err(format!("Unknown emit kind {}, expected tokens, symbols, ast, statement-trees, dot, tac or asm", kind)),
+        };
+        if !emit.contains(&kind) {
+            emit.push(kind);
+        }
+    }
+    Ok(emit)
+}
+
+fn parse_lints(lints: &str, level: LintLevel) -> Result<Vec<(Lint, LintLevel)>, String> {
+    let mut lint_levels = Vec::new();
+    for name in lints.split(',').map(|name| name.trim()).filter(|name| !name.is_empty()) {
+        if name == "all" {
+            lint_levels.extend(Lint::ALL.into_iter().map(|lint| (lint, level)));
+            continue;
+        }
+        match Lint::from_name(name) {
+            Some(lint) => lint_levels.push((lint, level)),
+            None => {
+                let names: Vec<&str> = Lint::ALL.iter().map(|lint| lint.get_name()).collect();
+                return Err(format!("Unknown lint {}, expected {} or all", name, names.join(", ")));
+            },
+        }
+    }
+    Ok(lint_levels)
+}
+
+fn parse_phase(phase: &str) -> Result<Phase, String> {
+    match phase {
+        "lexical" => Ok(Phase::Lexical),
+        "syntax" => Ok(Phase::Syntax),
+        "semantic" => Ok(Phase::Semantic),
+        "tac" => Ok(Phase::Tac),
+        phase => Err(format!("Unknown phase {}, expected lexical, syntax, semantic or tac", phase)),
+    }
+}
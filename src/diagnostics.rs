@@ -0,0 +1,6 @@
+mod diagnostic;
+mod json;
+mod renderer;
+
+pub use diagnostic::{Diagnostic, Note, Severity, ToDiagnostic};
+pub use renderer::DiagnosticRenderer;
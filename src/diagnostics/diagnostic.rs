@@ -0,0 +1,81 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::source_map::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/**
+ * Extra information attached to a diagnostic, optionally pointing at another part of the source.
+ */
+#[derive(Debug, Clone)]
+pub struct Note {
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+/**
+ * A compiler message that can be rendered against the source (see `DiagnosticRenderer`).
+ */
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+    pub span: Option<Span>,
+    pub label: Option<String>,
+    pub notes: Vec<Note>,
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &str, message: String, span: Option<Span>) -> Diagnostic {
+        Diagnostic {
+            severity,
+            code: code.to_string(),
+            message,
+            span,
+            label: None,
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    pub fn error(code: &str, message: String, span: Option<Span>) -> Diagnostic {
+        Diagnostic::new(Severity::Error, code, message, span)
+    }
+
+    /**
+     * Set the text shown under the primary span.
+     */
+    pub fn with_label(mut self, label: String) -> Diagnostic {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn with_note(mut self, message: String, span: Option<Span>) -> Diagnostic {
+        self.notes.push(Note { message, span });
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Diagnostic {
+        self.help.push(help);
+        self
+    }
+}
+
+pub trait ToDiagnostic {
+    fn to_diagnostic(&self) -> Diagnostic;
+}
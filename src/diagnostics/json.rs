@@ -0,0 +1,82 @@
+use crate::source_map::{SourceMap, Span};
+
+use super::diagnostic::Diagnostic;
+
+impl Diagnostic {
+    /**
+     * Serialize the diagnostic as a single line JSON object:
+     * ```text
+     * {"severity":"error","code":"E0205","message":"...","file":"test.cp","span":{...},"label":null,
+     *  "related":[{"message":"function declared here","file":"test.cp","span":{...}}],"notes":[],"help":[]}
+     * ```
+     * Spans hold the byte offsets `start` (inclusive) and `end` (exclusive) and the 1-based `line` and `column`.
+     * `related` holds the notes that point at other parts of the source, `notes` the ones that don't.
+     */
+    pub fn to_json(&self, source_map: &SourceMap) -> String {
+        let related = self.notes.iter()
+            .filter_map(|note| note.span.map(|span| format!(
+                "{{\"message\":{},\"file\":{},\"span\":{}}}",
+                json_string(&note.message),
+                json_file(source_map, Some(&span)),
+                json_span(Some(&span)),
+            )))
+            .collect::<Vec<String>>();
+        let notes = self.notes.iter()
+            .filter(|note| note.span.is_none())
+            .map(|note| json_string(&note.message))
+            .collect::<Vec<String>>();
+        let help = self.help.iter()
+            .map(|help| json_string(help))
+            .collect::<Vec<String>>();
+
+        format!(
+            "{{\"severity\":{},\"code\":{},\"message\":{},\"file\":{},\"span\":{},\"label\":{},\"related\":[{}],\"notes\":[{}],\"help\":[{}]}}",
+            json_string(&self.severity.to_string()),
+            json_string(&self.code),
+            json_string(&self.message),
+            json_file(source_map, self.span.as_ref()),
+            json_span(self.span.as_ref()),
+            self.label.as_ref().map(|label| json_string(label)).unwrap_or(String::from("null")),
+            related.join(","),
+            notes.join(","),
+            help.join(","),
+        )
+    }
+}
+
+fn json_file(source_map: &SourceMap, span: Option<&Span>) -> String {
+    match span.and_then(|span| source_map.get_file(span.file_id)) {
+        Some(file) => json_string(&file.name),
+        None => String::from("null"),
+    }
+}
+
+fn json_span(span: Option<&Span>) -> String {
+    match span {
+        Some(span) if span.line > 0 => format!(
+            "{{\"start\":{},\"end\":{},\"line\":{},\"column\":{}}}",
+            span.start,
+            span.end,
+            span.line,
+            span.column,
+        ),
+        _ => String::from("null"),
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
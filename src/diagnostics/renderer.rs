@@ -0,0 +1,168 @@
+use crate::source_map::{SourceMap, Span};
+
+use super::diagnostic::{Diagnostic, Severity};
+
+const TAB_WIDTH: usize = 4;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const RED: &str = "\x1b[1;31m";
+const YELLOW: &str = "\x1b[1;33m";
+const GREEN: &str = "\x1b[1;32m";
+const CYAN: &str = "\x1b[1;36m";
+const BLUE: &str = "\x1b[1;34m";
+
+/**
+ * Renders diagnostics in the style of rustc:
+ * ```text
+ * error[E0205]: wrong number of arguments: Expected 2 parameters, found 1
+ *  --> test.cp:8:5
+ *   |
+ * 8 | x = f(1);
+ *   |     ^^^^
+ *   |
+ * note: function declared here
+ *  --> test.cp:1:9
+ *   |
+ * 1 | def int f(int a, int b)
+ *   |         ^
+ * ```
+ */
+pub struct DiagnosticRenderer<'a> {
+    source_map: &'a SourceMap,
+    color: bool,
+}
+
+impl<'a> DiagnosticRenderer<'a> {
+    pub fn new(source_map: &'a SourceMap, color: bool) -> DiagnosticRenderer<'a> {
+        DiagnosticRenderer {
+            source_map,
+            color,
+        }
+    }
+
+    /**
+     * Render a list of diagnostics separated by blank lines.
+     */
+    pub fn render_all(&self, diagnostics: &[Diagnostic]) -> String {
+        diagnostics.iter()
+            .map(|diagnostic| self.render(diagnostic))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        let gutter_width = self.get_gutter_width(diagnostic);
+        let severity_color = match diagnostic.severity {
+            Severity::Error => RED,
+            Severity::Warning => YELLOW,
+        };
+
+        // Header
+        let mut msg = if diagnostic.code.is_empty() {
+            self.paint(&diagnostic.severity.to_string(), severity_color)
+        } else {
+            self.paint(&format!("{}[{}]", diagnostic.severity, diagnostic.code), severity_color)
+        };
+        msg.push_str(&self.paint(&format!(": {}", diagnostic.message), BOLD));
+        msg.push('\n');
+
+        // Primary snippet
+        if let Some(span) = &diagnostic.span {
+            msg.push_str(&self.render_snippet(span, diagnostic.label.as_deref(), severity_color, gutter_width));
+        }
+
+        // Notes pointing at other parts of the source get their own snippet
+        for note in diagnostic.notes.iter().filter(|note| note.span.is_some()) {
+            msg.push_str(&format!("{}\n", self.paint_gutter("", gutter_width)));
+            msg.push_str(&self.paint("note", GREEN));
+            msg.push_str(&self.paint(&format!(": {}", note.message), BOLD));
+            msg.push('\n');
+            msg.push_str(&self.render_snippet(note.span.as_ref().unwrap(), None, GREEN, gutter_width));
+        }
+
+        // Notes without a location and help messages are listed at the end
+        for note in diagnostic.notes.iter().filter(|note| note.span.is_none()) {
+            msg.push_str(&format!("{} {} {}: {}\n", " ".repeat(gutter_width), self.paint("=", BLUE), self.paint("note", BOLD), note.message));
+        }
+        for help in diagnostic.help.iter() {
+            msg.push_str(&format!("{} {} {}: {}\n", " ".repeat(gutter_width), self.paint("=", BLUE), self.paint("help", CYAN), help));
+        }
+
+        msg
+    }
+
+    fn render_snippet(&self, span: &Span, label: Option<&str>, color: &str, gutter_width: usize) -> String {
+        let file = match self.source_map.get_file(span.file_id) {
+            Some(file) if span.line > 0 => file,
+            _ => return String::new(),
+        };
+
+        let mut msg = format!(
+            "{}{} {}:{}:{}\n",
+            " ".repeat(gutter_width),
+            self.paint("-->", BLUE),
+            file.name,
+            span.line,
+            span.column,
+        );
+
+        let line = match file.get_line(span.line) {
+            Some(line) => line,
+            None => return msg,
+        };
+
+        // Only the part of the span on its first line is underlined
+        let line_start = file.get_line_start(span.line).unwrap_or(0);
+        let start = usize::min(span.start.saturating_sub(line_start), line.len());
+        let end = usize::min(span.end.saturating_sub(line_start), line.len());
+        let (prefix, underlined) = match (line.get(..start), line.get(start..end)) {
+            (Some(prefix), Some(underlined)) => (display_width(prefix), display_width(underlined)),
+            _ => (span.column - 1, 1),
+        };
+
+        let mut underline = " ".repeat(prefix) + &"^".repeat(usize::max(underlined, 1));
+        if let Some(label) = label {
+            underline.push(' ');
+            underline.push_str(label);
+        }
+
+        msg.push_str(&format!("{}\n", self.paint_gutter("", gutter_width)));
+        msg.push_str(&format!("{} {}\n", self.paint_gutter(&span.line.to_string(), gutter_width), expand_tabs(line)));
+        msg.push_str(&format!("{} {}\n", self.paint_gutter("", gutter_width), self.paint(&underline, color)));
+        msg
+    }
+
+    /**
+     * Get the width needed to print the largest line number of the diagnostic.
+     */
+    fn get_gutter_width(&self, diagnostic: &Diagnostic) -> usize {
+        let max_line = diagnostic.notes.iter()
+            .filter_map(|note| note.span.as_ref())
+            .chain(diagnostic.span.as_ref())
+            .map(|span| span.line)
+            .max()
+            .unwrap_or(0);
+        max_line.to_string().len()
+    }
+
+    fn paint_gutter(&self, line_number: &str, gutter_width: usize) -> String {
+        self.paint(&format!("{:>width$} |", line_number, width = gutter_width), BLUE)
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if self.color {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+fn expand_tabs(text: &str) -> String {
+    text.replace('\t', &" ".repeat(TAB_WIDTH))
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c == '\t' { TAB_WIDTH } else { 1 }).sum()
+}
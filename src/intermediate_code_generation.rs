@@ -1,7 +1,11 @@
-mod tac;
+pub mod layout;
+pub mod tac;
+pub mod tac_parser;
 mod tac_program_builder;
 
+use crate::source_map::Span;
 use crate::syntax_semantic_analysis::{
+    semantic_analysis::SemanticError,
     symbol_declaration::{
         to_var_name,
         BasicType
@@ -12,14 +16,24 @@ use crate::syntax_semantic_analysis::{
         SymbolTable
     }
 };
+use layout::{FrameMap, TargetLayout};
 use tac::TacProgram;
 
 use self::tac_program_builder::TacProgramBuilder;
 
-pub fn perform_intermediate_code_generation(table: SymbolTable) -> TacProgram {
-    let mut program_builder = TacProgramBuilder::new();
+/**
+ * Generate the 3-TAC of the program, along with the layout of each function's frame for the target
+ * and the span of the source each statement was generated from (a default span for the statements that
+ * only structure the program, like `BeginFunc`).
+ * Fails if a frame grows past `u32::MAX` bytes once its temporaries are added.
+ */
+pub fn perform_intermediate_code_generation(
+    table: &SymbolTable,
+    target: &TargetLayout,
+) -> Result<(TacProgram, FrameMap, Vec<Span>), Vec<SemanticError>> {
+    let mut program_builder = TacProgramBuilder::new(target.clone());
 
-    generate_scope_code(0, &table, &mut program_builder);
+    generate_scope_code(0, table, &mut program_builder);
 
     program_builder.get_program()
 }
@@ -43,6 +57,8 @@ fn generate_scope_code(curr_scope: usize, table: &SymbolTable, program_builder:
                         generate_scope_code(func_info.body_scope, table, program_builder);
                         // End function
                         program_builder.reset_curr_func();
+                    } else {
+                        program_builder.add_local(decl);
                     }
                 }
             },
@@ -55,17 +71,18 @@ fn generate_scope_code(curr_scope: usize, table: &SymbolTable, program_builder:
                 generate_scope_code(*new_scope, table, program_builder);
             },
             SymbolEntry::BuiltInFunction(builtin_func) => {
+                program_builder.set_span(builtin_func.span);
                 program_builder.add_builtin_func(builtin_func, curr_scope);
             },
             SymbolEntry::Assignment(assignment_info) => {
+                program_builder.set_span(assignment_info.span);
                 if let Some(index) = &assignment_info.index {
                     if let Some(arr) = table.find_decl_by_id(&assignment_info.var) {
-                        if let BasicType::Array(_, size) = &arr.var_type {
+                        if let BasicType::Array(..) = &arr.var_type {
                             program_builder.add_array_assignment(
                                 to_var_name(&assignment_info.var),
                                 index,
                                 &assignment_info.assignment,
-                                *size,
                                 curr_scope
                             );
                         }
@@ -0,0 +1,236 @@
+use crate::{logger::Loggable, syntax_semantic_analysis::symbol_declaration::BasicType};
+
+/**
+ * The largest frame the semantic analysis lets through, in bytes.
+ * Kept well under `u32::MAX` so that the temporaries added by the code generation still fit.
+ */
+pub const MAX_FRAME_SIZE: u32 = i32::MAX as u32;
+
+/**
+ * The size and alignment of a type, in bytes.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DataLayout {
+    pub size: u32,
+    pub align: u32,
+}
+
+/**
+ * The sizes and alignments of the types of the target machine.
+ * By default an int is 4 bytes and a double 8, each aligned to its size.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetLayout {
+    pub int: DataLayout,
+    pub double: DataLayout,
+}
+
+impl Default for TargetLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TargetLayout {
+    pub fn new() -> TargetLayout {
+        TargetLayout {
+            int: DataLayout { size: 4, align: 4 },
+            double: DataLayout { size: 8, align: 8 },
+        }
+    }
+
+    /**
+     * Parse a comma separated list of `type=size` or `type=size:align` pairs, e.g. `int=4,double=8:4`.
+     * The types left out keep their default layout, and the alignment defaults to the size.
+     */
+    pub fn parse(spec: &str) -> Result<TargetLayout, String> {
+        let mut layout = TargetLayout::new();
+        for pair in spec.split(',').map(|pair| pair.trim()).filter(|pair| !pair.is_empty()) {
+            let (type_name, data_layout) = pair.split_once('=')
+                .ok_or(format!("Invalid data layout {}, expected type=size or type=size:align", pair))?;
+            let (size, align) = match data_layout.split_once(':') {
+                Some((size, align)) => (parse_bytes(size)?, parse_bytes(align)?),
+                None => (parse_bytes(data_layout)?, parse_bytes(data_layout)?),
+            };
+            if !align.is_power_of_two() {
+                return Err(format!("Invalid alignment {}, expected a power of two", align));
+            }
+
+            let data_layout = DataLayout { size, align };
+            match type_name.trim() {
+                "int" => layout.int = data_layout,
+                "double" => layout.double = data_layout,
+                type_name => return Err(format!("Unknown type {}, expected int or double", type_name)),
+            }
+        }
+        Ok(layout)
+    }
+
+    /**
+     * Get the number of bytes a value of the type takes, all the elements for an array.
+     * None if an array is too large for its size to fit in a u32.
+     */
+    pub fn get_size(&self, var_type: &BasicType) -> Option<u32> {
+        match var_type {
+            BasicType::Int => Some(self.int.size),
+            BasicType::Double => Some(self.double.size),
+            BasicType::Array(element_type, length) => self.get_size(element_type)?.checked_mul(*length),
+            BasicType::Function(_) => Some(0),
+        }
+    }
+
+    /**
+     * Get the alignment of a value of the type, that of its elements for an array.
+     */
+    pub fn get_alignment(&self, var_type: &BasicType) -> u32 {
+        match var_type {
+            BasicType::Int => self.int.align,
+            BasicType::Double => self.double.align,
+            BasicType::Array(element_type, _) => self.get_alignment(element_type),
+            BasicType::Function(_) => 1,
+        }
+    }
+}
+
+impl Loggable for TargetLayout {
+    fn to_log_message(&self) -> String {
+        format!("int={}:{},double={}:{}", self.int.size, self.int.align, self.double.size, self.double.align)
+    }
+}
+
+fn parse_bytes(bytes: &str) -> Result<u32, String> {
+    match bytes.trim().parse::<u32>() {
+        Ok(bytes) if bytes > 0 => Ok(bytes),
+        _ => Err(format!("Invalid number of bytes {}, expected a positive integer", bytes)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlotKind {
+    Parameter,
+    Local,
+    Array,
+    Temporary,
+}
+
+impl Loggable for SlotKind {
+    fn to_log_message(&self) -> String {
+        match self {
+            SlotKind::Parameter => String::from("param"),
+            SlotKind::Local => String::from("local"),
+            SlotKind::Array => String::from("array"),
+            SlotKind::Temporary => String::from("temp"),
+        }
+    }
+}
+
+/**
+ * A variable of a function's frame, at `offset` bytes from the start of the frame.
+ * `source_name` is the identifier the variable was declared with, which is its 3-TAC name for temporaries
+ * and for frames read from 3-TAC.
+ */
+#[derive(Debug, Clone)]
+pub struct FrameSlot {
+    pub name: String,
+    pub source_name: String,
+    pub kind: SlotKind,
+    pub var_type: BasicType,
+    pub offset: u32,
+    pub size: u32,
+}
+
+/**
+ * Where the parameters, local variables, arrays and temporaries of a function are stored in its frame.
+ * Slots are laid out in the order they are added, each aligned for its type, and the frame's size is
+ * rounded up to the largest alignment so that frames can be stacked.
+ */
+#[derive(Debug, Clone)]
+pub struct FrameLayout {
+    pub function: String,
+    pub slots: Vec<FrameSlot>,
+    pub size: u32,
+    pub align: u32,
+    // Where the last slot ends, before the frame is padded
+    end: u32,
+}
+
+impl FrameLayout {
+    pub fn new(function: String) -> FrameLayout {
+        FrameLayout {
+            function,
+            slots: Vec::new(),
+            size: 0,
+            align: 1,
+            end: 0,
+        }
+    }
+
+    /**
+     * Add a slot at the end of the frame.
+     * Returns its offset, or None (leaving the frame as it was) if the frame would grow past `u32::MAX` bytes.
+     */
+    pub fn add_slot(&mut self, name: String, kind: SlotKind, var_type: BasicType, target: &TargetLayout) -> Option<u32> {
+        let source_name = name.clone();
+        self.add_slot_within(name, source_name, kind, var_type, target, u32::MAX)
+    }
+
+    /**
+     * Add a slot at the end of the frame, as long as the frame stays within `max_size` bytes.
+     * Returns its offset, or None (leaving the frame as it was) if the slot doesn't fit.
+     */
+    pub fn add_slot_within(&mut self, name: String, source_name: String, kind: SlotKind, var_type: BasicType, target: &TargetLayout, max_size: u32) -> Option<u32> {
+        let size = target.get_size(&var_type)?;
+        let align = target.get_alignment(&var_type);
+        let offset = self.end.checked_next_multiple_of(align)?;
+        let end = offset.checked_add(size)?;
+        let frame_align = self.align.max(align);
+        let frame_size = end.checked_next_multiple_of(frame_align).filter(|frame_size| *frame_size <= max_size)?;
+
+        self.slots.push(FrameSlot { name, source_name, kind, var_type, offset, size });
+        self.end = end;
+        self.align = frame_align;
+        self.size = frame_size;
+        Some(offset)
+    }
+
+    /**
+     * Add a slot whose offset is already known, e.g. one read from a frame map.
+     * The size and alignment of the frame are left as they are.
+     */
+    pub fn insert_slot(&mut self, slot: FrameSlot) {
+        self.end = self.end.max(slot.offset.saturating_add(slot.size));
+        self.slots.push(slot);
+    }
+
+    pub fn get_slot(&self, name: &str) -> Option<&FrameSlot> {
+        self.slots.iter().find(|slot| slot.name == name)
+    }
+}
+
+impl Loggable for FrameLayout {
+    fn to_log_message(&self) -> String {
+        let mut message = format!("frame {} size {} align {}\n", self.function, self.size, self.align);
+        for slot in self.slots.iter() {
+            message.push_str(&format!(
+                "\t{} {} offset {} size {} type {}\n",
+                slot.kind.to_log_message(),
+                slot.name,
+                slot.offset,
+                slot.size,
+                slot.var_type.to_log_message()
+            ));
+        }
+        message
+    }
+}
+
+/**
+ * The frames of every function of a program, in the order of the functions in its 3-TAC.
+ */
+pub type FrameMap = Vec<FrameLayout>;
+
+impl Loggable for FrameMap {
+    fn to_log_message(&self) -> String {
+        self.iter().map(|frame| frame.to_log_message()).collect::<Vec<String>>().join("\n")
+    }
+}
@@ -1,7 +1,8 @@
 use crate::lexical_analysis::Token;
 use crate::logger::Loggable;
+use crate::syntax_semantic_analysis::symbol_declaration::BasicType;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TacCommand {
     BeginFunc,
     EndFunc,
@@ -44,10 +45,10 @@ impl Loggable for TacCommand {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TacValue {
     Label(String),
-    Var(String),
+    Var(String, BasicType),
     Int(u32),
     Double(f64),
     PointerAccess(String, Box<TacValue>),
@@ -57,16 +58,17 @@ pub enum TacValue {
 }
 
 impl Loggable for TacValue {
+    // Doubles always have a decimal point (or an exponent), e.g. `0.0`, to be told apart from ints
     fn to_log_message(&self) -> String {
         match self {
-            TacValue::Label(val) | TacValue::Var(val) => {
+            TacValue::Label(val) | TacValue::Var(val, _) => {
                 val.clone()
             },
             TacValue::Int(int) => {
                 int.to_string()
             },
             TacValue::Double(double) => {
-                double.to_string()
+                format!("{:?}", double)
             },
             TacValue::PointerAccess(arr, index) => {
                 format!("*({} + {})", arr, index.to_log_message())
@@ -85,24 +87,209 @@ impl Loggable for TacValue {
 
 }
 
-#[derive(Debug, Clone)]
+impl TacValue {
+    /**
+     * Get the type of a variable or a literal.
+     */
+    pub fn get_type(&self) -> Option<BasicType> {
+        match self {
+            TacValue::Var(_, var_type) => Some(var_type.clone()),
+            TacValue::Int(_) => Some(BasicType::Int),
+            TacValue::Double(_) => Some(BasicType::Double),
+            _ => None,
+        }
+    }
+}
+
+/**
+ * An operator of the 3-TAC, for the operands of a single type.
+ * Comparisons give an int, 1 if true and 0 otherwise.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TacOperator {
+    IntAdd,
+    IntSub,
+    IntMul,
+    IntDiv,
+    IntMod,
+    IntEqual,
+    IntNotEqual,
+    IntLess,
+    IntGreater,
+    IntLessEqual,
+    IntGreaterEqual,
+    DoubleAdd,
+    DoubleSub,
+    DoubleMul,
+    DoubleDiv,
+    DoubleMod,
+    DoubleEqual,
+    DoubleNotEqual,
+    DoubleLess,
+    DoubleGreater,
+    DoubleLessEqual,
+    DoubleGreaterEqual,
+    IntToDouble,
+    DoubleToInt,
+}
+
+impl TacOperator {
+    /**
+     * Get the operator of a binary operator token, for operands of the type.
+     * Returns `None` for the tokens that aren't arithmetic or comparison operators, and for non numeric types.
+     */
+    pub fn from_token(token: &Token, operand_type: &BasicType) -> Option<TacOperator> {
+        let op = match (token, operand_type) {
+            (Token::Oplus, BasicType::Int) => TacOperator::IntAdd,
+            (Token::Ominus, BasicType::Int) => TacOperator::IntSub,
+            (Token::Omultiply, BasicType::Int) => TacOperator::IntMul,
+            (Token::Odivide, BasicType::Int) => TacOperator::IntDiv,
+            (Token::Omod, BasicType::Int) => TacOperator::IntMod,
+            (Token::Oequal, BasicType::Int) => TacOperator::IntEqual,
+            (Token::Onot, BasicType::Int) => TacOperator::IntNotEqual,
+            (Token::Olt, BasicType::Int) => TacOperator::IntLess,
+            (Token::Ogt, BasicType::Int) => TacOperator::IntGreater,
+            (Token::Olte, BasicType::Int) => TacOperator::IntLessEqual,
+            (Token::Ogte, BasicType::Int) => TacOperator::IntGreaterEqual,
+            (Token::Oplus, BasicType::Double) => TacOperator::DoubleAdd,
+            (Token::Ominus, BasicType::Double) => TacOperator::DoubleSub,
+            (Token::Omultiply, BasicType::Double) => TacOperator::DoubleMul,
+            (Token::Odivide, BasicType::Double) => TacOperator::DoubleDiv,
+            (Token::Omod, BasicType::Double) => TacOperator::DoubleMod,
+            (Token::Oequal, BasicType::Double) => TacOperator::DoubleEqual,
+            (Token::Onot, BasicType::Double) => TacOperator::DoubleNotEqual,
+            (Token::Olt, BasicType::Double) => TacOperator::DoubleLess,
+            (Token::Ogt, BasicType::Double) => TacOperator::DoubleGreater,
+            (Token::Olte, BasicType::Double) => TacOperator::DoubleLessEqual,
+            (Token::Ogte, BasicType::Double) => TacOperator::DoubleGreaterEqual,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    /**
+     * Get the conversion from a type to another, or `None` if there is nothing to convert.
+     */
+    pub fn get_conversion(from: &BasicType, to: &BasicType) -> Option<TacOperator> {
+        match (from, to) {
+            (BasicType::Int, BasicType::Double) => Some(TacOperator::IntToDouble),
+            (BasicType::Double, BasicType::Int) => Some(TacOperator::DoubleToInt),
+            _ => None,
+        }
+    }
+
+    pub fn is_conversion(&self) -> bool {
+        matches!(self, TacOperator::IntToDouble | TacOperator::DoubleToInt)
+    }
+
+    pub fn get_operand_type(&self) -> BasicType {
+        match self {
+            TacOperator::IntAdd |
+            TacOperator::IntSub |
+            TacOperator::IntMul |
+            TacOperator::IntDiv |
+            TacOperator::IntMod |
+            TacOperator::IntEqual |
+            TacOperator::IntNotEqual |
+            TacOperator::IntLess |
+            TacOperator::IntGreater |
+            TacOperator::IntLessEqual |
+            TacOperator::IntGreaterEqual |
+            TacOperator::IntToDouble => BasicType::Int,
+            _ => BasicType::Double,
+        }
+    }
+
+    pub fn get_result_type(&self) -> BasicType {
+        match self {
+            TacOperator::DoubleAdd |
+            TacOperator::DoubleSub |
+            TacOperator::DoubleMul |
+            TacOperator::DoubleDiv |
+            TacOperator::DoubleMod |
+            TacOperator::IntToDouble => BasicType::Double,
+            _ => BasicType::Int,
+        }
+    }
+}
+
+impl Loggable for TacOperator {
+    // Double operators are followed by a period, e.g. `+.`
+    fn to_log_message(&self) -> String {
+        let op = match self {
+            TacOperator::IntAdd | TacOperator::DoubleAdd => "+",
+            TacOperator::IntSub | TacOperator::DoubleSub => "-",
+            TacOperator::IntMul | TacOperator::DoubleMul => "*",
+            TacOperator::IntDiv | TacOperator::DoubleDiv => "/",
+            TacOperator::IntMod | TacOperator::DoubleMod => "%",
+            TacOperator::IntEqual | TacOperator::DoubleEqual => "==",
+            TacOperator::IntNotEqual | TacOperator::DoubleNotEqual => "<>",
+            TacOperator::IntLess | TacOperator::DoubleLess => "<",
+            TacOperator::IntGreater | TacOperator::DoubleGreater => ">",
+            TacOperator::IntLessEqual | TacOperator::DoubleLessEqual => "<=",
+            TacOperator::IntGreaterEqual | TacOperator::DoubleGreaterEqual => ">=",
+            TacOperator::IntToDouble => return String::from("(double)"),
+            TacOperator::DoubleToInt => return String::from("(int)"),
+        };
+        match self.get_operand_type() {
+            BasicType::Double => format!("{}.", op),
+            _ => String::from(op),
+        }
+    }
+}
+
+/**
+ * A value, a conversion or a binary operation, with the type of its result.
+ */
+#[derive(Debug, Clone, PartialEq)]
 pub struct TacOperation {
-    pub op: Option<Token>,
+    pub op: Option<TacOperator>,
     pub val1: TacValue,
     pub val2: Option<TacValue>,
+    pub op_type: BasicType,
+}
+
+impl TacOperation {
+    pub fn value(val: TacValue, op_type: BasicType) -> TacOperation {
+        TacOperation {
+            op: None,
+            val1: val,
+            val2: None,
+            op_type,
+        }
+    }
+
+    pub fn conversion(op: TacOperator, val: TacValue) -> TacOperation {
+        TacOperation {
+            op: Some(op),
+            val1: val,
+            val2: None,
+            op_type: op.get_result_type(),
+        }
+    }
+
+    pub fn binary(op: TacOperator, val1: TacValue, val2: TacValue) -> TacOperation {
+        TacOperation {
+            op: Some(op),
+            val1,
+            val2: Some(val2),
+            op_type: op.get_result_type(),
+        }
+    }
 }
 
 impl Loggable for TacOperation {
     fn to_log_message(&self) -> String {
-        let mut log_message = self.val1.to_log_message();
-        if let Some(token) = &self.op {
-            log_message.push_str(&format!(" {} {}", token.to_string(), self.val2.as_ref().unwrap().to_log_message()));
+        match (&self.op, &self.val2) {
+            // A conversion, e.g. `(double) x0`
+            (Some(op), None) => format!("{} {}", op.to_log_message(), self.val1.to_log_message()),
+            (Some(op), Some(val2)) => format!("{} {} {}", self.val1.to_log_message(), op.to_log_message(), val2.to_log_message()),
+            (None, _) => self.val1.to_log_message(),
         }
-        log_message
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TacStatement {
     Label(String),
     Assignment(String, TacOperation),
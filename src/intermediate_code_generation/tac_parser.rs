@@ -0,0 +1,1108 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    diagnostics::{Diagnostic, ToDiagnostic},
+    logger::Loggable,
+    source_map::{FileId, Span},
+    syntax_semantic_analysis::symbol_declaration::BasicType,
+};
+
+use super::layout::{FrameLayout, FrameMap, FrameSlot, SlotKind, TargetLayout};
+use super::tac::{TacCommand, TacOperation, TacOperator, TacProgram, TacStatement, TacValue};
+
+#[derive(Debug)]
+pub enum TacParseErrorType {
+    UnexpectedCharacter(char),
+    Expected {
+        expected: String,
+        found: String,
+    },
+    InvalidNumber(String),
+    UndefinedLabel(String),
+    DuplicateLabel(String),
+    UnknownVariable {
+        var: String,
+        function: String,
+    },
+    MissingFrame(String),
+    UnknownArraySize(String),
+    FrameTooLarge(String),
+}
+
+/**
+ * An error in a 3-TAC program or a frame map that is read back, pointing at the offending token or statement.
+ */
+#[derive(Debug)]
+pub struct TacParseError {
+    error_type: TacParseErrorType,
+    span: Span,
+}
+
+impl TacParseError {
+    pub fn new(error_type: TacParseErrorType, span: Span) -> Self {
+        TacParseError {
+            error_type,
+            span,
+        }
+    }
+
+    pub fn get_error_type(&self) -> &TacParseErrorType {
+        &self.error_type
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    fn get_message(&self) -> String {
+        match &self.error_type {
+            TacParseErrorType::UnexpectedCharacter(c) => format!("unexpected character `{}`", c),
+            TacParseErrorType::Expected { expected, found } => format!("expected {}, found {}", expected, found),
+            TacParseErrorType::InvalidNumber(number) => format!("invalid number `{}`", number),
+            TacParseErrorType::UndefinedLabel(label) => format!("undefined label `{}`", label),
+            TacParseErrorType::DuplicateLabel(label) => format!("label `{}` is defined more than once", label),
+            TacParseErrorType::UnknownVariable { var, function } => format!("`{}` is not in the frame of `{}`", var, function),
+            TacParseErrorType::MissingFrame(function) => format!("no frame for the function `{}`", function),
+            TacParseErrorType::UnknownArraySize(arr) => format!("the size of the array `{}` is unknown", arr),
+            TacParseErrorType::FrameTooLarge(function) => format!("the frame of `{}` is too large", function),
+        }
+    }
+}
+
+impl Loggable for TacParseError {
+    fn to_log_message(&self) -> String {
+        format!("{} on line {}, column {}", self.get_message(), self.span.line, self.span.column)
+    }
+}
+
+impl Loggable for Box<[TacParseError]> {
+    fn to_log_message(&self) -> String {
+        let mut msg = String::new();
+        for error in self.iter() {
+            msg.push_str(&error.to_log_message());
+            msg.push('\n');
+        }
+        msg
+    }
+}
+
+impl ToDiagnostic for TacParseError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = |code: &str| Diagnostic::error(code, self.get_message(), Some(self.span));
+        match &self.error_type {
+            TacParseErrorType::UnexpectedCharacter(_) => diagnostic("E0401"),
+            TacParseErrorType::Expected { expected, .. } => diagnostic("E0402").with_label(format!("expected {}", expected)),
+            TacParseErrorType::InvalidNumber(_) => diagnostic("E0403")
+                .with_help(String::from("ints are unsigned 32-bit integers, doubles have a decimal point, e.g. `2.0`")),
+            TacParseErrorType::UndefinedLabel(_) => diagnostic("E0404").with_label(String::from("jumps to no statement")),
+            TacParseErrorType::DuplicateLabel(_) => diagnostic("E0405").with_label(String::from("defined again here")),
+            TacParseErrorType::UnknownVariable { .. } => diagnostic("E0406")
+                .with_help(String::from("every variable of a function needs a slot in its frame map")),
+            TacParseErrorType::MissingFrame(_) => diagnostic("E0407")
+                .with_help(String::from("every function needs a frame in the frame map")),
+            TacParseErrorType::UnknownArraySize(_) => diagnostic("E0408")
+                .with_help(String::from("the sizes of arrays come from the frame map, which is written next to the 3-TAC")),
+            TacParseErrorType::FrameTooLarge(_) => diagnostic("E0409")
+                .with_label(String::from("does not fit in the frame")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TacToken {
+    Word(String),
+    Number(String),
+    Symbol(String),
+}
+
+impl Loggable for TacToken {
+    fn to_log_message(&self) -> String {
+        match self {
+            TacToken::Word(text) | TacToken::Number(text) | TacToken::Symbol(text) => format!("`{}`", text),
+        }
+    }
+}
+
+/**
+ * Split a line into tokens: words (labels, variables and commands), numbers and symbols.
+ * An operator followed by a period is a single symbol, e.g. `+.`, and `//` starts a comment.
+ */
+fn lex_line(line: &str, file_id: FileId, line_start: usize, line_number: usize) -> Result<Vec<(TacToken, Span)>, TacParseError> {
+    let bytes = line.as_bytes();
+    let span = |start: usize, end: usize| Span::new(file_id, line_start + start, line_start + end, line_number, start + 1);
+    let is_word_char = |c: u8| c.is_ascii_alphanumeric() || c == b'_';
+
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let token = if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        } else if line[i..].starts_with("//") {
+            break;
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && is_word_char(bytes[i]) {
+                i += 1;
+            }
+            TacToken::Word(line[start..i].to_string())
+        } else if c.is_ascii_digit() {
+            // Doubles may have an exponent, e.g. `1e-7`
+            while i < bytes.len() && (is_word_char(bytes[i]) || bytes[i] == b'.'
+                || (matches!(bytes[i], b'+' | b'-') && matches!(bytes[i - 1], b'e' | b'E'))) {
+                i += 1;
+            }
+            TacToken::Number(line[start..i].to_string())
+        } else {
+            let symbol = ["==", "<>", "<=", ">=", "+", "-", "*", "/", "%", "<", ">", "=", "(", ")", "[", "]", ":", ";"]
+                .into_iter()
+                .find(|symbol| line[i..].starts_with(symbol));
+            let symbol = match symbol {
+                Some(symbol) => symbol,
+                None => {
+                    let c = line[i..].chars().next().unwrap();
+                    return Err(TacParseError::new(TacParseErrorType::UnexpectedCharacter(c), span(i, i + c.len_utf8())));
+                },
+            };
+            i += symbol.len();
+            let mut symbol = symbol.to_string();
+            if parse_operator(&format!("{}.", symbol)).is_some() && bytes.get(i) == Some(&b'.') {
+                symbol.push('.');
+                i += 1;
+            }
+            TacToken::Symbol(symbol)
+        };
+        tokens.push((token, span(start, i)));
+    }
+    Ok(tokens)
+}
+
+/**
+ * Get the binary operator written as `op`, the double ones being followed by a period, e.g. `+.`.
+ */
+fn parse_operator(op: &str) -> Option<TacOperator> {
+    let op = match op {
+        "+" => TacOperator::IntAdd,
+        "-" => TacOperator::IntSub,
+        "*" => TacOperator::IntMul,
+        "/" => TacOperator::IntDiv,
+        "%" => TacOperator::IntMod,
+        "==" => TacOperator::IntEqual,
+        "<>" => TacOperator::IntNotEqual,
+        "<" => TacOperator::IntLess,
+        ">" => TacOperator::IntGreater,
+        "<=" => TacOperator::IntLessEqual,
+        ">=" => TacOperator::IntGreaterEqual,
+        "+." => TacOperator::DoubleAdd,
+        "-." => TacOperator::DoubleSub,
+        "*." => TacOperator::DoubleMul,
+        "/." => TacOperator::DoubleDiv,
+        "%." => TacOperator::DoubleMod,
+        "==." => TacOperator::DoubleEqual,
+        "<>." => TacOperator::DoubleNotEqual,
+        "<." => TacOperator::DoubleLess,
+        ">." => TacOperator::DoubleGreater,
+        "<=." => TacOperator::DoubleLessEqual,
+        ">=." => TacOperator::DoubleGreaterEqual,
+        _ => return None,
+    };
+    Some(op)
+}
+
+/**
+ * Parses the tokens of a single line.
+ */
+struct LineParser {
+    tokens: Vec<(TacToken, Span)>,
+    pos: usize,
+    // Where the line ends, for the errors about a missing token
+    end: Span,
+}
+
+impl LineParser {
+    fn new(tokens: Vec<(TacToken, Span)>, end: Span) -> LineParser {
+        LineParser {
+            tokens,
+            pos: 0,
+            end,
+        }
+    }
+
+    /**
+     * Get the span of the tokens parsed so far.
+     */
+    fn get_span(&self) -> Span {
+        let first = self.tokens[0].1;
+        let last = self.tokens[self.pos.max(1) - 1].1;
+        first.to(&last)
+    }
+
+    fn peek(&self) -> Option<&TacToken> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn expected(&self, expected: &str) -> TacParseError {
+        let (found, span) = match self.tokens.get(self.pos) {
+            Some((token, span)) => (token.to_log_message(), *span),
+            None => (String::from("the end of the line"), self.end),
+        };
+        TacParseError::new(
+            TacParseErrorType::Expected {
+                expected: expected.to_string(),
+                found,
+            },
+            span,
+        )
+    }
+
+    fn next_if_symbol(&mut self, symbol: &str) -> bool {
+        let found = matches!(self.peek(), Some(TacToken::Symbol(s)) if s == symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn next_if_word(&mut self, word: &str) -> bool {
+        let found = matches!(self.peek(), Some(TacToken::Word(w)) if w == word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), TacParseError> {
+        match self.next_if_symbol(symbol) {
+            true => Ok(()),
+            false => Err(self.expected(&format!("`{}`", symbol))),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), TacParseError> {
+        match self.next_if_word(keyword) {
+            true => Ok(()),
+            false => Err(self.expected(&format!("`{}`", keyword))),
+        }
+    }
+
+    /**
+     * Parse a label, a variable or a function name.
+     */
+    fn expect_name(&mut self, what: &str) -> Result<String, TacParseError> {
+        match self.peek() {
+            Some(TacToken::Word(word)) => {
+                let word = word.clone();
+                self.pos += 1;
+                Ok(word)
+            },
+            _ => Err(self.expected(what)),
+        }
+    }
+
+    /**
+     * Parse a size or a count, an unsigned int.
+     */
+    fn expect_size(&mut self) -> Result<u32, TacParseError> {
+        let (number, span) = match self.tokens.get(self.pos) {
+            Some((TacToken::Number(number), span)) => (number.clone(), *span),
+            _ => return Err(self.expected("a number")),
+        };
+        self.pos += 1;
+        number.parse::<u32>().map_err(|_| TacParseError::new(TacParseErrorType::InvalidNumber(number), span))
+    }
+
+    fn expect_end(&mut self) -> Result<(), TacParseError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.expected("the end of the line")),
+        }
+    }
+
+    /**
+     * Parse a variable, a literal or an array element, e.g. `*(a0 + t1_)`.
+     * Variables are typed afterwards, once the whole program is known.
+     */
+    fn parse_value(&mut self) -> Result<TacValue, TacParseError> {
+        let (token, span) = match self.tokens.get(self.pos) {
+            Some(token) => token.clone(),
+            None => return Err(self.expected("a value")),
+        };
+        let value = match token {
+            TacToken::Word(var) => TacValue::Var(var, BasicType::Int),
+            TacToken::Number(number) => {
+                let value = if number.contains(['.', 'e', 'E']) {
+                    number.parse::<f64>().ok().filter(|value| value.is_finite()).map(TacValue::Double)
+                } else {
+                    number.parse::<u32>().ok().map(TacValue::Int)
+                };
+                match value {
+                    Some(value) => value,
+                    None => return Err(TacParseError::new(TacParseErrorType::InvalidNumber(number), span)),
+                }
+            },
+            TacToken::Symbol(symbol) if symbol == "*" => {
+                self.pos += 1;
+                let (arr, index) = self.parse_pointer()?;
+                return Ok(TacValue::PointerAccess(arr, Box::new(index)));
+            },
+            TacToken::Symbol(_) => return Err(self.expected("a value")),
+        };
+        self.pos += 1;
+        Ok(value)
+    }
+
+    /**
+     * Parse the `(a0 + t1_)` of an array element.
+     */
+    fn parse_pointer(&mut self) -> Result<(String, TacValue), TacParseError> {
+        self.expect_symbol("(")?;
+        let arr = self.expect_name("an array")?;
+        self.expect_symbol("+")?;
+        let index = self.parse_value()?;
+        self.expect_symbol(")")?;
+        Ok((arr, index))
+    }
+
+    /**
+     * Parse the right side of an assignment.
+     */
+    fn parse_operation(&mut self) -> Result<TacOperation, TacParseError> {
+        if self.next_if_word("GetParams") {
+            return Ok(TacOperation::value(TacValue::GetParams(self.expect_size()?), BasicType::Int));
+        }
+        if self.next_if_word("LCall") {
+            return Ok(TacOperation::value(TacValue::LCallArgs(self.expect_name("a function")?), BasicType::Int));
+        }
+        // A conversion, e.g. `(double) t0_`
+        if self.next_if_symbol("(") {
+            let op = if self.next_if_word("double") {
+                TacOperator::IntToDouble
+            } else if self.next_if_word("int") {
+                TacOperator::DoubleToInt
+            } else {
+                return Err(self.expected("`double` or `int`"));
+            };
+            self.expect_symbol(")")?;
+            return Ok(TacOperation::conversion(op, self.parse_value()?));
+        }
+
+        let val1 = self.parse_value()?;
+        let op = match self.peek() {
+            Some(TacToken::Symbol(symbol)) if symbol != ";" => match parse_operator(symbol) {
+                Some(op) => op,
+                None => return Err(self.expected("an operator or `;`")),
+            },
+            _ => return Ok(TacOperation::value(val1, BasicType::Int)),
+        };
+        self.pos += 1;
+        Ok(TacOperation::binary(op, val1, self.parse_value()?))
+    }
+
+    fn parse_statement(&mut self) -> Result<TacStatement, TacParseError> {
+        // An array element being assigned, e.g. `*(a0 + 1) = t2_;`
+        if self.next_if_symbol("*") {
+            let (arr, index) = self.parse_pointer()?;
+            self.expect_symbol("=")?;
+            let op = self.parse_operation()?;
+            self.expect_symbol(";")?;
+            return Ok(TacStatement::PointerAssignment(arr, index, op));
+        }
+
+        let name = self.expect_name("a statement")?;
+        if self.next_if_symbol(":") {
+            return Ok(TacStatement::Label(name));
+        }
+        if self.next_if_symbol("=") {
+            let op = self.parse_operation()?;
+            self.expect_symbol(";")?;
+            return Ok(TacStatement::Assignment(name, op));
+        }
+
+        let (command, val) = match name.as_str() {
+            "BeginFunc" => (TacCommand::BeginFunc, Some(TacValue::Int(self.expect_size()?))),
+            "EndFunc" => (TacCommand::EndFunc, None),
+            "PushParam" => (TacCommand::PushParam, Some(self.parse_value()?)),
+            "PopParams" => (TacCommand::PopParams, Some(TacValue::Int(self.expect_size()?))),
+            "LCall" => (TacCommand::LCall, Some(TacValue::Label(self.expect_name("a function")?))),
+            "Goto" => (TacCommand::Goto, Some(TacValue::Label(self.expect_name("a label")?))),
+            "IfZ" => {
+                let cond = self.expect_name("a variable")?;
+                self.expect_keyword("Goto")?;
+                (TacCommand::IfZ, Some(TacValue::IfArgs(cond, self.expect_name("a label")?)))
+            },
+            "Return" => match self.peek() {
+                Some(TacToken::Symbol(symbol)) if symbol == ";" => (TacCommand::Return, None),
+                Some(_) => (TacCommand::Return, Some(self.parse_value()?)),
+                None => return Err(self.expected("a value or `;`")),
+            },
+            _ => {
+                self.pos -= 1;
+                return Err(self.expected("a command, a label or an assignment"));
+            },
+        };
+        self.expect_symbol(";")?;
+        Ok(TacStatement::Command(command, val))
+    }
+
+    /**
+     * Parse a type as written in a frame map, e.g. `double` or `[int; 3]`.
+     */
+    fn parse_type(&mut self) -> Result<BasicType, TacParseError> {
+        if self.next_if_word("int") {
+            return Ok(BasicType::Int);
+        }
+        if self.next_if_word("double") {
+            return Ok(BasicType::Double);
+        }
+        if self.next_if_symbol("[") {
+            let element_type = self.parse_type()?;
+            self.expect_symbol(";")?;
+            let length = self.expect_size()?;
+            self.expect_symbol("]")?;
+            return Ok(BasicType::Array(Box::new(element_type), length));
+        }
+        Err(self.expected("`int`, `double` or an array type"))
+    }
+}
+
+/**
+ * Lex every line of a source, calling `parse_line` on the lines with tokens.
+ * Returns the errors of the lines.
+ */
+fn parse_lines<F>(source: &str, file_id: FileId, mut parse_line: F) -> Vec<TacParseError>
+where
+    F: FnMut(&mut LineParser) -> Result<(), TacParseError>,
+{
+    let mut errors = Vec::new();
+    let mut line_start = 0;
+    for (i, line) in source.split('\n').enumerate() {
+        let end = Span::new(file_id, line_start + line.len(), line_start + line.len(), i + 1, line.len() + 1);
+        let result = lex_line(line, file_id, line_start, i + 1).and_then(|tokens| {
+            if tokens.is_empty() {
+                return Ok(());
+            }
+            let mut parser = LineParser::new(tokens, end);
+            parse_line(&mut parser).and_then(|_| parser.expect_end())
+        });
+        if let Err(e) = result {
+            errors.push(e);
+        }
+        line_start += line.len() + 1;
+    }
+    errors
+}
+
+/**
+ * Parse a 3-TAC program written by `Loggable for TacProgram`, one statement per line.
+ * Returns the program and the span of each statement in the source, for runtime errors to point at.
+ *
+ * The text doesn't hold the types of the variables. They are taken from the frame map when it is given, which
+ * makes the program equal to the one that was written. Otherwise they are inferred from the operators (`+.` takes
+ * doubles), conversions, literals, copies, calls and returns, and the variables nothing is known about are ints.
+ */
+pub fn parse_tac_program(source: &str, file_id: FileId, frame_map: Option<&FrameMap>) -> Result<(TacProgram, Vec<Span>), Vec<TacParseError>> {
+    let mut program: TacProgram = Vec::new();
+    let mut spans: Vec<Span> = Vec::new();
+    let mut errors = parse_lines(source, file_id, |parser| {
+        let statement = parser.parse_statement()?;
+        program.push(statement);
+        spans.push(parser.get_span());
+        Ok(())
+    });
+
+    if errors.is_empty() {
+        errors = check_labels(&program, &spans);
+    }
+    if errors.is_empty() {
+        errors = assign_types(&mut program, &spans, frame_map);
+    }
+    match errors.is_empty() {
+        true => Ok((program, spans)),
+        false => Err(errors),
+    }
+}
+
+/**
+ * Check that labels are defined once and that jumps and calls go to a label, except for the built-in `print`.
+ */
+fn check_labels(program: &TacProgram, spans: &[Span]) -> Vec<TacParseError> {
+    let mut errors = Vec::new();
+    let mut labels = HashSet::new();
+    for (statement, span) in program.iter().zip(spans.iter()) {
+        if let TacStatement::Label(label) = statement {
+            if !labels.insert(label.as_str()) {
+                errors.push(TacParseError::new(TacParseErrorType::DuplicateLabel(label.clone()), *span));
+            }
+        }
+    }
+
+    for (statement, span) in program.iter().zip(spans.iter()) {
+        let target = match statement {
+            TacStatement::Command(_, Some(TacValue::Label(label) | TacValue::IfArgs(_, label))) => label,
+            TacStatement::Assignment(_, TacOperation { val1: TacValue::LCallArgs(func), .. }) => func,
+            _ => continue,
+        };
+        if !labels.contains(target.as_str()) && target != "print" {
+            errors.push(TacParseError::new(TacParseErrorType::UndefinedLabel(target.clone()), *span));
+        }
+    }
+    errors
+}
+
+/**
+ * Get the function each statement belongs to, as an index into the names of the functions (also returned).
+ * A function starts at a label followed by `BeginFunc` and ends at its `EndFunc`.
+ */
+fn get_functions(program: &TacProgram) -> (Vec<Option<usize>>, Vec<String>) {
+    let mut functions = Vec::with_capacity(program.len());
+    let mut names = Vec::new();
+    let mut curr_func = None;
+    for (i, statement) in program.iter().enumerate() {
+        if let (TacStatement::Label(label), Some(TacStatement::Command(TacCommand::BeginFunc, _))) = (statement, program.get(i + 1)) {
+            names.push(label.clone());
+            curr_func = Some(names.len() - 1);
+        }
+        functions.push(curr_func);
+        if let TacStatement::Command(TacCommand::EndFunc, _) = statement {
+            curr_func = None;
+        }
+    }
+    (functions, names)
+}
+
+/**
+ * What a type is known for: a variable of a function (the type of the elements for an array)
+ * or the value a function returns.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum TypeKey {
+    Var(Option<usize>, String),
+    Return(String),
+}
+
+/**
+ * The types known in a program, and the pairs of variables that have the same type.
+ */
+#[derive(Default)]
+struct TypeConstraints {
+    types: HashMap<TypeKey, BasicType>,
+    links: Vec<(TypeKey, TypeKey)>,
+}
+
+impl TypeConstraints {
+    // The first type given wins, so the types of the frame map go first
+    fn set_type(&mut self, key: TypeKey, var_type: BasicType) {
+        self.types.entry(key).or_insert(var_type);
+    }
+
+    fn get_type(&self, func: Option<usize>, var: &str) -> BasicType {
+        self.types.get(&TypeKey::Var(func, var.to_string())).cloned().unwrap_or(BasicType::Int)
+    }
+
+    /**
+     * Give a key the type of a value: a literal's, or that of the variable or array it reads.
+     */
+    fn link_value(&mut self, key: TypeKey, func: Option<usize>, val: &TacValue) {
+        match val {
+            TacValue::Int(_) | TacValue::Double(_) => self.set_type(key, val.get_type().unwrap()),
+            TacValue::Var(var, _) | TacValue::PointerAccess(var, _) => self.links.push((key, TypeKey::Var(func, var.clone()))),
+            _ => {},
+        }
+    }
+
+    /**
+     * Set the type of the variables read by a value, and of the indices of the arrays it reads.
+     */
+    fn set_value_type(&mut self, func: Option<usize>, val: &TacValue, var_type: Option<BasicType>) {
+        match val {
+            TacValue::Var(var, _) | TacValue::PointerAccess(var, _) => {
+                if let Some(var_type) = var_type {
+                    self.set_type(TypeKey::Var(func, var.clone()), var_type);
+                }
+            },
+            _ => {},
+        }
+        if let TacValue::PointerAccess(_, index) = val {
+            self.set_value_type(func, index, Some(BasicType::Int));
+        }
+    }
+
+    /**
+     * Give the types of its operands to the variables of an operation, and its result's type to `target`.
+     */
+    fn add_operation(&mut self, target: TypeKey, func: Option<usize>, op: &TacOperation) {
+        match op.op {
+            Some(operator) => {
+                self.set_type(target, operator.get_result_type());
+                self.set_value_type(func, &op.val1, Some(operator.get_operand_type()));
+                if let Some(val2) = &op.val2 {
+                    self.set_value_type(func, val2, Some(operator.get_operand_type()));
+                }
+            },
+            None => {
+                if let TacValue::LCallArgs(called) = &op.val1 {
+                    self.links.push((target, TypeKey::Return(called.clone())));
+                } else {
+                    self.link_value(target, func, &op.val1);
+                    self.set_value_type(func, &op.val1, None);
+                }
+            },
+        }
+    }
+
+    /**
+     * Spread the known types along the links until nothing changes.
+     */
+    fn solve(&mut self) {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (a, b) in self.links.iter() {
+                let (from, to) = match (self.types.get(a), self.types.get(b)) {
+                    (Some(_), None) => (a, b),
+                    (None, Some(_)) => (b, a),
+                    _ => continue,
+                };
+                let var_type = self.types[from].clone();
+                self.types.insert(to.clone(), var_type);
+                changed = true;
+            }
+        }
+    }
+}
+
+/**
+ * Type the variables and operations of a parsed program, from the frame map if there is one.
+ * Returns the variables missing from the frame map.
+ */
+fn assign_types(program: &mut TacProgram, spans: &[Span], frame_map: Option<&FrameMap>) -> Vec<TacParseError> {
+    let (functions, names) = get_functions(program);
+    let mut errors = Vec::new();
+    let mut constraints = TypeConstraints::default();
+
+    let frames: Option<Vec<Option<&FrameLayout>>> = frame_map.map(|frame_map| {
+        names.iter().map(|name| frame_map.iter().find(|frame| frame.function == *name)).collect()
+    });
+    if let Some(frames) = &frames {
+        for (func, frame) in frames.iter().enumerate() {
+            let frame = match frame {
+                Some(frame) => frame,
+                None => {
+                    // The function's label
+                    let span = spans[functions.iter().position(|f| *f == Some(func)).unwrap()];
+                    errors.push(TacParseError::new(TacParseErrorType::MissingFrame(names[func].clone()), span));
+                    continue;
+                },
+            };
+            for slot in frame.slots.iter() {
+                let var_type = match &slot.var_type {
+                    BasicType::Array(element_type, _) => element_type.as_ref().clone(),
+                    var_type => var_type.clone(),
+                };
+                constraints.set_type(TypeKey::Var(Some(func), slot.name.clone()), var_type);
+            }
+        }
+    }
+
+    // The params of each function, in the order they are read (from the last one)
+    let mut params: HashMap<&str, Vec<TypeKey>> = HashMap::new();
+    for (statement, func) in program.iter().zip(functions.iter()) {
+        if let (TacStatement::Assignment(var, TacOperation { val1: TacValue::GetParams(_), .. }), Some(func)) = (statement, func) {
+            params.entry(names[*func].as_str()).or_default().push(TypeKey::Var(Some(*func), var.clone()));
+        }
+    }
+
+    let mut pushed: Vec<&TacValue> = Vec::new();
+    let mut reported = HashSet::new();
+    for (i, statement) in program.iter().enumerate() {
+        let func = functions[i];
+        let called = match statement {
+            TacStatement::Assignment(var, op) => {
+                constraints.add_operation(TypeKey::Var(func, var.clone()), func, op);
+                match &op.val1 {
+                    TacValue::LCallArgs(called) => Some(called),
+                    _ => None,
+                }
+            },
+            TacStatement::PointerAssignment(arr, index, op) => {
+                constraints.set_value_type(func, index, Some(BasicType::Int));
+                constraints.add_operation(TypeKey::Var(func, arr.clone()), func, op);
+                None
+            },
+            TacStatement::Command(TacCommand::Return, Some(val)) => {
+                if let Some(func) = func {
+                    constraints.link_value(TypeKey::Return(names[func].clone()), Some(func), val);
+                }
+                constraints.set_value_type(func, val, None);
+                None
+            },
+            TacStatement::Command(TacCommand::PushParam, Some(val)) => {
+                constraints.set_value_type(func, val, None);
+                pushed.push(val);
+                None
+            },
+            TacStatement::Command(TacCommand::LCall, Some(TacValue::Label(called))) => Some(called),
+            _ => None,
+        };
+
+        // The values pushed right before a call are its arguments, from the first one
+        if let Some(called_params) = called.and_then(|called| params.get(called.as_str())) {
+            if called_params.len() == pushed.len() {
+                for (param, val) in called_params.iter().zip(pushed.iter().rev()) {
+                    constraints.link_value(param.clone(), func, val);
+                }
+            }
+        }
+        if !matches!(statement, TacStatement::Command(TacCommand::PushParam, _)) {
+            pushed.clear();
+        }
+
+        if let (Some(frames), Some(func)) = (&frames, func) {
+            if let Some(frame) = frames[func] {
+                for var in get_vars(statement) {
+                    if frame.get_slot(var).is_none() && reported.insert((func, var.to_string())) {
+                        errors.push(TacParseError::new(
+                            TacParseErrorType::UnknownVariable {
+                                var: var.to_string(),
+                                function: names[func].clone(),
+                            },
+                            spans[i],
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return errors;
+    }
+
+    constraints.solve();
+    for (statement, func) in program.iter_mut().zip(functions.iter()) {
+        type_statement(statement, *func, &constraints);
+    }
+    errors
+}
+
+/**
+ * Get the variables and arrays a statement reads or writes.
+ */
+fn get_vars(statement: &TacStatement) -> Vec<&str> {
+    fn add_value<'a>(vars: &mut Vec<&'a str>, val: &'a TacValue) {
+        match val {
+            TacValue::Var(var, _) => vars.push(var),
+            TacValue::PointerAccess(arr, index) => {
+                vars.push(arr);
+                add_value(vars, index);
+            },
+            TacValue::IfArgs(cond, _) => vars.push(cond),
+            _ => {},
+        }
+    }
+
+    let mut vars = Vec::new();
+    match statement {
+        TacStatement::Assignment(var, op) | TacStatement::PointerAssignment(var, _, op) => {
+            vars.push(var.as_str());
+            if let TacStatement::PointerAssignment(_, index, _) = statement {
+                add_value(&mut vars, index);
+            }
+            add_value(&mut vars, &op.val1);
+            if let Some(val2) = &op.val2 {
+                add_value(&mut vars, val2);
+            }
+        },
+        TacStatement::Command(_, Some(val)) => add_value(&mut vars, val),
+        _ => {},
+    }
+    vars
+}
+
+fn type_value(val: &mut TacValue, func: Option<usize>, constraints: &TypeConstraints) {
+    match val {
+        TacValue::Var(var, var_type) => *var_type = constraints.get_type(func, var),
+        TacValue::PointerAccess(_, index) => type_value(index, func, constraints),
+        _ => {},
+    }
+}
+
+/**
+ * Set the types of the variables of a statement, and of its operation: that of its value when it is a variable,
+ * a literal or an array element, and of the variable it is assigned to otherwise (for `GetParams` and `LCall`).
+ */
+fn type_statement(statement: &mut TacStatement, func: Option<usize>, constraints: &TypeConstraints) {
+    let (target, index, op) = match statement {
+        TacStatement::Assignment(var, op) => (var.as_str(), None, op),
+        TacStatement::PointerAssignment(arr, index, op) => (arr.as_str(), Some(index), op),
+        TacStatement::Command(_, Some(val)) => {
+            type_value(val, func, constraints);
+            return;
+        },
+        _ => return,
+    };
+    if let Some(index) = index {
+        type_value(index, func, constraints);
+    }
+
+    type_value(&mut op.val1, func, constraints);
+    if let Some(val2) = &mut op.val2 {
+        type_value(val2, func, constraints);
+    }
+    if op.op.is_none() {
+        op.op_type = match &op.val1 {
+            TacValue::Var(_, var_type) => var_type.clone(),
+            TacValue::Int(_) | TacValue::Double(_) => op.val1.get_type().unwrap(),
+            TacValue::PointerAccess(arr, _) => constraints.get_type(func, arr),
+            _ => constraints.get_type(func, target),
+        };
+    }
+}
+
+/**
+ * Parse a frame map written by `Loggable for FrameMap`, e.g.
+ * ```text
+ * frame add1 size 16 align 4
+ *     param x1 offset 0 size 4 type int
+ *     array a1 offset 4 size 12 type [int; 3]
+ * ```
+ */
+pub fn parse_frame_map(source: &str, file_id: FileId) -> Result<FrameMap, Vec<TacParseError>> {
+    let mut frame_map: FrameMap = Vec::new();
+    let errors = parse_lines(source, file_id, |parser| {
+        if parser.next_if_word("frame") {
+            let mut frame = FrameLayout::new(parser.expect_name("a function")?);
+            parser.expect_keyword("size")?;
+            let size = parser.expect_size()?;
+            parser.expect_keyword("align")?;
+            let align = parser.expect_size()?;
+            frame.size = size;
+            frame.align = align;
+            frame_map.push(frame);
+            return Ok(());
+        }
+
+        let kind = match parser.peek() {
+            Some(TacToken::Word(word)) => match word.as_str() {
+                "param" => SlotKind::Parameter,
+                "local" => SlotKind::Local,
+                "array" => SlotKind::Array,
+                "temp" => SlotKind::Temporary,
+                _ => return Err(parser.expected("`frame` or a slot (`param`, `local`, `array` or `temp`)")),
+            },
+            _ => return Err(parser.expected("`frame` or a slot (`param`, `local`, `array` or `temp`)")),
+        };
+        if frame_map.is_empty() {
+            return Err(parser.expected("`frame`"));
+        }
+        parser.pos += 1;
+        let name = parser.expect_name("a variable")?;
+        parser.expect_keyword("offset")?;
+        let offset = parser.expect_size()?;
+        parser.expect_keyword("size")?;
+        let size = parser.expect_size()?;
+        parser.expect_keyword("type")?;
+        let var_type = parser.parse_type()?;
+        frame_map.last_mut().unwrap().insert_slot(FrameSlot { source_name: name.clone(), name, kind, var_type, offset, size });
+        Ok(())
+    });
+
+    match errors.is_empty() {
+        true => Ok(frame_map),
+        false => Err(errors),
+    }
+}
+
+/**
+ * Lay out a frame for every function of a parsed program that has no frame map, with its params first and then its
+ * variables in the order they appear (names ending with `_` being temporaries).
+ * Fails if the program uses arrays, whose sizes only the frame map knows.
+ */
+pub fn infer_frame_map(program: &TacProgram, spans: &[Span], target: &TargetLayout) -> Result<FrameMap, Vec<TacParseError>> {
+    let (functions, names) = get_functions(program);
+    let mut frame_map: FrameMap = names.iter().map(|name| FrameLayout::new(name.clone())).collect();
+    let mut errors = Vec::new();
+    let mut reported = HashSet::new();
+
+    // Params are read from the last one
+    for ((statement, func), span) in program.iter().zip(functions.iter()).zip(spans.iter()).rev() {
+        if let (TacStatement::Assignment(var, op @ TacOperation { val1: TacValue::GetParams(_), .. }), Some(func)) = (statement, func) {
+            if frame_map[*func].add_slot(var.clone(), SlotKind::Parameter, op.op_type.clone(), target).is_none() {
+                errors.push(TacParseError::new(TacParseErrorType::FrameTooLarge(names[*func].clone()), *span));
+            }
+        }
+    }
+
+    for (i, statement) in program.iter().enumerate() {
+        let func = match functions[i] {
+            Some(func) => func,
+            None => continue,
+        };
+        for var in get_vars(statement) {
+            if frame_map[func].get_slot(var).is_some() {
+                continue;
+            }
+            if is_array(statement, var) {
+                if reported.insert(var) {
+                    errors.push(TacParseError::new(TacParseErrorType::UnknownArraySize(var.to_string()), spans[i]));
+                }
+                continue;
+            }
+            let kind = match var.ends_with('_') {
+                true => SlotKind::Temporary,
+                false => SlotKind::Local,
+            };
+            let var_type = get_var_type(statement, var).unwrap_or(BasicType::Int);
+            if frame_map[func].add_slot(var.to_string(), kind, var_type, target).is_none() && reported.insert(var) {
+                errors.push(TacParseError::new(TacParseErrorType::FrameTooLarge(names[func].clone()), spans[i]));
+            }
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(frame_map),
+        false => Err(errors),
+    }
+}
+
+/**
+ * Whether a statement uses a name as an array.
+ */
+fn is_array(statement: &TacStatement, name: &str) -> bool {
+    fn reads_array(val: &TacValue, name: &str) -> bool {
+        match val {
+            TacValue::PointerAccess(arr, index) => arr == name || reads_array(index, name),
+            _ => false,
+        }
+    }
+
+    match statement {
+        TacStatement::PointerAssignment(arr, _, _) if arr == name => true,
+        TacStatement::Assignment(_, op) | TacStatement::PointerAssignment(_, _, op) => {
+            reads_array(&op.val1, name) || op.val2.as_ref().is_some_and(|val2| reads_array(val2, name))
+        },
+        TacStatement::Command(_, Some(val)) => reads_array(val, name),
+        _ => false,
+    }
+}
+
+/**
+ * Get the type of a variable of a typed statement, if the statement tells it.
+ */
+fn get_var_type(statement: &TacStatement, name: &str) -> Option<BasicType> {
+    fn find_type(val: &TacValue, name: &str) -> Option<BasicType> {
+        match val {
+            TacValue::Var(var, var_type) if var == name => Some(var_type.clone()),
+            TacValue::PointerAccess(_, index) => find_type(index, name),
+            _ => None,
+        }
+    }
+
+    match statement {
+        TacStatement::Assignment(var, op) if var == name => Some(op.op_type.clone()),
+        TacStatement::Assignment(_, op) | TacStatement::PointerAssignment(_, _, op) => {
+            let index = match statement {
+                TacStatement::PointerAssignment(_, index, _) => find_type(index, name),
+                _ => None,
+            };
+            index.or_else(|| find_type(&op.val1, name)).or_else(|| op.val2.as_ref().and_then(|val2| find_type(val2, name)))
+        },
+        TacStatement::Command(_, Some(val)) => find_type(val, name),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{TacParseError, TacParseErrorType};
+    use crate::intermediate_code_generation::layout::SlotKind;
+    use crate::logger::Loggable;
+    use crate::session::{CompileError, Session};
+
+    // A test program as printed by the compiler
+    struct PrintedProgram {
+        name: String,
+        tac: String,
+        frames: String,
+        has_arrays: bool,
+    }
+
+    /**
+     * Compile the test programs, printing the 3-TAC and frame map of those that compile.
+     */
+    fn print_test_programs() -> Vec<PrintedProgram> {
+        let mut paths: Vec<_> = fs::read_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/test_programs")).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "cp"))
+            .collect();
+        paths.sort();
+
+        let mut printed = Vec::new();
+        for path in paths {
+            let name = path.display().to_string();
+            let mut session = Session::new(&name);
+            let tac = match session.tac_program() {
+                Ok(program) => program.to_log_message(),
+                Err(_) => continue,
+            };
+            let frame_map = session.frame_map().unwrap();
+            let has_arrays = frame_map.iter().flat_map(|frame| frame.slots.iter()).any(|slot| slot.kind == SlotKind::Array);
+            printed.push(PrintedProgram { name, tac, frames: frame_map.to_log_message(), has_arrays });
+        }
+        assert!(!printed.is_empty());
+        printed
+    }
+
+    #[test]
+    fn printed_programs_parse_back_with_their_frame_map() {
+        for printed in print_test_programs() {
+            let mut session = Session::from_source("test.tac", &printed.tac);
+            let program = session.parse_tac_with_frame_map("test.frames", &printed.frames)
+                .unwrap_or_else(|e| panic!("{}: {:?}", printed.name, e.to_diagnostics()));
+            assert_eq!(program.to_log_message(), printed.tac, "{}", printed.name);
+            assert_eq!(session.frame_map().unwrap().to_log_message(), printed.frames, "{}", printed.name);
+        }
+    }
+
+    #[test]
+    fn printed_programs_parse_back_without_a_frame_map() {
+        for printed in print_test_programs() {
+            let mut session = Session::from_source("test.tac", &printed.tac);
+            match session.parse_tac() {
+                Ok(program) => {
+                    assert!(!printed.has_arrays, "{}: the sizes of its arrays can't be known", printed.name);
+                    assert_eq!(program.to_log_message(), printed.tac, "{}", printed.name);
+                },
+                // Without a frame map, the sizes of the arrays can't be known
+                Err(CompileError::Tac(errors)) if printed.has_arrays => {
+                    let unknown_size = |error: &TacParseError| matches!(error.get_error_type(), TacParseErrorType::UnknownArraySize(_));
+                    assert!(errors.iter().all(unknown_size), "{}", printed.name);
+                },
+                Err(e) => panic!("{}: {:?}", printed.name, e.to_diagnostics()),
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_programs_are_errors() {
+        let mut session = Session::from_source("test.tac", "Goto main0;\nmain0:\nBeginFunc 4;\nx0 = ;\nEndFunc;\n");
+        assert!(matches!(session.parse_tac(), Err(CompileError::Tac(errors)) if !errors.is_empty()));
+
+        let mut session = Session::from_source("test.tac", "Goto main0;\nmain0:\nBeginFunc 0;\nGoto L9;\nEndFunc;\n");
+        match session.parse_tac() {
+            Err(CompileError::Tac(errors)) => {
+                assert!(matches!(errors[0].get_error_type(), TacParseErrorType::UndefinedLabel(label) if label == "L9"));
+            },
+            result => panic!("expected an undefined label, got {:?}", result.map(|program| program.to_log_message())),
+        }
+    }
+
+    #[test]
+    fn malformed_frame_maps_are_errors() {
+        let mut session = Session::from_source("test.tac", "Goto main0;\nmain0:\nBeginFunc 0;\nEndFunc;\n");
+        let result = session.parse_tac_with_frame_map("test.frames", "frame main0 size four align 1\n");
+        assert!(matches!(result, Err(CompileError::Tac(errors)) if !errors.is_empty()));
+    }
+}
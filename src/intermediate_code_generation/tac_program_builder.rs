@@ -1,5 +1,6 @@
 use crate::{
     lexical_analysis::Token,
+    source_map::Span,
     syntax_semantic_analysis::{
         statement_tree::{
             StatementSymbol,
@@ -10,6 +11,10 @@ use crate::{
             BasicType,
             SymbolDecl
         },
+        semantic_analysis::{
+            SemanticError,
+            SemanticErrorType
+        },
         symbol_table::{
             BuiltInFunc,
             BuiltInFuncType
@@ -17,9 +22,11 @@ use crate::{
     }
 };
 
+use super::layout::{FrameLayout, FrameMap, SlotKind, TargetLayout};
 use super::tac::{
     TacCommand,
     TacOperation,
+    TacOperator,
     TacProgram,
     TacStatement,
     TacValue
@@ -28,9 +35,14 @@ use super::tac::{
 #[derive(Debug)]
 pub struct TacFunctionInfo {
     pub name: String,
+    // The name of the function in the source, `main` for the main program
+    pub source_name: String,
     pub statements: Vec<TacStatement>,
-    pub var_sizes: Vec<(String, (u32, u32))>,
-    pub params: Vec<(String, u32)>,
+    // The span of the source each statement was generated from
+    pub spans: Vec<Span>,
+    // Every variable of the function, parameters first
+    pub frame: FrameLayout,
+    pub params: Vec<String>,
     pub label_count: u16,
     pub temp_count: u16,
 }
@@ -39,14 +51,22 @@ pub struct TacFunctionInfo {
 pub struct TacProgramBuilder {
     funcs: Vec<TacFunctionInfo>,
     curr_func: usize,
+    // The span of the source the statements being added are generated from
+    curr_span: Span,
+    target: TargetLayout,
+    // Frames that grew too large, which the semantic analysis can't see all of as it doesn't know the temporaries
+    errors: Vec<SemanticError>,
 }
 
 //MARK: TacProgramBuilder
 impl TacProgramBuilder {
-    pub fn new() -> Self {
+    pub fn new(target: TargetLayout) -> Self {
         let mut program_builder = TacProgramBuilder {
             funcs: Vec::new(),
             curr_func: 0,
+            curr_span: Span::default(),
+            target,
+            errors: Vec::new(),
         };
 
         program_builder.add_function(String::from("main"));
@@ -55,48 +75,49 @@ impl TacProgramBuilder {
 
     //MARK: get_program
     // Consumes self
-    pub fn get_program(self) -> TacProgram {
+    pub fn get_program(self) -> Result<(TacProgram, FrameMap, Vec<Span>), Vec<SemanticError>> {
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+
         let mut program: TacProgram = Vec::new();
+        let mut frame_map: FrameMap = Vec::new();
+        let mut spans: Vec<Span> = Vec::new();
         program.push(TacStatement::Command(
             TacCommand::Goto,
             Some(TacValue::Label(String::from("main0")))
         ));
 
-        for func in self.funcs.iter().rev() {
-            // Find variable sizes
-            let mut var_sizes = 0;
-            for (_, size) in func.var_sizes.iter() {
-                var_sizes += size.0 * size.1;
-            }
-
+        for func in self.funcs.into_iter().rev() {
             program.push(TacStatement::Label(func.name.clone()));
             program.push(TacStatement::Command(
                 TacCommand::BeginFunc,
-                Some(TacValue::Int(var_sizes)),
+                Some(TacValue::Int(func.frame.size)),
             ));
 
             for param in func.params.iter().rev() {
-                program.push(TacStatement::Assignment(
-                    param.0.clone(),
-                    TacOperation {
-                        op: None,
-                        val1: TacValue::GetParams(param.1),
-                        val2: None,
-                    },
-                ));
+                if let Some(slot) = func.frame.get_slot(param) {
+                    program.push(TacStatement::Assignment(
+                        param.clone(),
+                        TacOperation::value(TacValue::GetParams(slot.size), slot.var_type.clone()),
+                    ));
+                }
             }
 
-            for statement in &func.statements {
-                program.push(statement.clone());
-            }
+            // The statements added around the function's own aren't generated from the source
+            spans.resize(program.len(), Span::default());
+            program.extend(func.statements);
+            spans.extend(func.spans);
 
             program.push(TacStatement::Command(
                 TacCommand::EndFunc,
                 None,
             ));
+            frame_map.push(func.frame);
         }
+        spans.resize(program.len(), Span::default());
 
-        program
+        Ok((program, frame_map, spans))
     }
 
     pub fn get_next_label(&mut self, label: String) -> String {
@@ -107,11 +128,13 @@ impl TacProgramBuilder {
     }
 
     pub fn add_function(&mut self, name: String) -> usize {
-        let name_with_count = format!("{}{}", name.clone(), self.funcs.len());
+        let name_with_count = format!("{}{}", name, self.funcs.len());
         self.funcs.push(TacFunctionInfo {
-            name: name_with_count,
+            name: name_with_count.clone(),
+            source_name: name,
             statements: Vec::new(),
-            var_sizes: Vec::new(),
+            spans: Vec::new(),
+            frame: FrameLayout::new(name_with_count),
             params: Vec::new(),
             label_count: 0,
             temp_count: 0,
@@ -124,39 +147,93 @@ impl TacProgramBuilder {
         self.curr_func = 0;
     }
 
+    /**
+     * Set the span of the source the next statements are generated from.
+     */
+    pub fn set_span(&mut self, span: Span) {
+        self.curr_span = span;
+    }
+
+    fn push_statement(&mut self, statement: TacStatement) {
+        let func = &mut self.funcs[self.curr_func];
+        func.statements.push(statement);
+        func.spans.push(self.curr_span);
+    }
+
+    /**
+     * Get the label of a function from its name, the function being added already.
+     */
+    fn get_function_label(&self, name: &str) -> String {
+        self.funcs.iter()
+            .enumerate()
+            .map(|(i, func)| (format!("{}{}", name, i), func))
+            .find(|(label, func)| *label == func.name)
+            .map_or(String::from(name), |(label, _)| label)
+    }
+
     pub fn add_parameter(&mut self, decl: &SymbolDecl) {
-        self.funcs[self.curr_func].params.push((decl.to_var_name(), decl.get_size()));
+        self.add_slot(decl.to_var_name(), decl.name.clone(), SlotKind::Parameter, decl.var_type.clone(), decl.span);
+        self.funcs[self.curr_func].params.push(decl.to_var_name());
+    }
+
+    /**
+     * Give a local variable (or a global, which is local to the main program) its place in the frame.
+     */
+    pub fn add_local(&mut self, decl: &SymbolDecl) {
+        let kind = match decl.var_type {
+            BasicType::Array(..) => SlotKind::Array,
+            _ => SlotKind::Local,
+        };
+        self.add_slot(decl.to_var_name(), decl.name.clone(), kind, decl.var_type.clone(), decl.span);
+    }
+
+    /**
+     * Add a variable to the frame of the current function, unless it is already there.
+     * A frame that doesn't fit is reported once, at the span of the variable that made it too large.
+     */
+    fn add_slot(&mut self, name: String, source_name: String, kind: SlotKind, var_type: BasicType, span: Span) {
+        let func = &mut self.funcs[self.curr_func];
+        if func.frame.get_slot(&name).is_some() {
+            return;
+        }
+        let fits = func.frame.add_slot_within(name, source_name, kind, var_type, &self.target, u32::MAX).is_some();
+        let reported = self.errors.iter()
+            .any(|error| matches!(error.get_error_type(), SemanticErrorType::FrameTooLarge(function) if *function == func.source_name));
+        if !fits && !reported {
+            self.errors.push(SemanticError::new(SemanticErrorType::FrameTooLarge(func.source_name.clone()), span));
+        }
     }
 
-    pub fn new_temp_var(&mut self, var_size: u32, scope: usize) -> SymbolDecl {
+    pub fn new_temp_var(&mut self, var_type: BasicType, scope: usize) -> SymbolDecl {
         let temp_var = SymbolDecl {
             name: format!("t{}_", self.funcs[self.curr_func].temp_count),
-            var_type: BasicType::Int,
+            var_type,
             scope,
+            span: Span::default(),
         };
         self.funcs[self.curr_func].temp_count += 1;
-        self.set_size(temp_var.name.clone(), var_size, 1);
+        self.add_slot(temp_var.name.clone(), temp_var.name.clone(), SlotKind::Temporary, temp_var.var_type.clone(), self.curr_span);
         temp_var
     }
 
     pub fn add_statement(&mut self, statement_tree: &StatementTree, scope: usize) -> TacOperation {
-        self._add_statement(statement_tree.start.unwrap(), &statement_tree, scope)
+        self._add_statement(statement_tree.start.unwrap(), statement_tree, scope)
     }
 
     //MARK: add_assignment
     pub fn add_assignment(&mut self, var: String, op: TacOperation) {
-        let size = self.get_val_size(&op.val1);
-        self.set_size(var.clone(), size, 1);
-
         let assignment = TacStatement::Assignment(
             var,
             op,
         );
-        self.funcs[self.curr_func].statements.push(assignment);
+        self.push_statement(assignment);
     }
 
     pub fn add_assignment_statement(&mut self, var: String, statement: &StatementTree, scope: usize) {
         let op = self.add_statement(statement, scope);
+        if let Some(span) = statement.get_tree_span() {
+            self.set_span(span);
+        }
         self.add_assignment(var, op);
     }
 
@@ -165,35 +242,38 @@ impl TacProgramBuilder {
         arr: String,
         index: &StatementTree,
         statement: &StatementTree,
-        arr_len: u32,
         scope: usize
     ) {
+        let span = self.curr_span;
         let index_op = self.add_statement(index, scope);
-        let index_val = if let Some(_) = index_op.op {
-            let temp_var = self.new_temp_var(self.get_val_size(&index_op.val1), scope);
+        let index_val = if index_op.op.is_some() {
+            let temp_var = self.new_temp_var(BasicType::Int, scope);
+            if let Some(index_span) = index.get_tree_span() {
+                self.set_span(index_span);
+            }
             self.add_assignment(temp_var.name.clone(), index_op);
-            TacValue::Var(temp_var.name.clone())
+            to_tac_var(&temp_var)
         } else {
             index_op.val1
         };
 
         let statement_op = self.add_statement(statement, scope);
-        self.set_size(arr.clone(), self.get_val_size(&statement_op.val1), arr_len);
 
+        self.set_span(span);
         let assignment = TacStatement::PointerAssignment(
             arr,
             index_val,
             statement_op,
         );
-        self.funcs[self.curr_func].statements.push(assignment);
+        self.push_statement(assignment);
     }
 
-    pub fn add_push_param(&mut self, var: String) {
+    pub fn add_push_param(&mut self, var: TacValue) {
         let push_param = TacStatement::Command(
             TacCommand::PushParam,
-            Some(TacValue::Var(var)),
+            Some(var),
         );
-        self.funcs[self.curr_func].statements.push(push_param);
+        self.push_statement(push_param);
     }
 
     pub fn add_pop_params(&mut self, size: u32) {
@@ -201,14 +281,14 @@ impl TacProgramBuilder {
             TacCommand::PopParams,
             TacValue::Int(size).into(),
         );
-        self.funcs[self.curr_func].statements.push(pop_params);
+        self.push_statement(pop_params);
     }
 
-    pub fn add_call_func(&mut self, func_name: String, params: Box<[String]>, return_var: Option<String>) {
+    pub fn add_call_func(&mut self, func_name: String, params: &[SymbolDecl], return_var: Option<SymbolDecl>) {
         let mut params_size = 0;
         for param in params.iter() {
-            self.add_push_param(param.clone());
-            params_size += self.get_val_size(&TacValue::Var(param.clone()));
+            self.add_push_param(to_tac_var(param));
+            params_size += self.get_var_size(&param.name);
         }
 
         let func_call = TacStatement::Command(
@@ -217,13 +297,9 @@ impl TacProgramBuilder {
         );
 
         if let Some(var) = return_var {
-            self.add_assignment(var, TacOperation {
-                op: None,
-                val1: TacValue::LCallArgs(func_name),
-                val2: None,
-            });
+            self.add_assignment(var.name, TacOperation::value(TacValue::LCallArgs(func_name), var.var_type));
         } else {
-            self.funcs[self.curr_func].statements.push(func_call);
+            self.push_statement(func_call);
         }
 
         self.add_pop_params(params_size);
@@ -232,23 +308,24 @@ impl TacProgramBuilder {
     // Scope required to add temp vars
     pub fn add_builtin_func(&mut self, builtin_func: &BuiltInFunc, scope: usize) -> SymbolDecl {
         let temp_var: SymbolDecl = self.new_temp_var(
-            builtin_func.statement.get_type_size(),
+            get_tree_type(&builtin_func.statement),
             scope
         );
         self.add_assignment_statement(temp_var.name.clone(), &builtin_func.statement, scope);
+        self.set_span(builtin_func.span);
 
         match &builtin_func.func_type {
             BuiltInFuncType::Return => {
                 let return_statement = TacStatement::Command(
                     TacCommand::Return,
-                    Some(TacValue::Var(temp_var.name.clone())),
+                    Some(to_tac_var(&temp_var)),
                 );
-                self.funcs[self.curr_func].statements.push(return_statement);
+                self.push_statement(return_statement);
             },
             BuiltInFuncType::Print => {
                 self.add_call_func(
                     String::from("print"),
-                    vec![temp_var.name.clone()].into_boxed_slice(),
+                    std::slice::from_ref(&temp_var),
                     None,
                 );
             },
@@ -262,44 +339,37 @@ impl TacProgramBuilder {
             TacCommand::Goto,
             Some(TacValue::Label(label)),
         );
-        self.funcs[self.curr_func].statements.push(goto);
+        self.push_statement(goto);
     }
 
     pub fn add_label(&mut self, label: String) {
         let label = TacStatement::Label(label);
-        self.funcs[self.curr_func].statements.push(label);
+        self.push_statement(label);
     }
 
-    fn _add_if(&mut self, cond_var: String, has_else: bool) -> (String, String) {
-        let cond_label = if has_else {
-            self.get_next_label(String::from("else"))
-        } else {
-            self.get_next_label(String::from("fi"))
-        };
-
+    fn add_if_z(&mut self, cond_var: String, label: String) {
         let ifz = TacStatement::Command(
             TacCommand::IfZ,
             TacValue::IfArgs(
-                cond_var.clone(),
-                cond_label.clone(),
+                cond_var,
+                label,
             ).into(),
         );
-        self.funcs[self.curr_func].statements.push(ifz);
-
-        if has_else {
-            let end_label = self.get_next_label(String::from("fi"));
-            (cond_label, end_label)
-        } else {
-            (String::new(), cond_label)
-        }
+        self.push_statement(ifz);
     }
 
     pub fn add_if(&mut self, condition: &StatementTree, scope: usize, has_else: bool) -> (String, String) {
-        // Create condition var
-        let cond_var = self.new_temp_var(condition.get_type_size(), scope);
-        self.add_assignment_statement(cond_var.name.clone(), condition, scope);
+        let (else_label, end_label) = if has_else {
+            (self.get_next_label(String::from("else")), self.get_next_label(String::from("fi")))
+        } else {
+            (String::new(), self.get_next_label(String::from("fi")))
+        };
+
+        // Skip the body when the condition is false
+        let false_label = if has_else { else_label.clone() } else { end_label.clone() };
+        self.add_condition(condition, scope, &false_label);
 
-        self._add_if(cond_var.name.clone(), has_else)
+        (else_label, end_label)
     }
 
     pub fn add_while_statement(&mut self, condition: &StatementTree, scope: usize) -> (String, String) {
@@ -308,49 +378,152 @@ impl TacProgramBuilder {
         self.add_label(while_label.clone());
         let end_while_label = self.get_next_label(String::from("od"));
         
-        // Create condition var
-        let cond_var = self.new_temp_var(condition.get_type_size(), scope);
-        self.add_assignment_statement(cond_var.name.clone(), condition, scope);
-        
-        // Add if statement
-        let ifz = TacStatement::Command(
-            TacCommand::IfZ,
-            TacValue::IfArgs(
-                cond_var.name.clone(),
-                end_while_label.clone(),
-            ).into(),
-        );
-        self.funcs[self.curr_func].statements.push(ifz);
+        // Leave the loop when the condition is false
+        self.add_condition(condition, scope, &end_while_label);
 
         (while_label, end_while_label)        
     }
 
+    /**
+     * Add the code of an `if` or `while` condition, jumping to `false_label` when it is false
+     * and falling through when it is true.
+     */
+    fn add_condition(&mut self, condition: &StatementTree, scope: usize, false_label: &str) {
+        // A condition is true when it isn't zero, while `and`, `or` and `not` check for a positive value
+        self.add_jump(condition.start.unwrap(), condition, scope, false_label, false, false);
+    }
+
+    //MARK: add_jump
+    /**
+     * Add jumping code for a condition: jump to `label` when the condition is `jump_if`, fall through otherwise.
+     * The operands of `and` and `or` are only evaluated when they decide the result, and `positive` is whether
+     * the condition is an operand of `and`, `or` or `not`, which are true for positive values.
+     */
+    fn add_jump(&mut self, curr_node: usize, nodes: &StatementTree, scope: usize, label: &str, jump_if: bool, positive: bool) {
+        let node = &nodes.nodes[curr_node];
+        match (&node.symbol, node.left, node.right) {
+            (StatementSymbol::Operator(Token::Kand), Some(left), Some(right)) => {
+                if jump_if {
+                    // Both operands have to be true to jump
+                    let false_label = self.get_next_label(String::from("false"));
+                    self.add_jump(left, nodes, scope, &false_label, false, true);
+                    self.add_jump(right, nodes, scope, label, true, true);
+                    self.add_label(false_label);
+                } else {
+                    self.add_jump(left, nodes, scope, label, false, true);
+                    self.add_jump(right, nodes, scope, label, false, true);
+                }
+            },
+            (StatementSymbol::Operator(Token::Kor), Some(left), Some(right)) => {
+                if jump_if {
+                    self.add_jump(left, nodes, scope, label, true, true);
+                    self.add_jump(right, nodes, scope, label, true, true);
+                } else {
+                    // Both operands have to be false to jump
+                    let true_label = self.get_next_label(String::from("true"));
+                    self.add_jump(left, nodes, scope, &true_label, true, true);
+                    self.add_jump(right, nodes, scope, label, false, true);
+                    self.add_label(true_label);
+                }
+            },
+            (StatementSymbol::SingleChildOperator(Token::Knot), Some(child), _) => {
+                self.add_jump(child, nodes, scope, label, !jump_if, true);
+            },
+            (StatementSymbol::SingleChildOperator(Token::Soparen), Some(child), _) => {
+                self.add_jump(child, nodes, scope, label, jump_if, positive);
+            },
+            _ => {
+                let cond_var = self.add_condition_var(curr_node, nodes, scope, positive);
+                if jump_if {
+                    let false_label = self.get_next_label(String::from("false"));
+                    self.add_if_z(cond_var, false_label.clone());
+                    self.add_goto(String::from(label));
+                    self.add_label(false_label);
+                } else {
+                    self.add_if_z(cond_var, String::from(label));
+                }
+            },
+        }
+    }
+
+    /**
+     * Get a variable that is zero when the expression is false, checking that it is positive if `positive` is set.
+     */
+    fn add_condition_var(&mut self, curr_node: usize, nodes: &StatementTree, scope: usize, positive: bool) -> String {
+        let mut op = self._add_statement(curr_node, nodes, scope);
+        self.set_span(nodes.get_span(curr_node));
+
+        // A comparison is already 0 or 1
+        let is_comparison = matches!(
+            nodes.nodes[curr_node].symbol,
+            StatementSymbol::Operator(Token::Oequal | Token::Onot | Token::Olt | Token::Ogt | Token::Olte | Token::Ogte)
+        );
+        if positive && !is_comparison {
+            let op_type = op.op_type.clone();
+            let val = self.add_value(op, scope);
+            op = TacOperation::binary(get_operator(&Token::Ogt, &op_type), val, to_literal(0, &op_type));
+        }
+
+        match op {
+            TacOperation { op: None, val1: TacValue::Var(var, _), .. } => var,
+            op => {
+                let temp_var = self.new_temp_var(op.op_type.clone(), scope);
+                self.add_assignment(temp_var.name.clone(), op);
+                temp_var.name
+            },
+        }
+    }
+
+    /**
+     * Get the value of an operation, storing it in a temp var unless it is a single value.
+     */
+    fn add_value(&mut self, op: TacOperation, scope: usize) -> TacValue {
+        if op.op.is_some() {
+            let temp_var = self.new_temp_var(op.op_type.clone(), scope);
+            self.add_assignment(temp_var.name.clone(), op);
+            to_tac_var(&temp_var)
+        } else {
+            op.val1
+        }
+    }
+
+    /**
+     * Get the value of `and`, `or` or `not` as 1 or 0, evaluated with jumping code.
+     */
+    fn add_logical_value(&mut self, curr_node: usize, nodes: &StatementTree, scope: usize) -> TacOperation {
+        let result_var = self.new_temp_var(get_node_type(nodes, curr_node), scope);
+        let false_label = self.get_next_label(String::from("false"));
+        let end_label = self.get_next_label(String::from("end"));
+
+        self.add_jump(curr_node, nodes, scope, &false_label, false, true);
+        self.set_span(nodes.get_span(curr_node));
+        let true_assignment = TacOperation::value(to_literal(1, &result_var.var_type), result_var.var_type.clone());
+        self.add_assignment(result_var.name.clone(), true_assignment);
+        self.add_goto(end_label.clone());
+
+        self.add_label(false_label);
+        let false_assignment = TacOperation::value(to_literal(0, &result_var.var_type), result_var.var_type.clone());
+        self.add_assignment(result_var.name.clone(), false_assignment);
+        self.add_label(end_label);
+
+        TacOperation::value(to_tac_var(&result_var), result_var.var_type.clone())
+    }
+
     //MARK: _add_statement
     fn _add_statement(&mut self, curr_node: usize, nodes: &StatementTree, scope: usize) -> TacOperation {
         let node = &nodes.nodes[curr_node];
         match &node.symbol {
             StatementSymbol::Decl(decl_id) => {
-                TacOperation {
-                    op: None,
-                    val1: TacValue::Var(to_var_name(decl_id)),
-                    val2: None,
-                }
+                let var_type = get_node_type(nodes, curr_node);
+                TacOperation::value(TacValue::Var(to_var_name(decl_id), var_type.clone()), var_type)
             },
             StatementSymbol::Literal(literal) => {
                 match literal {
                     Token::Tint(int) => {
-                        TacOperation {
-                            op: None,
-                            val1: TacValue::Int(*int),
-                            val2: None,
-                        }
+                        TacOperation::value(TacValue::Int(*int), BasicType::Int)
                     },
                     Token::Tdouble(double) => {
-                        TacOperation {
-                            op: None,
-                            val1: TacValue::Double(*double),
-                            val2: None,
-                        }
+                        TacOperation::value(TacValue::Double(*double), BasicType::Double)
                     },
                     _ => {
                         panic!("Invalid literal type");
@@ -359,39 +532,51 @@ impl TacProgramBuilder {
             },
             StatementSymbol::ArrayAccess(arr_id, index_statement) => {
                 // Get index into temp var
-                let temp_var: SymbolDecl = self.new_temp_var(4, scope);
+                let temp_var: SymbolDecl = self.new_temp_var(BasicType::Int, scope);
                 self.add_assignment_statement(temp_var.name.clone(), index_statement, scope);
 
-                TacOperation {
-                    op: None,
-                    val1: TacValue::PointerAccess(
+                TacOperation::value(
+                    TacValue::PointerAccess(
                         to_var_name(arr_id),
-                        TacValue::Var(temp_var.name.clone()).into(),
+                        to_tac_var(&temp_var).into(),
                     ),
-                    val2: None,
-                }
+                    get_node_type(nodes, curr_node),
+                )
             },
             StatementSymbol::FunctionCall(func_id, args) => {
                 // Create temp vars for args
-                let mut temp_vars: Vec<String> = Vec::with_capacity(args.len());
+                let mut temp_vars: Vec<SymbolDecl> = Vec::with_capacity(args.len());
                 for arg in args.iter() {
-                    let temp_var: SymbolDecl = self.new_temp_var(arg.get_type_size(), scope);
+                    let temp_var: SymbolDecl = self.new_temp_var(get_tree_type(arg), scope);
                     self.add_assignment_statement(temp_var.name.clone(), arg, scope);
-                    temp_vars.push(temp_var.name.clone());
+                    temp_vars.push(temp_var);
                 }
                 
-                let return_var = self.new_temp_var(4, scope);
+                let return_var = self.new_temp_var(get_node_type(nodes, curr_node), scope);
+                self.set_span(nodes.get_span(curr_node));
+                let label = self.get_function_label(&func_id.0);
                 self.add_call_func(
-                    func_id.0.clone(),
-                    temp_vars.into_boxed_slice(),
-                    Some(return_var.name.clone()),
+                    label,
+                    &temp_vars,
+                    Some(return_var.clone()),
                 );
 
-                TacOperation {
-                    op: None,
-                    val1: TacValue::Var(return_var.name.clone()),
-                    val2: None,
-                }
+                TacOperation::value(to_tac_var(&return_var), return_var.var_type)
+            },
+            StatementSymbol::SingleChildOperator(Token::Knot) => {
+                self.add_logical_value(curr_node, nodes, scope)
+            },
+            StatementSymbol::SingleChildOperator(Token::Ominus)
+                if matches!(nodes.nodes[node.left.unwrap()].symbol, StatementSymbol::Literal(Token::Tint(value)) if value == i32::MIN.unsigned_abs()) => {
+                // The smallest int has no literal of its own, so it is computed as `0 - 2147483647 - 1`
+                let subtract = get_operator(&Token::Ominus, &BasicType::Int);
+                let temp_var = self.new_temp_var(BasicType::Int, scope);
+                self.set_span(nodes.get_span(curr_node));
+                self.add_assignment(
+                    temp_var.name.clone(),
+                    TacOperation::binary(subtract, TacValue::Int(0), TacValue::Int(i32::MAX as u32)),
+                );
+                TacOperation::binary(subtract, to_tac_var(&temp_var), TacValue::Int(1))
             },
             StatementSymbol::SingleChildOperator(token) => {
                 let child_op = self._add_statement(
@@ -399,66 +584,28 @@ impl TacProgramBuilder {
                     nodes,
                     scope
                 );
-                let child_var = self.new_temp_var(self.get_val_size(&child_op.val1), scope);
+                let child_var = self.new_temp_var(get_node_type(nodes, node.left.unwrap()), scope);
+                self.set_span(nodes.get_span(node.left.unwrap()));
                 self.add_assignment(child_var.name.clone(), child_op.clone());
+                self.set_span(nodes.get_span(curr_node));
 
                 match token {
                     Token::Ominus => {
-                        TacOperation {
-                            op: Some(token.clone()),
-                            val1: TacValue::Int(0),
-                            val2: Some(TacValue::Var(child_var.name.clone())),
-                        }
+                        TacOperation::binary(
+                            get_operator(token, &child_var.var_type),
+                            to_literal(0, &child_var.var_type),
+                            to_tac_var(&child_var),
+                        )
                     },
                     Token::Soparen => {
-                        TacOperation {
-                            op: None,
-                            val1: TacValue::Var(child_var.name.clone()),
-                            val2: None,
-                        }
+                        TacOperation::value(to_tac_var(&child_var), child_var.var_type.clone())
                     },
-                    Token::Knot => {
-                        // Check if var greater than 0
-                        let temp_var = self.new_temp_var(child_var.get_size(), scope);
-                        let op = TacOperation {
-                            op: Some(Token::Ogt),
-                            val1: TacValue::Var(child_var.name.clone()),
-                            val2: Some(TacValue::Int(0)),
-                        };
-                        self.add_assignment(temp_var.name.clone(), op);
-
-                        // Add if statement
-                        let (else_label, end_label) = self._add_if(temp_var.name.clone(), true);
-                        
-                        // Create result var
-                        let result_var = self.new_temp_var(4, scope);
-
-                        // Add true assignment (set to 0)
-                        let true_assignment = TacOperation {
-                            op: None,
-                            val1: TacValue::Int(0),
-                            val2: None,
-                        };
-                        self.add_assignment(result_var.name.clone(), true_assignment);
-                        self.add_goto(end_label.clone());
-
-                        // Add false assignment (set to 1)
-                        self.add_label(else_label.clone());
-                        let false_assignment = TacOperation {
-                            op: None,
-                            val1: TacValue::Int(1),
-                            val2: None,
-                        };
-                        self.add_assignment(result_var.name.clone(), false_assignment);
-
-                        // Add end label
-                        self.add_label(end_label.clone());
-
-                        // Return result var
-                        TacOperation {
-                            op: None,
-                            val1: TacValue::Var(result_var.name.clone()),
-                            val2: None,
+                    Token::Kint | Token::Kdouble => {
+                        let target_type = get_node_type(nodes, curr_node);
+                        match TacOperator::get_conversion(&child_var.var_type, &target_type) {
+                            Some(conversion) => TacOperation::conversion(conversion, to_tac_var(&child_var)),
+                            // Casting to the same type
+                            None => TacOperation::value(to_tac_var(&child_var), child_var.var_type.clone()),
                         }
                     },
                     _ => {
@@ -466,6 +613,9 @@ impl TacProgramBuilder {
                     },
                 }
             },
+            StatementSymbol::Operator(Token::Kand | Token::Kor) => {
+                self.add_logical_value(curr_node, nodes, scope)
+            },
             StatementSymbol::Operator(token) => {
                 let left_op = self._add_statement(
                     node.left.unwrap(),
@@ -477,22 +627,13 @@ impl TacProgramBuilder {
                     nodes,
                     scope
                 );
+                let left_type = left_op.op_type.clone();
 
-                let left_val = if let Some(_) = left_op.op {
-                    let temp_var = self.new_temp_var(self.get_val_size(&left_op.val1), scope);
-                    self.add_assignment(temp_var.name.clone(), left_op.clone());
-                    TacValue::Var(temp_var.name.clone())
-                } else {
-                    left_op.val1
-                };
-
-                let right_val = if let Some(_) = right_op.op {
-                    let temp_var = self.new_temp_var(self.get_val_size(&right_op.val1), scope);
-                    self.add_assignment(temp_var.name.clone(), right_op.clone());
-                    TacValue::Var(temp_var.name.clone())
-                } else {
-                    right_op.val1
-                };
+                self.set_span(nodes.get_span(node.left.unwrap()));
+                let left_val = self.add_value(left_op, scope);
+                self.set_span(nodes.get_span(node.right.unwrap()));
+                let right_val = self.add_value(right_op, scope);
+                self.set_span(nodes.get_span(curr_node));
 
                 match token {
                     Token::Oplus |
@@ -506,99 +647,8 @@ impl TacProgramBuilder {
                     Token::Ogt |
                     Token::Olte |
                     Token::Ogte => {
-                        TacOperation {
-                            op: Some(token.clone()),
-                            val1: left_val,
-                            val2: Some(right_val),
-                        }
-                    },
-                    Token::Kand => {
-                        // Check if greater than 0
-                        let op = TacOperation {
-                            op: Some(Token::Ogt),
-                            val1: left_val,
-                            val2: Some(TacValue::Int(0)),
-                        };
-                        let temp_var = self.new_temp_var(self.get_val_size(&op.val1), scope);
-                        self.add_assignment(temp_var.name.clone(), op);
-
-                        // Add if statement
-                        let (else_label, end_label) = self._add_if(temp_var.name.clone(), true);
-                        
-                        // Create result var
-                        let result_var = self.new_temp_var(4, scope);
-
-                        // Add true assignment (check right statement)
-                        let true_assignment = TacOperation {
-                            op: Some(Token::Ogt),
-                            val1: right_val,
-                            val2: Some(TacValue::Int(0)),
-                        };
-                        self.add_assignment(result_var.name.clone(), true_assignment);
-                        self.add_goto(end_label.clone());
-
-                        // Add false assignment (set to 0)
-                        self.add_label(else_label.clone());
-                        let false_assignment = TacOperation {
-                            op: None,
-                            val1: TacValue::Int(0),
-                            val2: None,
-                        };
-                        self.add_assignment(result_var.name.clone(), false_assignment);
-
-                        // Add end label
-                        self.add_label(end_label.clone());
-
-                        // Return result var
-                        TacOperation {
-                            op: None,
-                            val1: TacValue::Var(result_var.name.clone()),
-                            val2: None,
-                        }
-                    },
-                    Token::Kor => {
-                        // Check if greater than 0
-                        let op = TacOperation {
-                            op: Some(Token::Ogt),
-                            val1: left_val,
-                            val2: Some(TacValue::Int(0)),
-                        };
-                        let temp_var = self.new_temp_var(self.get_val_size(&op.val1), scope);
-                        self.add_assignment(temp_var.name.clone(), op);
-
-                        // Add if statement
-                        let (else_label, end_label) = self._add_if(temp_var.name.clone(), true);
-                        
-                        // Create result var
-                        let result_var = self.new_temp_var(4, scope);
-
-                        // Add true assignment (set to 1)
-                        let true_assignment = TacOperation {
-                            op: None,
-                            val1: TacValue::Int(1),
-                            val2: None,
-                        };
-                        self.add_assignment(result_var.name.clone(), true_assignment);
-                        self.add_goto(end_label.clone());
-
-                        // Add false assignment (check right statement)
-                        self.add_label(else_label.clone());
-                        let false_assignment = TacOperation {
-                            op: Some(Token::Ogt),
-                            val1: right_val,
-                            val2: Some(TacValue::Int(0)),
-                        };
-                        self.add_assignment(result_var.name.clone(), false_assignment);
-
-                        // Add end label
-                        self.add_label(end_label.clone());
-
-                        // Return result var
-                        TacOperation {
-                            op: None,
-                            val1: TacValue::Var(result_var.name.clone()),
-                            val2: None,
-                        }
+                        // Both operands have the same type, ints being converted when mixed with doubles
+                        TacOperation::binary(get_operator(token, &left_type), left_val, right_val)
                     },
                     token => {
                         panic!("Invalid operator {:?}", token);
@@ -608,62 +658,103 @@ impl TacProgramBuilder {
         }
     }
 
-    fn set_size(&mut self, var: String, size: u32, instances: u32) {
-        if self.funcs[self.curr_func].var_sizes.len() == 0 {
-            self.funcs[self.curr_func].var_sizes.push((var, (size, instances)));
-            return;
-        }
+    /**
+     * Get the size of a variable of the current function.
+     */
+    fn get_var_size(&self, var: &str) -> u32 {
+        self.funcs[self.curr_func].frame.get_slot(var).map_or(0, |slot| slot.size)
+    }
+}
 
-        let mut low = 0;
-        let mut high = self.funcs[self.curr_func].var_sizes.len();
+/**
+ * Get the type the semantic analysis gave a node. A node it has left untyped (e.g. the parenthesis around
+ * a cast) has the type of its child, or is an int.
+ */
+fn get_node_type(tree: &StatementTree, node: usize) -> BasicType {
+    match (&tree.nodes[node].node_type, tree.nodes[node].left) {
+        (Some(node_type), _) => node_type.clone(),
+        (None, Some(left)) if matches!(tree.nodes[node].symbol, StatementSymbol::SingleChildOperator(_)) => get_node_type(tree, left),
+        (None, _) => BasicType::Int,
+    }
+}
 
-        while low < high {
-            let mid = (low + high) / 2;
-            if self.funcs[self.curr_func].var_sizes[mid].0 == var {
-                if self.funcs[self.curr_func].var_sizes[mid].1.0 < size {
-                    self.funcs[self.curr_func].var_sizes[mid].1.0 = size;
-                }
-                return;
-            } else if self.funcs[self.curr_func].var_sizes[mid].0 < var {
-                low = mid + 1;
-            } else {
-                high = mid;
-            }
-        }
+fn get_tree_type(tree: &StatementTree) -> BasicType {
+    tree.start.map_or(BasicType::Int, |start| get_node_type(tree, start))
+}
 
-        self.funcs[self.curr_func].var_sizes.insert(low, (var, (size, instances)));
-    }
-
-    pub fn get_val_size(&self, val: &TacValue) -> u32 {
-        match val {
-            TacValue::Int(_) => 4,
-            TacValue::Double(_) => 4,
-            TacValue::Var(var) => {
-                let mut low = 0;
-                let mut high = self.funcs[self.curr_func].var_sizes.len();
-
-                while low < high {
-                    let mid = (low + high) / 2;
-                    if self.funcs[self.curr_func].var_sizes[mid].0 == *var {
-                        let size = self.funcs[self.curr_func].var_sizes[mid].1;
-                        return size.0 * size.1;
-                    } else if self.funcs[self.curr_func].var_sizes[mid].0 < *var {
-                        low = mid + 1;
-                    } else {
-                        high = mid;
-                    }
-                }
+/**
+ * Get the variable of the 3-TAC for a declaration (or a temp var).
+ */
+fn to_tac_var(decl: &SymbolDecl) -> TacValue {
+    TacValue::Var(decl.name.clone(), decl.var_type.clone())
+}
 
-                // Check parameters
-                for param in self.funcs[self.curr_func].params.iter() {
-                    if param.0 == *var {
-                        return param.1;
-                    }
-                }
+/**
+ * Get a literal of the type, e.g. `0` or `0.0`.
+ */
+fn to_literal(value: u32, var_type: &BasicType) -> TacValue {
+    match var_type {
+        BasicType::Double => TacValue::Double(value as f64),
+        _ => TacValue::Int(value),
+    }
+}
+
+fn get_operator(token: &Token, operand_type: &BasicType) -> TacOperator {
+    match TacOperator::from_token(token, operand_type) {
+        Some(op) => op,
+        None => panic!("Invalid operator {:?} for {}", token, operand_type),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intermediate_code_generation::layout::TargetLayout;
+    use crate::session::{CompileError, Session};
+    use crate::syntax_semantic_analysis::semantic_analysis::SemanticErrorType;
+
+    // Prints its argument, to see which calls are made
+    const TRACE: &str = "def int f(int x)\nprint x;\nreturn x\nfed;\n";
+
+    fn run(source: &str) -> String {
+        let mut output = Vec::new();
+        Session::from_bytes("test.cp", source.as_bytes()).run(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn and_skips_its_right_side_when_the_left_one_is_false() {
+        assert_eq!(run(&format!("{}if f(0) > 0 and f(1) > 0 then print 10 fi\n.", TRACE)), "0\n");
+        assert_eq!(run(&format!("{}if f(1) > 0 and f(2) > 0 then print 10 fi\n.", TRACE)), "1\n2\n10\n");
+    }
 
-                return 0;
+    #[test]
+    fn or_skips_its_right_side_when_the_left_one_is_true() {
+        assert_eq!(run(&format!("{}if f(2) > 0 or f(3) > 0 then print 11 fi\n.", TRACE)), "2\n11\n");
+        assert_eq!(run(&format!("{}if f(0) > 0 or f(3) > 0 then print 11 fi\n.", TRACE)), "0\n3\n11\n");
+    }
+
+    #[test]
+    fn short_circuits_when_used_as_a_value() {
+        assert_eq!(run(&format!("{}int c;\nc = f(0) > 0 and f(1) > 0;\nprint c;\nc = f(2) > 0 or f(3) > 0;\nprint c\n.", TRACE)), "0\n0\n2\n1\n");
+    }
+
+    #[test]
+    fn logical_operators_see_negative_values_as_false() {
+        // Conditions are true when they aren't zero, while `and`, `or` and `not` only see positive values as true
+        let source = "int n, c;\nn = 0 - 5;\nif n then print 1 fi;\nc = not n;\nprint c;\nc = n and 1;\nprint c;\nc = n or 0;\nprint c;\nif not n then print 2 fi\n.";
+        assert_eq!(run(source), "1\n1\n0\n0\n2\n");
+    }
+
+    #[test]
+    fn frame_too_large_with_temporaries_is_an_error() {
+        let mut session = Session::from_bytes("test.cp", b"double d;\nd = 1.5;\nd = d * 2.0 + d * 3.0 + d;\nprint d\n.");
+        session.set_target_layout(TargetLayout::parse("double=1073741824").unwrap());
+        match session.tac_program() {
+            Err(CompileError::Semantic(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert!(matches!(errors[0].get_error_type(), SemanticErrorType::FrameTooLarge(function) if function == "main"));
             },
-            _ => 0,
+            result => panic!("expected a semantic error, got {:?}", result),
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,676 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    io::Write,
+};
+
+use crate::{
+    diagnostics::{Diagnostic, ToDiagnostic},
+    intermediate_code_generation::{
+        layout::{FrameLayout, FrameMap, TargetLayout},
+        tac::{TacCommand, TacOperation, TacOperator, TacProgram, TacStatement, TacValue},
+    },
+    logger::Loggable,
+    source_map::Span,
+    syntax_semantic_analysis::symbol_declaration::BasicType,
+};
+
+// Deeper calls are reported as a stack overflow, most likely an endless recursion
+const MAX_CALL_DEPTH: usize = 10000;
+// The most values the frames of the calls can hold together, to report arrays too large to run instead of aborting
+const MAX_MEMORY_CELLS: usize = 1 << 24;
+
+/**
+ * A value held by a variable (or an array element) while the program runs.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuntimeValue {
+    Int(i32),
+    Double(f64),
+}
+
+impl RuntimeValue {
+    fn zero(var_type: &BasicType) -> RuntimeValue {
+        match var_type {
+            BasicType::Double => RuntimeValue::Double(0.0),
+            BasicType::Array(element_type, _) => RuntimeValue::zero(element_type),
+            _ => RuntimeValue::Int(0),
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        match self {
+            RuntimeValue::Int(value) => value == 0,
+            RuntimeValue::Double(value) => value == 0.0,
+        }
+    }
+
+    fn get_type(self) -> BasicType {
+        match self {
+            RuntimeValue::Int(_) => BasicType::Int,
+            RuntimeValue::Double(_) => BasicType::Double,
+        }
+    }
+}
+
+impl Display for RuntimeValue {
+    // Doubles always have a decimal point (or an exponent), e.g. `3.0`
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RuntimeValue::Int(value) => write!(f, "{}", value),
+            RuntimeValue::Double(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RuntimeErrorType {
+    DivisionByZero(TacOperator),
+    ArithmeticOverflow(String),
+    IndexOutOfBounds(String),
+    StackOverflow(String),
+    MissingReturn(String),
+    InvalidProgram(String),
+    OutputError(String),
+    OutOfMemory(String),
+}
+
+/**
+ * An error that stopped the program, with the statement of the 3-TAC that was running,
+ * the function it belongs to and, if the statement was generated from the source, the span it comes from.
+ */
+#[derive(Debug)]
+pub struct RuntimeError {
+    // Boxed to keep the results of the interpreter small
+    error_type: Box<RuntimeErrorType>,
+    span: Option<Span>,
+    statement: String,
+    function: String,
+}
+
+impl RuntimeError {
+    pub fn get_error_type(&self) -> &RuntimeErrorType {
+        &self.error_type
+    }
+
+    pub fn get_span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Loggable for RuntimeError {
+    fn to_log_message(&self) -> String {
+        let location = match &self.span {
+            Some(span) => format!("on line {}, column {}", span.line, span.column),
+            None if self.function.is_empty() => String::from("at the start of the program"),
+            None => format!("in {}", self.function),
+        };
+        match self.error_type.as_ref() {
+            RuntimeErrorType::DivisionByZero(op) => format!("Division by zero {}: {}", location, op.to_log_message()),
+            RuntimeErrorType::ArithmeticOverflow(operation) => format!("Arithmetic overflow {}: {}", location, operation),
+            RuntimeErrorType::IndexOutOfBounds(msg) => format!("Index out of bounds {}: {}", location, msg),
+            RuntimeErrorType::StackOverflow(func) => format!("Stack overflow {}: {}", location, func),
+            RuntimeErrorType::MissingReturn(func) => format!("Missing return {}: {}", location, func),
+            RuntimeErrorType::InvalidProgram(msg) => format!("Invalid program {}: {}", location, msg),
+            RuntimeErrorType::OutputError(msg) => format!("Output error {}: {}", location, msg),
+            RuntimeErrorType::OutOfMemory(func) => format!("Out of memory {}: {}", location, func),
+        }
+    }
+}
+
+impl ToDiagnostic for RuntimeError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let diagnostic = match self.error_type.as_ref() {
+            RuntimeErrorType::DivisionByZero(op) => Diagnostic::error(
+                "E0301",
+                String::from(match op {
+                    TacOperator::IntMod | TacOperator::DoubleMod => "attempt to calculate the remainder with a divisor of zero",
+                    _ => "attempt to divide by zero",
+                }),
+                self.span,
+            ).with_label(String::from("the divisor is zero")),
+            RuntimeErrorType::ArithmeticOverflow(operation) => Diagnostic::error(
+                "E0302",
+                format!("arithmetic overflow: `{}` doesn't fit in an int", operation),
+                self.span,
+            ).with_label(String::from("attempt to compute a value out of the range of an int")),
+            RuntimeErrorType::IndexOutOfBounds(msg) => Diagnostic::error(
+                "E0303",
+                format!("index out of bounds: {}", msg),
+                self.span,
+            ).with_label(String::from("index out of bounds")),
+            RuntimeErrorType::StackOverflow(func) => Diagnostic::error(
+                "E0304",
+                format!("stack overflow: more than {} nested calls", MAX_CALL_DEPTH),
+                self.span,
+            ).with_label(format!("while calling `{}`", func)),
+            RuntimeErrorType::MissingReturn(func) => Diagnostic::error(
+                "E0305",
+                format!("function `{}` ended without returning a value", func),
+                self.span,
+            ).with_label(String::from("called here")),
+            RuntimeErrorType::InvalidProgram(msg) => Diagnostic::error(
+                "E0306",
+                format!("invalid 3-TAC: {}", msg),
+                self.span,
+            ),
+            RuntimeErrorType::OutputError(msg) => Diagnostic::error(
+                "E0307",
+                format!("could not write the output of the program: {}", msg),
+                self.span,
+            ),
+            RuntimeErrorType::OutOfMemory(func) => Diagnostic::error(
+                "E0308",
+                format!("out of memory: the variables of the calls take more than {} values", MAX_MEMORY_CELLS),
+                self.span,
+            ).with_label(format!("while calling `{}`", func))
+            .with_help(String::from("make the arrays smaller")),
+        };
+        // No function is running yet when the frame of the main program can't be made
+        let note = match self.function.is_empty() {
+            true => format!("while running `{}`", self.statement),
+            false => format!("while running `{}` in `{}`", self.statement, self.function),
+        };
+        diagnostic.with_note(note, None)
+    }
+}
+
+/**
+ * Where the variables of a function are stored in its frame's memory: one value per int or double,
+ * and one per element of an array, in the order of the frame's slots.
+ */
+struct FunctionMemory<'a> {
+    layout: &'a FrameLayout,
+    // The index of each variable's first value and its number of values
+    cells: HashMap<&'a str, (usize, u32)>,
+    size: usize,
+}
+
+impl<'a> FunctionMemory<'a> {
+    fn new(layout: &'a FrameLayout) -> FunctionMemory<'a> {
+        let mut cells = HashMap::new();
+        let mut size = 0;
+        for slot in layout.slots.iter() {
+            let length = match slot.var_type {
+                BasicType::Array(_, length) => length,
+                _ => 1,
+            };
+            cells.insert(slot.name.as_str(), (size, length));
+            size += length as usize;
+        }
+        FunctionMemory { layout, cells, size }
+    }
+}
+
+struct Frame<'a> {
+    function: &'a str,
+    memory: Vec<RuntimeValue>,
+    // Where the caller continues after the call, and the variable getting the returned value
+    return_address: usize,
+    return_var: Option<&'a str>,
+    // The number of params pushed when the function was called, and how many of them `GetParams` has read
+    params_top: usize,
+    params_read: usize,
+}
+
+/**
+ * Runs a 3-TAC program, statement by statement, starting with the `Goto main0` at its top.
+ *
+ * Every call gets a frame holding the variables of the function, laid out as in the frame map, all of them
+ * starting at zero. `PushParam` pushes a value for the next call, `GetParams` reads them from the last one pushed
+ * and `PopParams` removes them once the call returns. The built-in `print` writes the last value pushed.
+ */
+pub struct Interpreter<'a> {
+    program: &'a TacProgram,
+    target: &'a TargetLayout,
+    // The span of the source each statement was generated from, if known
+    spans: &'a [Span],
+    labels: HashMap<&'a str, usize>,
+    functions: HashMap<&'a str, FunctionMemory<'a>>,
+    call_stack: Vec<Frame<'a>>,
+    // The number of values held by the frames of the call stack
+    memory_used: usize,
+    params: Vec<RuntimeValue>,
+    pc: usize,
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(program: &'a TacProgram, frame_map: &'a FrameMap, target: &'a TargetLayout) -> Interpreter<'a> {
+        let mut labels = HashMap::new();
+        for (i, statement) in program.iter().enumerate() {
+            if let TacStatement::Label(label) = statement {
+                labels.insert(label.as_str(), i);
+            }
+        }
+
+        Interpreter {
+            program,
+            target,
+            spans: &[],
+            labels,
+            functions: frame_map.iter().map(|frame| (frame.function.as_str(), FunctionMemory::new(frame))).collect(),
+            call_stack: Vec::new(),
+            memory_used: 0,
+            params: Vec::new(),
+            pc: 0,
+        }
+    }
+
+    /**
+     * Set the span of the source each statement was generated from, for runtime errors to point at.
+     */
+    pub fn with_spans(mut self, spans: &'a [Span]) -> Interpreter<'a> {
+        self.spans = spans;
+        self
+    }
+
+    /**
+     * Run the program until the end of the main program, writing what it prints to `output`.
+     */
+    pub fn run<W: Write>(&mut self, output: &mut W) -> Result<(), RuntimeError> {
+        self.pc = 0;
+        self.call_stack.clear();
+        self.memory_used = 0;
+        self.params.clear();
+        self.push_frame("main0", 0, None)?;
+
+        let program = self.program;
+        while self.pc < program.len() {
+            let statement = &program[self.pc];
+            self.pc += 1;
+            match statement {
+                TacStatement::Label(_) => {},
+                TacStatement::Assignment(var, op) => match &op.val1 {
+                    // The returned value is assigned by the `Return`
+                    TacValue::LCallArgs(func) => self.call(func, Some(var), output)?,
+                    TacValue::GetParams(_) => {
+                        let value = self.get_param()?;
+                        self.store(var, value)?;
+                    },
+                    _ => {
+                        let value = self.evaluate_operation(op)?;
+                        self.store(var, value)?;
+                    },
+                },
+                TacStatement::PointerAssignment(arr, index, op) => {
+                    let value = self.evaluate_operation(op)?;
+                    let cell = self.get_element_cell(arr, index)?;
+                    self.get_frame_mut().memory[cell] = value;
+                },
+                TacStatement::Command(command, val) => {
+                    if !self.run_command(command, val.as_ref(), output)? {
+                        return Ok(());
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Run a command, returning whether the program goes on.
+     */
+    fn run_command<W: Write>(&mut self, command: &'a TacCommand, val: Option<&'a TacValue>, output: &mut W) -> Result<bool, RuntimeError> {
+        match (command, val) {
+            // The frame is created by the call
+            (TacCommand::BeginFunc, _) => {},
+            (TacCommand::Goto, Some(TacValue::Label(label))) => self.jump(label)?,
+            (TacCommand::IfZ, Some(TacValue::IfArgs(cond, label))) => {
+                if self.load(cond)?.is_zero() {
+                    self.jump(label)?;
+                }
+            },
+            (TacCommand::PushParam, Some(val)) => {
+                let value = self.evaluate(val)?;
+                self.params.push(value);
+            },
+            (TacCommand::PopParams, Some(TacValue::Int(size))) => self.pop_params(*size)?,
+            (TacCommand::LCall, Some(TacValue::Label(func))) => self.call(func, None, output)?,
+            (TacCommand::Return, val) => {
+                let value = match val {
+                    Some(val) => Some(self.evaluate(val)?),
+                    None => None,
+                };
+                return self.return_from_call(value);
+            },
+            (TacCommand::EndFunc, _) => {
+                if self.call_stack.len() > 1 {
+                    let frame = self.call_stack.pop().unwrap();
+                    let error_type = RuntimeErrorType::MissingReturn(String::from(frame.function));
+                    // Pointing at the call, the end of the function isn't in the source
+                    return Err(self.error_at(error_type, frame.return_address - 1));
+                }
+                return Ok(false);
+            },
+            (command, _) => {
+                return Err(self.error(RuntimeErrorType::InvalidProgram(
+                    format!("invalid argument for {}", command.to_log_message())
+                )));
+            },
+        }
+        Ok(true)
+    }
+
+    fn call<W: Write>(&mut self, func: &'a str, return_var: Option<&'a str>, output: &mut W) -> Result<(), RuntimeError> {
+        if func == "print" && !self.labels.contains_key(func) {
+            let value = match self.params.last() {
+                Some(value) => *value,
+                None => return Err(self.error(RuntimeErrorType::InvalidProgram(String::from("print called without a param")))),
+            };
+            return writeln!(output, "{}", value)
+                .map_err(|e| self.error(RuntimeErrorType::OutputError(e.to_string())));
+        }
+
+        let return_address = self.pc;
+        self.push_frame(func, return_address, return_var)?;
+        self.jump(func)
+    }
+
+    fn push_frame(&mut self, func: &'a str, return_address: usize, return_var: Option<&'a str>) -> Result<(), RuntimeError> {
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            return Err(self.error(RuntimeErrorType::StackOverflow(String::from(func))));
+        }
+        let (function, memory) = match self.functions.get_key_value(func) {
+            Some((function, memory)) => (*function, memory),
+            None => return Err(self.error(RuntimeErrorType::InvalidProgram(format!("no frame for the function {}", func)))),
+        };
+
+        if memory.size > MAX_MEMORY_CELLS - self.memory_used {
+            return Err(self.error(RuntimeErrorType::OutOfMemory(String::from(func))));
+        }
+
+        let mut frame_memory = Vec::with_capacity(memory.size);
+        for slot in memory.layout.slots.iter() {
+            let (_, length) = memory.cells[slot.name.as_str()];
+            frame_memory.extend(std::iter::repeat_n(RuntimeValue::zero(&slot.var_type), length as usize));
+        }
+
+        self.memory_used += memory.size;
+        self.call_stack.push(Frame {
+            function,
+            memory: frame_memory,
+            return_address,
+            return_var,
+            params_top: self.params.len(),
+            params_read: 0,
+        });
+        Ok(())
+    }
+
+    /**
+     * Go back to the caller with the returned value, returning whether the program goes on.
+     */
+    fn return_from_call(&mut self, value: Option<RuntimeValue>) -> Result<bool, RuntimeError> {
+        let frame = self.call_stack.pop().unwrap();
+        self.memory_used -= frame.memory.len();
+        if self.call_stack.is_empty() {
+            // Returning from the main program ends it
+            return Ok(false);
+        }
+
+        self.pc = frame.return_address;
+        if let Some(var) = frame.return_var {
+            match value {
+                Some(value) => self.store(var, value)?,
+                None => {
+                    let error_type = RuntimeErrorType::MissingReturn(String::from(frame.function));
+                    return Err(self.error_at(error_type, frame.return_address - 1));
+                },
+            }
+        }
+        Ok(true)
+    }
+
+    /**
+     * Read the next param of the current call, from the last one pushed.
+     */
+    fn get_param(&mut self) -> Result<RuntimeValue, RuntimeError> {
+        let frame = self.get_frame();
+        match frame.params_top.checked_sub(frame.params_read + 1) {
+            Some(index) => {
+                let value = self.params[index];
+                self.get_frame_mut().params_read += 1;
+                Ok(value)
+            },
+            None => Err(self.error(RuntimeErrorType::InvalidProgram(String::from("more params read than pushed")))),
+        }
+    }
+
+    fn pop_params(&mut self, size: u32) -> Result<(), RuntimeError> {
+        let mut popped = 0;
+        while popped < size {
+            match self.params.pop() {
+                Some(value) => popped += self.target.get_size(&value.get_type()).unwrap_or_default(),
+                None => break,
+            }
+        }
+        if popped != size {
+            return Err(self.error(RuntimeErrorType::InvalidProgram(
+                format!("popped {} bytes of params instead of {}", popped, size)
+            )));
+        }
+        Ok(())
+    }
+
+    fn jump(&mut self, label: &str) -> Result<(), RuntimeError> {
+        match self.labels.get(label) {
+            Some(address) => {
+                self.pc = *address;
+                Ok(())
+            },
+            None => Err(self.error(RuntimeErrorType::InvalidProgram(format!("undefined label {}", label)))),
+        }
+    }
+
+    fn evaluate_operation(&self, op: &TacOperation) -> Result<RuntimeValue, RuntimeError> {
+        let val1 = self.evaluate(&op.val1)?;
+        let (op, val2) = match (&op.op, &op.val2) {
+            (None, _) => return Ok(val1),
+            (Some(op), None) => return self.convert(*op, val1),
+            (Some(op), Some(val2)) => (*op, self.evaluate(val2)?),
+        };
+
+        match (val1, val2) {
+            (RuntimeValue::Int(left), RuntimeValue::Int(right)) if op.get_operand_type() == BasicType::Int => {
+                self.apply_int_operator(op, left, right)
+            },
+            (RuntimeValue::Double(left), RuntimeValue::Double(right)) if op.get_operand_type() == BasicType::Double => {
+                self.apply_double_operator(op, left, right)
+            },
+            (left, right) => Err(self.error(RuntimeErrorType::InvalidProgram(format!(
+                "{} applied to {} and {}",
+                op.to_log_message(),
+                left.get_type(),
+                right.get_type()
+            )))),
+        }
+    }
+
+    fn apply_int_operator(&self, op: TacOperator, left: i32, right: i32) -> Result<RuntimeValue, RuntimeError> {
+        let value = match op {
+            TacOperator::IntDiv | TacOperator::IntMod if right == 0 => {
+                return Err(self.error(RuntimeErrorType::DivisionByZero(op)));
+            },
+            TacOperator::IntAdd => left.checked_add(right),
+            TacOperator::IntSub => left.checked_sub(right),
+            TacOperator::IntMul => left.checked_mul(right),
+            TacOperator::IntDiv => left.checked_div(right),
+            TacOperator::IntMod => left.checked_rem(right),
+            TacOperator::IntEqual => Some((left == right) as i32),
+            TacOperator::IntNotEqual => Some((left != right) as i32),
+            TacOperator::IntLess => Some((left < right) as i32),
+            TacOperator::IntGreater => Some((left > right) as i32),
+            TacOperator::IntLessEqual => Some((left <= right) as i32),
+            TacOperator::IntGreaterEqual => Some((left >= right) as i32),
+            _ => None,
+        };
+        value.map(RuntimeValue::Int).ok_or_else(|| self.error(RuntimeErrorType::ArithmeticOverflow(
+            format!("{} {} {}", left, op.to_log_message(), right)
+        )))
+    }
+
+    fn apply_double_operator(&self, op: TacOperator, left: f64, right: f64) -> Result<RuntimeValue, RuntimeError> {
+        let value = match op {
+            TacOperator::DoubleDiv | TacOperator::DoubleMod if right == 0.0 => {
+                return Err(self.error(RuntimeErrorType::DivisionByZero(op)));
+            },
+            TacOperator::DoubleAdd => RuntimeValue::Double(left + right),
+            TacOperator::DoubleSub => RuntimeValue::Double(left - right),
+            TacOperator::DoubleMul => RuntimeValue::Double(left * right),
+            TacOperator::DoubleDiv => RuntimeValue::Double(left / right),
+            TacOperator::DoubleMod => RuntimeValue::Double(left % right),
+            TacOperator::DoubleEqual => RuntimeValue::Int((left == right) as i32),
+            TacOperator::DoubleNotEqual => RuntimeValue::Int((left != right) as i32),
+            TacOperator::DoubleLess => RuntimeValue::Int((left < right) as i32),
+            TacOperator::DoubleGreater => RuntimeValue::Int((left > right) as i32),
+            TacOperator::DoubleLessEqual => RuntimeValue::Int((left <= right) as i32),
+            TacOperator::DoubleGreaterEqual => RuntimeValue::Int((left >= right) as i32),
+            _ => return Err(self.error(RuntimeErrorType::InvalidProgram(format!("{} applied to doubles", op.to_log_message())))),
+        };
+        Ok(value)
+    }
+
+    fn convert(&self, op: TacOperator, value: RuntimeValue) -> Result<RuntimeValue, RuntimeError> {
+        match (op, value) {
+            (TacOperator::IntToDouble, RuntimeValue::Int(value)) => Ok(RuntimeValue::Double(value as f64)),
+            (TacOperator::DoubleToInt, RuntimeValue::Double(value)) => {
+                // Dropping the fractional part
+                let truncated = value.trunc();
+                if truncated >= i32::MIN as f64 && truncated <= i32::MAX as f64 {
+                    Ok(RuntimeValue::Int(truncated as i32))
+                } else {
+                    Err(self.error(RuntimeErrorType::ArithmeticOverflow(format!("(int) {:?}", value))))
+                }
+            },
+            (op, value) => Err(self.error(RuntimeErrorType::InvalidProgram(
+                format!("{} applied to {}", op.to_log_message(), value.get_type())
+            ))),
+        }
+    }
+
+    fn evaluate(&self, val: &TacValue) -> Result<RuntimeValue, RuntimeError> {
+        match val {
+            TacValue::Var(var, _) => self.load(var),
+            TacValue::Int(value) => i32::try_from(*value)
+                .map(RuntimeValue::Int)
+                .map_err(|_| self.error(RuntimeErrorType::ArithmeticOverflow(value.to_string()))),
+            TacValue::Double(value) => Ok(RuntimeValue::Double(*value)),
+            TacValue::PointerAccess(arr, index) => {
+                let cell = self.get_element_cell(arr, index)?;
+                Ok(self.get_frame().memory[cell])
+            },
+            val => Err(self.error(RuntimeErrorType::InvalidProgram(format!("{} is not a value", val.to_log_message())))),
+        }
+    }
+
+    fn load(&self, var: &str) -> Result<RuntimeValue, RuntimeError> {
+        let (cell, _) = self.get_cells(var)?;
+        Ok(self.get_frame().memory[cell])
+    }
+
+    fn store(&mut self, var: &str, value: RuntimeValue) -> Result<(), RuntimeError> {
+        let (cell, _) = self.get_cells(var)?;
+        self.get_frame_mut().memory[cell] = value;
+        Ok(())
+    }
+
+    /**
+     * Get the cell of an element of an array, checking that the index is in its bounds.
+     */
+    fn get_element_cell(&self, arr: &str, index: &TacValue) -> Result<usize, RuntimeError> {
+        let (cell, length) = self.get_cells(arr)?;
+        let index = match self.evaluate(index)? {
+            RuntimeValue::Int(index) => index,
+            RuntimeValue::Double(_) => {
+                return Err(self.error(RuntimeErrorType::InvalidProgram(format!("the index of {} is a double", arr))));
+            },
+        };
+        match u32::try_from(index) {
+            Ok(index) if index < length => Ok(cell + index as usize),
+            _ => Err(self.error(RuntimeErrorType::IndexOutOfBounds(
+                format!("the size of `{}` is {} but the index is {}", self.get_source_name(arr), length, index)
+            ))),
+        }
+    }
+
+    /**
+     * Get the first cell of a variable of the current frame and its number of cells.
+     */
+    fn get_cells(&self, var: &str) -> Result<(usize, u32), RuntimeError> {
+        let function = self.get_frame().function;
+        self.functions.get(function)
+            .and_then(|memory| memory.cells.get(var))
+            .copied()
+            .ok_or_else(|| self.error(RuntimeErrorType::InvalidProgram(format!("{} is not a variable of {}", var, function))))
+    }
+
+    /**
+     * Get the identifier a variable of the current function was declared with, for the messages of errors.
+     */
+    fn get_source_name<'b>(&'b self, var: &'b str) -> &'b str {
+        self.functions.get(self.get_frame().function)
+            .and_then(|memory| memory.layout.get_slot(var))
+            .map_or(var, |slot| slot.source_name.as_str())
+    }
+
+    fn get_frame(&self) -> &Frame<'a> {
+        self.call_stack.last().unwrap()
+    }
+
+    fn get_frame_mut(&mut self) -> &mut Frame<'a> {
+        self.call_stack.last_mut().unwrap()
+    }
+
+    /**
+     * Create an error for the statement being run.
+     */
+    fn error(&self, error_type: RuntimeErrorType) -> RuntimeError {
+        self.error_at(error_type, self.pc.saturating_sub(1))
+    }
+
+    fn error_at(&self, error_type: RuntimeErrorType, address: usize) -> RuntimeError {
+        // A default span means the statement wasn't generated from the source
+        let span = self.spans.get(address).filter(|span| **span != Span::default()).copied();
+        let statement = self.program.get(address).map_or(String::new(), |statement| {
+            statement.to_log_message().trim().trim_end_matches(';').to_string()
+        });
+        let function = self.call_stack.last().map_or(String::new(), |frame| String::from(frame.function));
+        RuntimeError {
+            error_type: Box::new(error_type),
+            span,
+            statement,
+            function,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RuntimeErrorType;
+    use crate::session::{CompileError, Session};
+
+    fn run_error(source: &str) -> RuntimeErrorType {
+        let mut output = Vec::new();
+        match Session::from_bytes("test.cp", source.as_bytes()).run(&mut output) {
+            Err(CompileError::Runtime(error)) => *error.error_type,
+            result => panic!("expected a runtime error, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn index_out_of_bounds_names_the_array_of_the_source() {
+        match run_error("int a[3], i;\ni = 3;\na[i] = 1;\nprint a[0]\n.") {
+            RuntimeErrorType::IndexOutOfBounds(msg) => assert_eq!(msg, "the size of `a` is 3 but the index is 3"),
+            error_type => panic!("expected an index out of bounds, got {:?}", error_type),
+        }
+    }
+
+    #[test]
+    fn frames_too_large_to_run_are_out_of_memory() {
+        assert!(matches!(run_error("int a[100000000];\na[1] = 1;\nprint a[1]\n."), RuntimeErrorType::OutOfMemory(_)));
+
+        // Each call fits, but not all of them together
+        let source = "def int f(int n)\nint a[10000000];\na[0] = n;\nif n > 0 then return f(n - 1) fi;\nreturn a[0]\nfed;\nprint f(3)\n.";
+        assert!(matches!(run_error(source), RuntimeErrorType::OutOfMemory(func) if func == "f1"));
+    }
+}
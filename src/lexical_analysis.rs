@@ -7,26 +7,38 @@ mod constants;
 use std::fs::File;
 use std::io::Read;
 
+use crate::source_map::FileId;
 use transition_table::init_transition_table;
 pub use token::{Token, InvalidToken, LexicalError, ParsedToken};
 use token_buffer::TokenBuffer;
 
-pub fn perform_lexical_analysis(filename: &String) -> Result<Vec<ParsedToken>, LexicalError> {    
+pub fn perform_lexical_analysis(filename: &str, file_id: FileId) -> Result<Vec<ParsedToken>, LexicalError> {
+    // Open file
+    let file = File::open(filename).map_err(|e| LexicalError::FileOpenError(e.to_string()))?;
+    perform_lexical_analysis_from_reader(file, file_id)
+}
+
+pub fn perform_lexical_analysis_from_str(source: &str, file_id: FileId) -> Result<Vec<ParsedToken>, LexicalError> {
+    perform_lexical_analysis_from_bytes(source.as_bytes(), file_id)
+}
+
+pub fn perform_lexical_analysis_from_bytes(source: &[u8], file_id: FileId) -> Result<Vec<ParsedToken>, LexicalError> {
+    perform_lexical_analysis_from_reader(source, file_id)
+}
+
+pub fn perform_lexical_analysis_from_reader<R: Read>(mut reader: R, file_id: FileId) -> Result<Vec<ParsedToken>, LexicalError> {
     // Initialize state table
     let state_table = init_transition_table();
 
-    // Open file
-    let mut file = File::open(filename).map_err(|e| LexicalError::FileOpenError(e.to_string()))?;
-
     // Create double buffer
-    let mut token_buffer = TokenBuffer::new();
+    let mut token_buffer = TokenBuffer::new(file_id);
 
     // Clear first buffer
     for i in 0..constants::BUFFER_SIZE {
         token_buffer.buffers[0][i] = 0;
     }
     // Read first buffer
-    let read_size = file.read(&mut token_buffer.buffers[0]).map_err(|e| LexicalError::FileReadError(e.to_string()))?;
+    let read_size = fill_buffer(&mut reader, &mut token_buffer.buffers[0]).map_err(|e| LexicalError::FileReadError(e.to_string()))?;
     if read_size == 0 {
         // Empty file
         return Err(LexicalError::EmptyFile);
@@ -45,7 +57,7 @@ pub fn perform_lexical_analysis(filename: &String) -> Result<Vec<ParsedToken>, L
             }
 
             // Read next buffer
-            file.read(&mut token_buffer.buffers[prev_buffer]).map_err(|e| LexicalError::FileReadError(e.to_string()))?;
+            fill_buffer(&mut reader, &mut token_buffer.buffers[prev_buffer]).map_err(|e| LexicalError::FileReadError(e.to_string()))?;
             prev_buffer = token_buffer.lb_buffer;
         }
 
@@ -73,4 +85,21 @@ pub fn perform_lexical_analysis(filename: &String) -> Result<Vec<ParsedToken>, L
         return Err(LexicalError::NoValidTokens);
     }
     Ok(tokens)
-}
\ No newline at end of file
+}
+
+/**
+ * Read from the source until the buffer is full or the end of the input is reached.
+ * A single read on a pipe or socket can return less than a full buffer before the end of the input.
+ */
+fn fill_buffer<R: Read>(reader: &mut R, buffer: &mut [u8]) -> std::io::Result<usize> {
+    let mut total = 0;
+    while total < buffer.len() {
+        match reader.read(&mut buffer[total..]) {
+            Ok(0) => break,
+            Ok(read_size) => total += read_size,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
+}
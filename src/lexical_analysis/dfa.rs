@@ -46,7 +46,7 @@ fn get_keyword_identifier_dfa() -> Dfa {
     let alph_state_diff = init_count - 2;
     for keyword in KEYWORDS {
         let mut chars = keyword.0.chars();
-        let curr_char = chars.nth(0).unwrap();
+        let curr_char = chars.next().unwrap();
         let mut prev_index = key_id_alphabet.iter().position(|x| *x == curr_char).unwrap() - alph_state_diff;
         let mut state_transitions: Vec<usize> = vec![];
 
@@ -1,9 +1,25 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::diagnostics::{Diagnostic, ToDiagnostic};
 use crate::logger::Loggable;
+use crate::source_map::Span;
 
 #[derive(Debug)]
 pub struct InvalidToken {
     pub lexeme: String,
-    pub line: usize,
+    pub span: Span,
+}
+
+impl Loggable for InvalidToken {
+    fn to_log_message(&self) -> String {
+        format!("{:?} on line {}, column {}", self.lexeme, self.span.line, self.span.column)
+    }
+}
+
+impl ToDiagnostic for InvalidToken {
+    fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error("E0001", format!("invalid token `{}`", self.lexeme), Some(self.span))
+    }
 }
 
 #[derive(Debug)]
@@ -29,11 +45,11 @@ impl Loggable for LexicalError {
 
                 let mut log_message = String::from("Invalid tokens:");
                 for token in tokens {
-                    log_message.push_str(format!("\n{:?}", token).as_str());
+                    log_message.push_str(format!("\n{}", token.to_log_message()).as_str());
                 }
                 log_message
             }
-            LexicalError::InvalidToken(token) => format!("Invalid token: {:?}", token),
+            LexicalError::InvalidToken(token) => format!("Invalid token: {}", token.to_log_message()),
             LexicalError::EmptyFile => String::from("File is empty"),
             LexicalError::EndOfFile => String::from("End of file"),
             LexicalError::NoValidTokens => String::from("No valid tokens found"),
@@ -42,6 +58,23 @@ impl Loggable for LexicalError {
 
 }
 
+impl LexicalError {
+    /**
+     * Get one diagnostic per error (invalid tokens are reported separately).
+     */
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            LexicalError::InvalidTokens(tokens) => tokens.iter().map(|token| token.to_diagnostic()).collect(),
+            LexicalError::InvalidToken(token) => vec![token.to_diagnostic()],
+            LexicalError::FileOpenError(e) => vec![Diagnostic::error("E0002", format!("could not open source file: {}", e), None)],
+            LexicalError::FileReadError(e) => vec![Diagnostic::error("E0003", format!("could not read source file: {}", e), None)],
+            LexicalError::EmptyFile => vec![Diagnostic::error("E0004", String::from("source file is empty"), None)],
+            LexicalError::EndOfFile => vec![Diagnostic::error("E0005", String::from("unexpected end of file"), None)],
+            LexicalError::NoValidTokens => vec![Diagnostic::error("E0006", String::from("no valid tokens found"), None)],
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Identifier(String),
@@ -136,49 +169,50 @@ impl Token {
             Token::Scbracket => 37,
         }
     }
+}
 
-    pub fn to_string(&self) -> String {
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
-            Token::Identifier(s) => s.clone(),
-            Token::Tint(i) => i.to_string(),
-            Token::Tdouble(d) => d.to_string(),
-            Token::Kif => String::from("if"),
-            Token::Kthen => String::from("then"),
-            Token::Kelse => String::from("else"),
-            Token::Kfi => String::from("fi"),
-            Token::Kwhile => String::from("while"),
-            Token::Kdo => String::from("do"),
-            Token::Kod => String::from("od"),
-            Token::Kdef => String::from("def"),
-            Token::Kfed => String::from("fed"),
-            Token::Kreturn => String::from("return"),
-            Token::Kand => String::from("and"),
-            Token::Kor => String::from("or"),
-            Token::Knot => String::from("not"),
-            Token::Kint => String::from("int"),
-            Token::Kdouble => String::from("double"),
-            Token::Kprint => String::from("print"),
-            Token::Oplus => String::from("+"),
-            Token::Ominus => String::from("-"),
-            Token::Omultiply => String::from("*"),
-            Token::Odivide => String::from("/"),
-            Token::Omod => String::from("%"),
-            Token::Oassign => String::from("="),
-            Token::Oequal => String::from("=="),
-            Token::Olt => String::from("<"),
-            Token::Olte => String::from("<="),
-            Token::Ogt => String::from(">"),
-            Token::Ogte => String::from(">="),
-            Token::Onot => String::from("<>"),
-            Token::Scomma => String::from(","),
-            Token::Ssemicolon => String::from(";"),
-            Token::Speriod => String::from("."),
-            Token::Soparen => String::from("("),
-            Token::Scparen => String::from(")"),
-            Token::Sobracket => String::from("["),
-            Token::Scbracket => String::from("]"),
+            Token::Identifier(s) => write!(f, "{}", s),
+            Token::Tint(i) => write!(f, "{}", i),
+            Token::Tdouble(d) => write!(f, "{}", d),
+            Token::Kif => write!(f, "if"),
+            Token::Kthen => write!(f, "then"),
+            Token::Kelse => write!(f, "else"),
+            Token::Kfi => write!(f, "fi"),
+            Token::Kwhile => write!(f, "while"),
+            Token::Kdo => write!(f, "do"),
+            Token::Kod => write!(f, "od"),
+            Token::Kdef => write!(f, "def"),
+            Token::Kfed => write!(f, "fed"),
+            Token::Kreturn => write!(f, "return"),
+            Token::Kand => write!(f, "and"),
+            Token::Kor => write!(f, "or"),
+            Token::Knot => write!(f, "not"),
+            Token::Kint => write!(f, "int"),
+            Token::Kdouble => write!(f, "double"),
+            Token::Kprint => write!(f, "print"),
+            Token::Oplus => write!(f, "+"),
+            Token::Ominus => write!(f, "-"),
+            Token::Omultiply => write!(f, "*"),
+            Token::Odivide => write!(f, "/"),
+            Token::Omod => write!(f, "%"),
+            Token::Oassign => write!(f, "="),
+            Token::Oequal => write!(f, "=="),
+            Token::Olt => write!(f, "<"),
+            Token::Olte => write!(f, "<="),
+            Token::Ogt => write!(f, ">"),
+            Token::Ogte => write!(f, ">="),
+            Token::Onot => write!(f, "<>"),
+            Token::Scomma => write!(f, ","),
+            Token::Ssemicolon => write!(f, ";"),
+            Token::Speriod => write!(f, "."),
+            Token::Soparen => write!(f, "("),
+            Token::Scparen => write!(f, ")"),
+            Token::Sobracket => write!(f, "["),
+            Token::Scbracket => write!(f, "]"),
         }
-    
     }
 }
 
@@ -201,12 +235,12 @@ impl Loggable for Vec<Token> {
 #[derive(Debug, Clone)]
 pub struct ParsedToken {
     pub token: Token,
-    pub line: usize,
+    pub span: Span,
 }
 
 impl Loggable for ParsedToken {
     fn to_log_message(&self) -> String {
-        format!("{:?} on line {}\n", self.token, self.line)
+        format!("{:?} on line {}, column {}\n", self.token, self.span.line, self.span.column)
     }
 }
 
@@ -1,3 +1,5 @@
+use crate::source_map::{FileId, Span};
+
 use super::constants::BUFFER_SIZE;
 use super::token::{Token, InvalidToken, LexicalError, ParsedToken};
 use super::transition_table::TransitionTable;
@@ -9,10 +11,15 @@ pub struct TokenBuffer {
     pub lb_buffer: usize,
     pub f_buffer: usize,
     lines_read: usize,
+    file_id: FileId,
+    // Byte offsets into the whole input
+    begin_offset: usize,
+    forward_offset: usize,
+    line_start: usize,
 }
 
 impl TokenBuffer {
-    pub fn new() -> TokenBuffer {
+    pub fn new(file_id: FileId) -> TokenBuffer {
         TokenBuffer {
             buffers: [[0; BUFFER_SIZE]; 2],
             lexeme_begin: 0,
@@ -20,6 +27,10 @@ impl TokenBuffer {
             lb_buffer: 0,
             f_buffer: 0,
             lines_read: 0,
+            file_id,
+            begin_offset: 0,
+            forward_offset: 0,
+            line_start: 0,
         }
     }
 
@@ -34,7 +45,7 @@ impl TokenBuffer {
             if is_comment {
                 if c == '\n' {
                     is_comment = false;
-                    self.lines_read += 1;
+                    self.new_line();
                 }
                 c = self.advance_sentinels();
                 continue;
@@ -50,7 +61,7 @@ impl TokenBuffer {
                     }
                 } else {
                     if c == '\n' {
-                        self.lines_read += 1;
+                        self.new_line();
                     }
                 }
                 c = self.advance_sentinels();
@@ -76,7 +87,7 @@ impl TokenBuffer {
 
             // If whitespace, skip
             if c == '\n' {
-                self.lines_read += 1;
+                self.new_line();
             }
             c = self.advance_sentinels();
         }
@@ -89,7 +100,7 @@ impl TokenBuffer {
         let mut state = 0;
         while let Some(new_state) = transition_table.get_next_state(state, c) {
             state = new_state;
-            if states.len() == 0 || state != states.last().unwrap().clone() {
+            if states.is_empty() || state != *states.last().unwrap() {
                 states.push(state);
             }
             
@@ -98,6 +109,7 @@ impl TokenBuffer {
         }
 
         // Check if the last state is an accepting state
+        let span = self.get_lexeme_span();
         if transition_table.is_accepting(state) {
             let lexeme = self.get_lexeme();
             match transition_table.get_token(&states) {
@@ -105,48 +117,52 @@ impl TokenBuffer {
                     // Get lexeme and advance sentinels
                     match token {
                         Token::Identifier(_) => {
-                            return Ok(ParsedToken { token: Token::Identifier(lexeme), line: self.lines_read + 1 });
+                            Ok(ParsedToken { token: Token::Identifier(lexeme), span })
                         },
                         Token::Tint(_) => {
                             // Convert lexeme to integer and return as token
                             if let Ok(int) = lexeme.parse::<u32>() {
-                                return Ok(ParsedToken { token: Token::Tint(int), line: self.lines_read + 1 });
+                                Ok(ParsedToken { token: Token::Tint(int), span })
                             } else {
-                                return Err(LexicalError::InvalidToken(InvalidToken { lexeme, line: self.lines_read + 1 }));
+                                Err(LexicalError::InvalidToken(InvalidToken { lexeme, span }))
                             }
                         },
                         Token::Tdouble(_) => {
                             // Convert lexeme to double and return as token
                             if let Ok(double) = lexeme.parse::<f64>() {
-                                return Ok(ParsedToken { token: Token::Tdouble(double), line: self.lines_read + 1 });
+                                Ok(ParsedToken { token: Token::Tdouble(double), span })
                             } else {
-                                return Err(LexicalError::InvalidToken(InvalidToken { lexeme, line: self.lines_read + 1 }));
+                                Err(LexicalError::InvalidToken(InvalidToken { lexeme, span }))
                             }
                         },
                         _ => {
                             // Return token as is
-                            return Ok(ParsedToken { token, line: self.lines_read + 1 });
+                            Ok(ParsedToken { token, span })
                         },
                     }
                 },
                 None => {
                     // Invalid token
-                    return Err(LexicalError::InvalidToken(InvalidToken { lexeme, line: self.lines_read + 1 }));
+                    Err(LexicalError::InvalidToken(InvalidToken { lexeme, span }))
                 }
             }
         } else {
             // Invalid token
             let lexeme = self.get_lexeme();
 
-            if states.len() == 0 {
+            if states.is_empty() {
                 self.advance_sentinels();
             }
 
-            return Err(LexicalError::InvalidToken(InvalidToken { lexeme, line: self.lines_read + 1 }));
+            Err(LexicalError::InvalidToken(InvalidToken { lexeme, span }))
         }
     }
 
     fn set_sentinels(&mut self, loc: usize) -> char {
+        // loc is always relative to the forward sentinel
+        self.forward_offset += loc - self.forward;
+        self.begin_offset = self.forward_offset;
+
         if loc < self.lexeme_begin {
             self.lexeme_begin = loc;
             self.forward = loc;
@@ -156,13 +172,13 @@ impl TokenBuffer {
             self.lexeme_begin = loc;
             self.forward = loc;
             if self.lexeme_begin >= BUFFER_SIZE {
-                self.lexeme_begin = self.lexeme_begin % BUFFER_SIZE;
+                self.lexeme_begin %= BUFFER_SIZE;
                 self.forward = self.lexeme_begin;
                 self.lb_buffer = (self.lb_buffer + 1) % 2;
                 self.f_buffer = self.lb_buffer;
             }
         }
-        return self.get_forward_char();
+        self.get_forward_char()
     }
 
     fn advance_sentinels(&mut self) -> char {
@@ -171,15 +187,35 @@ impl TokenBuffer {
 
     fn advance_forward(&mut self) -> char {
         self.forward += 1;
+        self.forward_offset += 1;
         if self.forward == BUFFER_SIZE {
             self.forward = 0;
             self.f_buffer = (self.f_buffer + 1) % 2;
         }
-        return self.get_forward_char();
+        self.get_forward_char()
+    }
+
+    fn new_line(&mut self) {
+        self.lines_read += 1;
+        self.line_start = self.forward_offset + 1;
+    }
+
+    /**
+     * Get the span from the lexeme begin to the forward sentinel (at least one character long).
+     */
+    fn get_lexeme_span(&self) -> Span {
+        let end = usize::max(self.forward_offset, self.begin_offset + 1);
+        Span::new(
+            self.file_id,
+            self.begin_offset,
+            end,
+            self.lines_read + 1,
+            self.begin_offset - self.line_start + 1,
+        )
     }
 
     fn get_forward_char(&self) -> char {
-        return self.buffers[self.f_buffer][self.forward] as char;
+        self.buffers[self.f_buffer][self.forward] as char
     }
 
     fn get_char_after_forward(&self) -> char {
@@ -189,7 +225,7 @@ impl TokenBuffer {
             next_forward = 0;
             next_buffer = (next_buffer + 1) % 2;
         }
-        return self.buffers[next_buffer][next_forward] as char;
+        self.buffers[next_buffer][next_forward] as char
     }
 
     /**
@@ -205,9 +241,9 @@ impl TokenBuffer {
             }
         } else {
             lexeme = String::from_utf8_lossy(&self.buffers[self.lb_buffer][self.lexeme_begin..]).to_string();
-            lexeme.push_str(&String::from_utf8_lossy(&self.buffers[self.f_buffer][..self.forward]).to_string());
+            lexeme.push_str(String::from_utf8_lossy(&self.buffers[self.f_buffer][..self.forward]).as_ref());
         }
         self.set_sentinels(self.forward);
-        return lexeme;
+        lexeme
     }
 }
\ No newline at end of file
@@ -8,6 +8,8 @@ struct TokenStates {
     states: Box<[usize]>,
 }
 
+type TokenStatesList = Box<[Option<TokenStates>]>;
+
 #[derive(Debug)]
 pub struct TransitionTable {
     table: Box<[Box<[Option<usize>]>]>,
@@ -15,11 +17,11 @@ pub struct TransitionTable {
     alphabet: Box<[char]>,
     alphabet_indexes: Box<[usize]>,
     alphabet_start: usize,
-    token_map: Box<[Option<Box<[Option<TokenStates>]>>]>,
+    token_map: Box<[Option<TokenStatesList>]>,
 }
 
 impl TransitionTable {
-    fn init(dfas: &Vec<Dfa>) -> TransitionTable {
+    fn init(dfas: &[Dfa]) -> TransitionTable {
         // Get number of states
         let mut state_count = 1;
         for dfa in dfas.iter() {
@@ -72,11 +74,9 @@ impl TransitionTable {
         for i in 0..alphabet_indexes.len() {
             if alphabet_indexes[i] == 0 {
                 let char_index = i + alphabet_start;
-                if char_index >= 0x41 && char_index <= 0x5A {
-                    alphabet_indexes[i] = alpha_index + 1;
-                } else if char_index >= 0x61 && char_index <= 0x7A {
+                if (0x41..=0x5A).contains(&char_index) || (0x61..=0x7A).contains(&char_index) {
                     alphabet_indexes[i] = alpha_index + 1;
-                } else if char_index >= 0x30 && char_index <= 0x39 {
+                } else if (0x30..=0x39).contains(&char_index) {
                     alphabet_indexes[i] = digit_index + 1;
                 }
             }
@@ -142,30 +142,30 @@ impl TransitionTable {
     
         // Add accepting states to table
         for (accept_i, accept) in dfa.accepting.iter().enumerate() {
-            self.accepting[start_state + accept_i] = accept.clone();
+            self.accepting[start_state + accept_i] = *accept;
         }
     
         // Add token map to table
         if let Some(token_map) = &dfa.token_map {
             for (token, states) in token_map.iter() {
-                if states.len() == 0 {
+                if states.is_empty() {
                     panic!("Token map states length is 0 for token {:?}!", token);
                 }
 
                 let mapped_states = (states.iter().map(|x| *x + start_state - 1).collect::<Vec<usize>>()).into_boxed_slice();
-                let last_state = mapped_states.last().unwrap().clone();
+                let last_state = *mapped_states.last().unwrap();
 
-                let curr_token_states = Some(TokenStates { token: token.clone(), states: mapped_states });
+                let curr_token_states = TokenStates { token: token.clone(), states: mapped_states };
                 if let Some(token_list) = &self.token_map[last_state] {
                     // Make sure token doesn't already exist for states
-                    if token_list.contains(&curr_token_states) {
+                    if token_list.contains(&Some(curr_token_states.clone())) {
                         panic!("Token {:?} already exists for state {} in token map!", token, last_state);
                     }
                 }
 
                 // Add token to token map
                 let new_token_states = 
-                    self.add_token_state(&self.token_map[last_state], curr_token_states.unwrap());
+                    self.add_token_state(&self.token_map[last_state], curr_token_states);
                 self.token_map[last_state] = Some(new_token_states);
             }
         }
@@ -174,7 +174,7 @@ impl TransitionTable {
         start_state + dfa.dfa.len() - 1
     }
 
-    fn add_token_state(&self, curr_token_states: &Option<Box<[Option<TokenStates>]>>, new_token_states: TokenStates) -> Box<[Option<TokenStates>]> {
+    fn add_token_state(&self, curr_token_states: &Option<TokenStatesList>, new_token_states: TokenStates) -> TokenStatesList {
         if curr_token_states.is_none() {
             return vec![Some(new_token_states)].into_boxed_slice();
         }
@@ -247,11 +247,11 @@ impl TransitionTable {
     }
 
     pub fn get_token(&self, states: &[usize]) -> Option<Token> {
-        if states.len() == 0 {
+        if states.is_empty() {
             return None;
         }
 
-        if let Some(token_states) = &self.token_map[states.last().unwrap().clone()] {
+        if let Some(token_states) = &self.token_map[*states.last().unwrap()] {
             if token_states.len() == 1 {
                 return Some(token_states[0].clone().unwrap().token.clone());
             } else {
@@ -262,7 +262,7 @@ impl TransitionTable {
             }
         }
 
-        return None;
+        None
     }
 }
 
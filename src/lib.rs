@@ -0,0 +1,11 @@
+pub mod lexical_analysis;
+pub mod syntax_semantic_analysis;
+pub mod ast;
+pub mod intermediate_code_generation;
+pub mod interpreter;
+pub mod logger;
+pub mod source_map;
+pub mod diagnostics;
+mod session;
+
+pub use session::{CompileError, Session};
@@ -11,6 +11,12 @@ impl Loggable for EmptyLog {
     }
 }
 
+impl Loggable for String {
+    fn to_log_message(&self) -> String {
+        self.clone()
+    }
+}
+
 pub struct FileLogAttributes {
     filename: String,
     append: bool,
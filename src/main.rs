@@ -1,107 +1,338 @@
-mod lexical_analysis;
-mod syntax_semantic_analysis;
-mod intermediate_code_generation;
-mod logger;
+mod cli;
 
-use crate::logger::FileLogAttributes;
+use std::{env, fs, io::{self, ErrorKind, IsTerminal}, panic, path::Path, process};
 
-use std::env;
-use lexical_analysis::ParsedToken;
-use syntax_semantic_analysis::symbol_table::SymbolTable;
+use cli::{CliCommand, CliOptions, ColorMode, EmitKind, ErrorFormat, Phase, Verbosity};
+use ezsharp_compiler::{
+    diagnostics::{Diagnostic, DiagnosticRenderer, ToDiagnostic},
+    lexical_analysis::LexicalError,
+    logger::{self, FileLogAttributes, Loggable},
+    syntax_semantic_analysis::{dot, grammar::Grammar, grammar_dump},
+    CompileError,
+    Session,
+};
 
-fn main() {
-    let mut log_folder = String::from("logs");
-    let mut output_file = String::from("o.tac");
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_LEXICAL_ERROR: i32 = 1;
+const EXIT_SYNTAX_ERROR: i32 = 2;
+const EXIT_SEMANTIC_ERROR: i32 = 3;
+const EXIT_INTERNAL_ERROR: i32 = 4;
+const EXIT_RUNTIME_ERROR: i32 = 5;
+const EXIT_USAGE_ERROR: i32 = 64;
 
-    let args: Vec<String> = env::args().collect();
-    if args.len() < 2 {
-        panic!("No input file provided");
-    }
+fn main() {
+    let (options, run) = match cli::parse_args(env::args().skip(1)) {
+        Ok(CliCommand::Compile(options)) => (options, false),
+        Ok(CliCommand::Run(options)) => (options, true),
+        Ok(CliCommand::DumpGrammar(format, output_file)) => {
+            let grammar = match Grammar::get_constant_grammar() {
+                Ok(grammar) => grammar,
+                Err(e) => {
+                    eprintln!("error: internal compiler error: {}", e);
+                    process::exit(EXIT_INTERNAL_ERROR);
+                },
+            };
+            let dump = grammar_dump::dump_grammar(grammar, format);
+            let exit_code = match output_file {
+                Some(path) => match logger::log_to_file(&dump, &FileLogAttributes::new(path.clone(), false)) {
+                    Ok(()) => EXIT_SUCCESS,
+                    Err(e) => {
+                        eprintln!("error: could not write {}: {}", path, e);
+                        EXIT_INTERNAL_ERROR
+                    },
+                },
+                None => {
+                    print!("{}", dump);
+                    EXIT_SUCCESS
+                },
+            };
+            process::exit(exit_code);
+        },
+        Ok(CliCommand::Help) => {
+            println!("{}", cli::USAGE);
+            process::exit(EXIT_SUCCESS);
+        },
+        Ok(CliCommand::Version) => {
+            println!("ezsharp_compiler {}", env!("CARGO_PKG_VERSION"));
+            process::exit(EXIT_SUCCESS);
+        },
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(EXIT_USAGE_ERROR);
+        },
+    };
 
-    let filename = &args[1];
-    if !filename.contains(&String::from(".cp")) {
-        panic!("Unknown file type. Only .cp files are supported.");
-    }
+    // A panic is a bug in the compiler, not in the compiled program
+    panic::set_hook(Box::new(|info| {
+        eprintln!("error: internal compiler error: {}", info);
+    }));
+    let exit_code = panic::catch_unwind(|| compile(&options, run)).unwrap_or(EXIT_INTERNAL_ERROR);
+    process::exit(exit_code);
+}
 
-    if args.len() > 2 {
-        if args.contains(&String::from("--log-folder")) {
-            let log_folder_pos = args.iter().position(|arg| arg == "--log-folder").unwrap() + 1;
-            if log_folder_pos >= args.len() {
-                panic!("No log folder provided");
-            }
-            log_folder = args[log_folder_pos].clone();
+/**
+ * Run the compiler phases up to `options.stop_after` and write the requested outputs,
+ * then run the program if `run` is set.
+ * Returns the exit code.
+ */
+fn compile(options: &CliOptions, run: bool) -> i32 {
+    // Read the program from stdin when the filename is "-"
+    let mut session = if options.filename == "-" {
+        match Session::from_reader("<stdin>", io::stdin()) {
+            Ok(session) => session,
+            Err(e) => return report_errors(&Session::new("-"), e, options),
         }
+    } else {
+        Session::new(&options.filename)
+    };
+    for (lint, level) in options.lint_levels.iter() {
+        session.set_lint_level(*lint, *level);
+    }
+    session.set_target_layout(options.target_layout.clone());
 
-        if args.contains(&String::from("--output")) {
-            let output_file_pos = args.iter().position(|arg| arg == "--output").unwrap() + 1;
-            if output_file_pos >= args.len() {
-                panic!("No output file provided");
-            }
-            output_file = args[output_file_pos].clone();
+    // A 3-TAC program is read back instead of being compiled
+    let result = match Path::new(&options.filename).extension() {
+        Some(extension) if extension == "tac" => read_tac_input(&mut session, options),
+        _ => run_phases(&mut session, options),
+    };
+    if let Err(exit_code) = result {
+        return exit_code;
+    }
+    if run {
+        // The program's output goes to stdout, its runtime errors to stderr
+        if let Err(e) = session.run(&mut io::stdout().lock()) {
+            return report_errors(&session, e, options);
         }
     }
+    EXIT_SUCCESS
+}
 
-    // Perform lexical analysis on the file
-    let lexical_result = lexical_analysis::perform_lexical_analysis(filename);
-    let tokens: Vec<ParsedToken> = match lexical_result {
+fn run_phases(session: &mut Session, options: &CliOptions) -> Result<(), i32> {
+    // Lexical analysis
+    match session.tokens() {
         Ok(tokens) => {
-            logger::log_to_file(
-                &tokens,
-                &FileLogAttributes::new((log_folder.clone() + "/tokens.log").to_string(), false),
-            ).unwrap();
-            logger::clear_log_file((log_folder.clone() + "/lexical_errors.log").to_string()).unwrap();
-            println!("Lexical analysis completed successfully");
-            tokens
+            if options.emits(EmitKind::Tokens) {
+                write_output(tokens, &options.get_log_path("tokens.log"), options)?;
+            }
+            clear_stale_log("lexical_errors.log", options)?;
+            print_progress(options, "Lexical analysis completed successfully");
         },
         Err(e) => {
-            logger::log_to_file(
-                &e,
-                &FileLogAttributes::new((log_folder.clone() + "/lexical_errors.log").to_string(), false),
-            ).unwrap();
-            logger::clear_log_file((log_folder.clone() + "/tokens.log").to_string()).unwrap();
-            panic!("Lexical errors found. Check logs for more information.");
+            clear_stale_log("tokens.log", options)?;
+            return Err(report_errors(session, e, options));
         },
     };
+    if options.stop_after == Phase::Lexical {
+        return Ok(());
+    }
 
-    // Perform syntax analysis on the file
-    logger::clear_log_file((log_folder.clone() + "/syntax_errors.log").to_string()).unwrap();
-    logger::clear_log_file((log_folder.clone() + "/semantic_errors.log").to_string()).unwrap();
-    
-    let syntax_result = syntax_semantic_analysis::perform_syntax_semantic_analysis(tokens);
-    let table: SymbolTable = match syntax_result {
-        Ok(table) => {
-            logger::log_to_file(
-                &table,
-                &FileLogAttributes::new((log_folder.clone() + "/symbol_table.log").to_string(), false),
-            ).unwrap();
-            println!("Syntax and Semantic analysis completed successfully");
-            table
+    clear_stale_log("syntax_errors.log", options)?;
+    clear_stale_log("semantic_errors.log", options)?;
+    clear_stale_log("semantic_warnings.log", options)?;
+
+    // Syntax analysis only
+    if options.stop_after == Phase::Syntax {
+        if let Err(e) = session.check_syntax() {
+            return Err(report_errors(session, e, options));
+        }
+        print_progress(options, "Syntax analysis completed successfully");
+        return Ok(());
+    }
+
+    // Syntax and semantic analysis, whose warnings are reported even when it fails
+    let analysis_error = session.symbol_table().err();
+    report_warnings(session, options)?;
+    if let Some(e) = analysis_error {
+        clear_stale_log("symbol_table.log", options)?;
+        return Err(report_errors(session, e, options));
+    }
+    if let Ok(table) = session.symbol_table() {
+        if options.emits(EmitKind::Symbols) {
+            write_output(table, &options.get_log_path("symbol_table.log"), options)?;
+        }
+        if options.emits(EmitKind::StatementTrees) {
+            write_output(&table.statements_to_log_message(), &options.get_log_path("statement_trees.log"), options)?;
+        }
+        if options.emits(EmitKind::Dot) {
+            write_output(&dot::statement_trees_to_dot(table), &options.get_log_path("statement_trees.dot"), options)?;
+            write_output(&dot::scope_tree_to_dot(table), &options.get_log_path("scopes.dot"), options)?;
+        }
+        print_progress(options, "Syntax and Semantic analysis completed successfully");
+    }
+
+    // The parse tree and the AST come from a parse of their own, which can only succeed once the one above has
+    if options.emits(EmitKind::Dot) {
+        match session.parse_tree() {
+            Ok(tree) => write_output(&dot::parse_tree_to_dot(tree), &options.get_log_path("parse_tree.dot"), options)?,
+            Err(e) => return Err(report_errors(session, e, options)),
+        }
+    }
+    if options.emits(EmitKind::Ast) {
+        match session.ast() {
+            Ok(program) => write_output(program, &options.get_log_path("ast.log"), options)?,
+            Err(e) => return Err(report_errors(session, e, options)),
+        }
+    }
+    if options.stop_after == Phase::Semantic {
+        return Ok(());
+    }
+
+    // Intermediate code generation
+    match session.tac_program() {
+        Ok(tac_program) => {
+            if options.emits(EmitKind::Tac) {
+                write_output(tac_program, &options.get_output_path(), options)?;
+            }
         },
-        Err(e) => {
-            logger::clear_log_file((log_folder.clone() + "/symbol_table.log").to_string()).unwrap();
-            if !e.syntax_errors.is_empty() {
-                // Syntax errors
-                logger::log_to_file(
-                    &e.syntax_errors.into_boxed_slice(),
-                    &FileLogAttributes::new((log_folder.clone() + "/syntax_errors.log").to_string(), false),
-                ).unwrap();
-                panic!("Syntax errors found. Check logs for more information.");
-            } else {
-                // Semantic errors
-                logger::log_to_file(
-                    &e.semantic_errors.into_boxed_slice(),
-                    &FileLogAttributes::new((log_folder.clone() + "/semantic_errors.log").to_string(), false),
-                ).unwrap();
-                panic!("Semantic errors found. Check logs for more information.");
+        Err(e) => return Err(report_errors(session, e, options)),
+    };
+    if options.emits(EmitKind::Tac) {
+        // Generated along with the 3-TAC, so it can't fail
+        if let Ok(frame_map) = session.frame_map() {
+            write_output(frame_map, &options.get_frame_map_path(), options)?;
+        }
+    }
+    print_progress(options, "Intermediate code generation completed successfully");
+
+    Ok(())
+}
+
+/**
+ * Read a 3-TAC program with the frame map next to it (its path with a `.frames` extension) if there is one,
+ * and write them back out if the 3-TAC is emitted.
+ */
+fn read_tac_input(session: &mut Session, options: &CliOptions) -> Result<(), i32> {
+    clear_stale_log("tac_errors.log", options)?;
+
+    let frame_map_path = Path::new(&options.filename).with_extension("frames");
+    let result = match fs::read_to_string(&frame_map_path) {
+        Ok(frame_map) => session.parse_tac_with_frame_map(&frame_map_path.to_string_lossy(), &frame_map),
+        Err(e) if e.kind() == ErrorKind::NotFound => session.parse_tac(),
+        Err(e) => Err(CompileError::Lexical(LexicalError::FileReadError(e.to_string()))),
+    };
+    match result {
+        Ok(tac_program) => {
+            if options.emits(EmitKind::Tac) {
+                write_output(tac_program, &options.get_output_path(), options)?;
             }
+        },
+        Err(e) => return Err(report_errors(session, e, options)),
+    };
+    if options.emits(EmitKind::Tac) {
+        if let Ok(frame_map) = session.frame_map() {
+            write_output(frame_map, &options.get_frame_map_path(), options)?;
         }
+    }
+    print_progress(options, "3-TAC program read successfully");
+
+    Ok(())
+}
+
+/**
+ * Report the errors of a failed phase and get the matching exit code.
+ * Human readable errors are printed to stderr and logged to the phase's error log (except with `run`),
+ * JSON errors are only printed to stderr, one object per line.
+ */
+fn report_errors(session: &Session, error: CompileError, options: &CliOptions) -> i32 {
+    print_diagnostics(session, &error.to_diagnostics(), options);
+
+    let (log_file, exit_code) = match &error {
+        CompileError::Lexical(_) => ("lexical_errors.log", EXIT_LEXICAL_ERROR),
+        CompileError::Syntax(_) => ("syntax_errors.log", EXIT_SYNTAX_ERROR),
+        CompileError::Semantic(_) => ("semantic_errors.log", EXIT_SEMANTIC_ERROR),
+        CompileError::Runtime(_) => ("runtime_errors.log", EXIT_RUNTIME_ERROR),
+        // Errors in a 3-TAC input are syntax errors of the 3-TAC
+        CompileError::Tac(_) => ("tac_errors.log", EXIT_SYNTAX_ERROR),
+        CompileError::Internal(_) => ("internal_errors.log", EXIT_INTERNAL_ERROR),
     };
 
-    let tac_program = intermediate_code_generation::perform_intermediate_code_generation(table);
-    // dbg!(&tac_program);
-    logger::log_to_file(
-        &tac_program,
-        &FileLogAttributes::new((log_folder.clone() + "/" + &output_file).to_string(), false),
-    ).unwrap();
-    println!("Intermediate code generation completed successfully");
+    if options.logs_errors() {
+        let log_path = options.get_log_path(log_file);
+        let log_result = match error {
+            CompileError::Lexical(e) => write_output(&e, &log_path, options),
+            CompileError::Syntax(errors) => write_output(&errors.into_boxed_slice(), &log_path, options),
+            CompileError::Semantic(errors) => write_output(&errors.into_boxed_slice(), &log_path, options),
+            CompileError::Runtime(e) => write_output(&e, &log_path, options),
+            CompileError::Tac(errors) => write_output(&errors.into_boxed_slice(), &log_path, options),
+            CompileError::Internal(message) => write_output(&message, &log_path, options),
+        };
+        if let Err(log_exit_code) = log_result {
+            return log_exit_code;
+        }
+    }
+
+    exit_code
+}
+
+/**
+ * Print the warnings of the semantic analysis and log them to `semantic_warnings.log`, the same way as errors.
+ */
+fn report_warnings(session: &Session, options: &CliOptions) -> Result<(), i32> {
+    let warnings = session.get_warnings();
+    if warnings.is_empty() {
+        return Ok(());
+    }
+
+    let diagnostics: Vec<Diagnostic> = warnings.iter().map(|warning| warning.to_diagnostic()).collect();
+    print_diagnostics(session, &diagnostics, options);
+    if options.error_format == ErrorFormat::Human {
+        // Keep the warnings apart from the errors that may follow
+        eprintln!();
+    }
+    if options.logs_errors() {
+        write_output(warnings, &options.get_log_path("semantic_warnings.log"), options)?;
+    }
+    Ok(())
+}
+
+fn print_diagnostics(session: &Session, diagnostics: &[Diagnostic], options: &CliOptions) {
+    match options.error_format {
+        ErrorFormat::Human => {
+            let color = match options.color {
+                ColorMode::Auto => io::stderr().is_terminal(),
+                ColorMode::Always => true,
+                ColorMode::Never => false,
+            };
+            let renderer = DiagnosticRenderer::new(session.get_source_map(), color);
+            eprint!("{}", renderer.render_all(diagnostics));
+        },
+        ErrorFormat::Json => {
+            for diagnostic in diagnostics.iter() {
+                eprintln!("{}", diagnostic.to_json(session.get_source_map()));
+            }
+        },
+    }
+}
+
+fn write_output<T: Loggable>(loggable: &T, path: &str, options: &CliOptions) -> Result<(), i32> {
+    logger::log_to_file(loggable, &FileLogAttributes::new(path.to_string(), false)).map_err(|e| {
+        eprintln!("error: could not write {}: {}", path, e);
+        EXIT_INTERNAL_ERROR
+    })?;
+
+    if options.verbosity == Verbosity::Verbose {
+        println!("Wrote {}", path);
+    }
+    Ok(())
+}
+
+/**
+ * Empty a log left over from a previous compilation so it isn't mistaken for this one's.
+ * Nothing is cleared when the errors aren't logged, e.g. with `run`.
+ */
+fn clear_stale_log(filename: &str, options: &CliOptions) -> Result<(), i32> {
+    let path = options.get_log_path(filename);
+    if options.log_errors && Path::new(&path).exists() {
+        logger::clear_log_file(path.clone()).map_err(|e| {
+            eprintln!("error: could not clear {}: {}", path, e);
+            EXIT_INTERNAL_ERROR
+        })?;
+    }
+    Ok(())
+}
+
+fn print_progress(options: &CliOptions, message: &str) {
+    if options.verbosity != Verbosity::Quiet {
+        println!("{}", message);
+    }
 }
@@ -0,0 +1,300 @@
+use std::{fs, io::{Read, Write}};
+
+use crate::{
+    ast::{self, Program},
+    diagnostics::{Diagnostic, ToDiagnostic},
+    intermediate_code_generation::{
+        self,
+        layout::{FrameMap, TargetLayout},
+        tac::TacProgram,
+        tac_parser::{self, TacParseError},
+    },
+    interpreter::{Interpreter, RuntimeError},
+    lexical_analysis::{self, LexicalError, ParsedToken},
+    source_map::{FileId, SourceMap, Span},
+    syntax_semantic_analysis::{
+        self,
+        grammar::Grammar,
+        parse_tree::ParseTree,
+        lints::{Lint, LintLevel, LintLevels},
+        semantic_analysis::{SemanticError, SemanticWarning},
+        symbol_table::SymbolTable,
+        syntax_analysis::SyntaxError,
+    },
+};
+
+#[derive(Debug)]
+pub enum CompileError {
+    Lexical(LexicalError),
+    Syntax(Vec<SyntaxError>),
+    Semantic(Vec<SemanticError>),
+    Runtime(RuntimeError),
+    Tac(Vec<TacParseError>),
+    // A bug in the compiler, e.g. an invalid grammar
+    Internal(String),
+}
+
+impl CompileError {
+    pub fn to_diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            CompileError::Lexical(e) => e.to_diagnostics(),
+            CompileError::Syntax(errors) => errors.iter().map(|e| e.to_diagnostic()).collect(),
+            CompileError::Semantic(errors) => errors.iter().map(|e| e.to_diagnostic()).collect(),
+            CompileError::Runtime(e) => vec![e.to_diagnostic()],
+            CompileError::Tac(errors) => errors.iter().map(|e| e.to_diagnostic()).collect(),
+            CompileError::Internal(message) => vec![Diagnostic::error("E0000", format!("internal compiler error: {}", message), None)],
+        }
+    }
+}
+
+/**
+ * A single compilation of one EZSharp program.
+ * Each phase is only run when its result (or the result of a later phase) is requested,
+ * and its output is kept so that following phases can reuse it.
+ */
+pub struct Session {
+    name: String,
+    filename: Option<String>,
+    source_map: SourceMap,
+    file_id: Option<FileId>,
+    tokens: Option<Vec<ParsedToken>>,
+    parse_tree: Option<ParseTree>,
+    ast: Option<Program>,
+    symbol_table: Option<SymbolTable>,
+    lint_levels: LintLevels,
+    warnings: Vec<SemanticWarning>,
+    target_layout: TargetLayout,
+    tac_program: Option<TacProgram>,
+    frame_map: Option<FrameMap>,
+    tac_spans: Vec<Span>,
+}
+
+impl Session {
+    pub fn new(filename: &str) -> Session {
+        Session::with_input(filename, Some(filename.to_string()))
+    }
+
+    /**
+     * Create a session for source code that is already in memory.
+     * The name is only used to refer to the source in messages.
+     */
+    pub fn from_source(name: &str, source: &str) -> Session {
+        Session::from_bytes(name, source.as_bytes())
+    }
+
+    pub fn from_bytes(name: &str, source: &[u8]) -> Session {
+        let mut session = Session::with_input(name, None);
+        session.file_id = Some(session.source_map.add_file(name, String::from_utf8_lossy(source).into_owned()));
+        session
+    }
+
+    /**
+     * Create a session from any reader (e.g. stdin).
+     * The reader is consumed immediately so the session can be compiled more than once.
+     */
+    pub fn from_reader<R: Read>(name: &str, mut reader: R) -> Result<Session, CompileError> {
+        let mut source = Vec::new();
+        reader.read_to_end(&mut source)
+            .map_err(|e| CompileError::Lexical(LexicalError::FileReadError(e.to_string())))?;
+        Ok(Session::from_bytes(name, &source))
+    }
+
+    fn with_input(name: &str, filename: Option<String>) -> Session {
+        Session {
+            name: name.to_string(),
+            filename,
+            source_map: SourceMap::new(),
+            file_id: None,
+            tokens: None,
+            parse_tree: None,
+            ast: None,
+            symbol_table: None,
+            lint_levels: LintLevels::new(),
+            warnings: Vec::new(),
+            target_layout: TargetLayout::new(),
+            tac_program: None,
+            frame_map: None,
+            tac_spans: Vec::new(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /**
+     * Get the id of the session's source in the source map, reading the source file if needed.
+     */
+    pub fn load_source(&mut self) -> Result<FileId, CompileError> {
+        if let Some(file_id) = self.file_id {
+            return Ok(file_id);
+        }
+
+        let filename = self.filename.clone().unwrap_or_default();
+        let source = fs::read(&filename)
+            .map_err(|e| CompileError::Lexical(LexicalError::FileOpenError(e.to_string())))?;
+        let file_id = self.source_map.add_file(&self.name, String::from_utf8_lossy(&source).into_owned());
+        self.file_id = Some(file_id);
+        Ok(file_id)
+    }
+
+    pub fn tokens(&mut self) -> Result<&Vec<ParsedToken>, CompileError> {
+        if self.tokens.is_none() {
+            let file_id = self.load_source()?;
+            let source = &self.source_map.get_file(file_id).unwrap().source;
+            let tokens = lexical_analysis::perform_lexical_analysis_from_str(source, file_id)
+                .map_err(CompileError::Lexical)?;
+            self.tokens = Some(tokens);
+        }
+        Ok(self.tokens.as_ref().unwrap())
+    }
+
+    /**
+     * Check the program's syntax without building the symbol table.
+     */
+    pub fn check_syntax(&mut self) -> Result<(), CompileError> {
+        if self.symbol_table.is_some() {
+            return Ok(());
+        }
+
+        let grammar = Grammar::get_constant_grammar().map_err(CompileError::Internal)?;
+        let tokens = self.tokens()?;
+        syntax_semantic_analysis::perform_syntax_analysis(grammar, tokens).map_err(CompileError::Syntax)
+    }
+
+    /**
+     * Get the parse tree of the program. It is built by its own parse, without the semantic actions.
+     */
+    pub fn parse_tree(&mut self) -> Result<&ParseTree, CompileError> {
+        if self.parse_tree.is_none() {
+            let grammar = Grammar::get_constant_grammar().map_err(CompileError::Internal)?;
+            let tokens = self.tokens()?;
+            let tree = syntax_semantic_analysis::build_parse_tree(grammar, tokens).map_err(CompileError::Syntax)?;
+            self.parse_tree = Some(tree);
+        }
+        Ok(self.parse_tree.as_ref().unwrap())
+    }
+
+    pub fn ast(&mut self) -> Result<&Program, CompileError> {
+        if self.ast.is_none() {
+            let program = ast::lowering::lower_parse_tree(self.parse_tree()?);
+            self.ast = Some(program);
+        }
+        Ok(self.ast.as_ref().unwrap())
+    }
+
+    pub fn symbol_table(&mut self) -> Result<&SymbolTable, CompileError> {
+        if self.symbol_table.is_none() {
+            let grammar = Grammar::get_constant_grammar().map_err(CompileError::Internal)?;
+            self.tokens()?;
+            let tokens = self.tokens.as_ref().unwrap();
+            match syntax_semantic_analysis::perform_syntax_semantic_analysis(grammar, tokens, &self.lint_levels, &self.target_layout) {
+                Ok((table, warnings)) => {
+                    self.symbol_table = Some(table);
+                    self.warnings = warnings;
+                },
+                Err(e) => {
+                    self.warnings = e.semantic_warnings;
+                    if !e.syntax_errors.is_empty() {
+                        return Err(CompileError::Syntax(e.syntax_errors));
+                    }
+                    return Err(CompileError::Semantic(e.semantic_errors));
+                },
+            }
+        }
+        Ok(self.symbol_table.as_ref().unwrap())
+    }
+
+    /**
+     * Set whether the warnings of a lint are left out, reported, or reported as errors.
+     * Only affects the semantic analysis if it hasn't run yet.
+     */
+    pub fn set_lint_level(&mut self, lint: Lint, level: LintLevel) {
+        self.lint_levels.set_level(lint, level);
+    }
+
+    /**
+     * Get the warnings of the semantic analysis, once `symbol_table` has been called (even if it failed).
+     */
+    pub fn get_warnings(&self) -> &Vec<SemanticWarning> {
+        &self.warnings
+    }
+
+    /**
+     * Set the sizes and alignments of the types, which the frames of the functions are laid out with.
+     * Only affects the intermediate code generation if it hasn't run yet.
+     */
+    pub fn set_target_layout(&mut self, target_layout: TargetLayout) {
+        self.target_layout = target_layout;
+    }
+
+    pub fn tac_program(&mut self) -> Result<&TacProgram, CompileError> {
+        if self.tac_program.is_none() {
+            self.symbol_table()?;
+            let table = self.symbol_table.as_ref().unwrap();
+            let (program, frame_map, spans) = intermediate_code_generation::perform_intermediate_code_generation(table, &self.target_layout)
+                .map_err(CompileError::Semantic)?;
+            self.tac_program = Some(program);
+            self.frame_map = Some(frame_map);
+            self.tac_spans = spans;
+        }
+        Ok(self.tac_program.as_ref().unwrap())
+    }
+
+    /**
+     * Get where the variables of each function are stored in its frame, generated with the 3-TAC.
+     */
+    pub fn frame_map(&mut self) -> Result<&FrameMap, CompileError> {
+        self.tac_program()?;
+        Ok(self.frame_map.as_ref().unwrap())
+    }
+
+    /**
+     * Read the session's source as a 3-TAC program, as written by the compiler, instead of compiling it.
+     * Without a frame map, the frames of the functions are laid out from the variables of the program,
+     * which fails if it uses arrays.
+     */
+    pub fn parse_tac(&mut self) -> Result<&TacProgram, CompileError> {
+        self.load_tac(None)
+    }
+
+    /**
+     * Read the session's source as a 3-TAC program, along with its frame map (`source`, named `name` in messages),
+     * which gives the types of the variables and the sizes of the arrays.
+     */
+    pub fn parse_tac_with_frame_map(&mut self, name: &str, source: &str) -> Result<&TacProgram, CompileError> {
+        let file_id = self.source_map.add_file(name, source.to_string());
+        let frame_map = tac_parser::parse_frame_map(source, file_id).map_err(CompileError::Tac)?;
+        self.load_tac(Some(frame_map))
+    }
+
+    fn load_tac(&mut self, frame_map: Option<FrameMap>) -> Result<&TacProgram, CompileError> {
+        let file_id = self.load_source()?;
+        let source = &self.source_map.get_file(file_id).unwrap().source;
+        let (program, spans) = tac_parser::parse_tac_program(source, file_id, frame_map.as_ref()).map_err(CompileError::Tac)?;
+        let frame_map = match frame_map {
+            Some(frame_map) => frame_map,
+            None => tac_parser::infer_frame_map(&program, &spans, &self.target_layout).map_err(CompileError::Tac)?,
+        };
+
+        self.tac_program = Some(program);
+        self.frame_map = Some(frame_map);
+        self.tac_spans = spans;
+        Ok(self.tac_program.as_ref().unwrap())
+    }
+
+    /**
+     * Run the program with the TAC interpreter, writing what it prints to `output`.
+     */
+    pub fn run<W: Write>(&mut self, output: &mut W) -> Result<(), CompileError> {
+        self.tac_program()?;
+        let program = self.tac_program.as_ref().unwrap();
+        let frame_map = self.frame_map.as_ref().unwrap();
+        let mut interpreter = Interpreter::new(program, frame_map, &self.target_layout).with_spans(&self.tac_spans);
+        interpreter.run(output).map_err(CompileError::Runtime)
+    }
+}
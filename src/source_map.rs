@@ -0,0 +1,121 @@
+pub type FileId = usize;
+
+/**
+ * A region of a source file.
+ * `start` and `end` are byte offsets into the file (`end` is exclusive),
+ * `line` and `column` are the 1-based position of `start`.
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub file_id: FileId,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(file_id: FileId, start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span {
+            file_id,
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /**
+     * Get the smallest span covering both spans.
+     */
+    pub fn to(&self, other: &Span) -> Span {
+        let (first, last) = if self.start <= other.start { (self, other) } else { (other, self) };
+        Span {
+            file_id: first.file_id,
+            start: first.start,
+            end: usize::max(first.end, last.end),
+            line: first.line,
+            column: first.column,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SourceFile {
+    pub id: FileId,
+    pub name: String,
+    pub source: String,
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(id: FileId, name: String, source: String) -> SourceFile {
+        let mut line_starts = vec![0];
+        for (i, c) in source.bytes().enumerate() {
+            if c == b'\n' {
+                line_starts.push(i + 1);
+            }
+        }
+
+        SourceFile {
+            id,
+            name,
+            source,
+            line_starts,
+        }
+    }
+
+    pub fn get_line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /**
+     * Get the text of a 1-based line without its line ending.
+     */
+    pub fn get_line(&self, line: usize) -> Option<&str> {
+        if line == 0 || line > self.line_starts.len() {
+            return None;
+        }
+
+        let start = self.line_starts[line - 1];
+        let end = if line < self.line_starts.len() {
+            self.line_starts[line] - 1
+        } else {
+            self.source.len()
+        };
+        Some(self.source[start..end].trim_end_matches('\r'))
+    }
+
+    /**
+     * Get the byte offset of the start of a 1-based line.
+     */
+    pub fn get_line_start(&self, line: usize) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+        self.line_starts.get(line - 1).copied()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> SourceMap {
+        SourceMap {
+            files: Vec::new(),
+        }
+    }
+
+    pub fn add_file(&mut self, name: &str, source: String) -> FileId {
+        let id = self.files.len();
+        self.files.push(SourceFile::new(id, name.to_string(), source));
+        id
+    }
+
+    pub fn get_file(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get(id)
+    }
+}
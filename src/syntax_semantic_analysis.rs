@@ -1,157 +1,461 @@
-mod ll1_table;
-mod productions;
-mod first_set;
-mod follow_set;
-mod non_terminals;
+pub mod ll1_table;
+pub mod error_recovery;
+pub mod productions;
+pub mod grammar;
+pub mod grammar_dump;
+pub mod dot;
+pub mod control_flow;
+pub mod constant_evaluation;
+pub mod lints;
+pub mod variable_usage;
+pub mod first_set;
+pub mod follow_set;
+pub mod non_terminals;
+pub mod parse_tree;
 mod stack;
 pub mod symbol_table;
-mod syntax_analysis;
-mod semantic_analysis;
+pub mod syntax_analysis;
+pub mod semantic_analysis;
 pub mod symbol_declaration;
 pub mod statement_tree;
-mod semantic_actions;
+pub mod semantic_actions;
 
 use crate::{
-    lexical_analysis::{ParsedToken, Token}, syntax_semantic_analysis::{ll1_table::{generate_ll1_table, LL1Table}, syntax_analysis::SyntaxErrorType}
+    intermediate_code_generation::layout::TargetLayout,
+    lexical_analysis::{ParsedToken, Token},
+    source_map::Span, syntax_semantic_analysis::syntax_analysis::SyntaxErrorType
 };
 
-use productions::ProductionType;
+use grammar::Grammar;
+use lints::LintLevels;
+use productions::{Production, ProductionType};
 use non_terminals::NonTerminal;
-use stack::Stack;
+use parse_tree::ParseTree;
+use stack::{ProductionPosition, Stack};
 use symbol_table::SymbolTable;
 
-use self::{semantic_analysis::SemanticError, syntax_analysis::SyntaxError};
-use self::follow_set::FollowSetType;
+use self::{semantic_analysis::{SemanticError, SemanticWarning}, syntax_analysis::SyntaxError};
+use self::error_recovery::{RecoveryAction, INSERTABLE_TOKENS, SYNC_TOKENS};
 
+/**
+ * Number of tokens that must be matched after a syntax error before the next one is reported.
+ */
+const ERROR_SUPPRESSION_TOKENS: usize = 3;
+
+#[derive(Debug)]
 pub struct SyntaxSemanticErrors {
     pub syntax_errors: Vec<SyntaxError>,
     pub semantic_errors: Vec<SemanticError>,
+    pub semantic_warnings: Vec<SemanticWarning>,
+}
+
+/**
+ * Parse the program and build its symbol table, then check the control flow of its functions, its constant expressions
+ * and how its variables are used.
+ * Warnings are returned along with the symbol table, or with the errors if there are any.
+ * Their lints' levels decide whether they are left out, kept, or turned into errors.
+ */
+pub fn perform_syntax_semantic_analysis(
+    grammar: &Grammar,
+    tokens: &[ParsedToken],
+    lint_levels: &LintLevels,
+    target: &TargetLayout,
+) -> Result<(SymbolTable, Vec<SemanticWarning>), SyntaxSemanticErrors> {
+    let mut semantic_info = semantic_analysis::SemanticInfo::new(target);
+    let mut errors = parse_tokens(grammar, tokens, Some(&mut semantic_info), None);
+
+    // dbg!(&semantic_info.symbol_table);
+    if !errors.syntax_errors.is_empty() || !errors.semantic_errors.is_empty() {
+        return Err(errors);
+    }
+
+    // Only once the symbol table is complete, as a missing statement would look like a missing return
+    let table = &semantic_info.symbol_table;
+    let (mut semantic_errors, mut warnings) = control_flow::check_control_flow(table);
+    let (constant_errors, constant_warnings) = constant_evaluation::check_constants(table);
+    semantic_errors.extend(constant_errors);
+    warnings.extend(constant_warnings);
+    warnings.extend(variable_usage::check_variable_usage(table));
+    warnings.append(&mut semantic_info.warnings);
+    warnings.sort_by_key(|warning| warning.get_span().start);
+
+    let (denied_warnings, warnings) = lint_levels.apply(warnings);
+    semantic_errors.extend(denied_warnings);
+    if !semantic_errors.is_empty() {
+        semantic_errors.sort_by_key(|error| error.get_span().start);
+        errors.semantic_errors = semantic_errors;
+        errors.semantic_warnings = warnings;
+        return Err(errors);
+    }
+
+    Ok((semantic_info.symbol_table, warnings))
 }
 
-pub fn perform_syntax_semantic_analysis(tokens: Vec<ParsedToken>) -> Result<SymbolTable, SyntaxSemanticErrors> {
-    let productions = productions::get_constant_productions();
-    let follow_sets = follow_set::get_constant_follow_sets();
+/**
+ * Only check the program's syntax, without performing any of the semantic actions.
+ */
+pub fn perform_syntax_analysis(grammar: &Grammar, tokens: &[ParsedToken]) -> Result<(), Vec<SyntaxError>> {
+    let errors = parse_tokens(grammar, tokens, None, None);
+    if !errors.syntax_errors.is_empty() {
+        return Err(errors.syntax_errors);
+    }
+    Ok(())
+}
 
-    // Generate LL(1) table
-    let table: LL1Table = generate_ll1_table(&productions, &follow_sets);
-    
-    // Parse using a stack
-    let mut semantic_info = semantic_analysis::SemanticInfo::new();
+/**
+ * Build the parse tree of the program, without performing any of the semantic actions.
+ * No tree is returned if the program has syntax errors, as it would be made of the parser's guesses.
+ */
+pub fn build_parse_tree(grammar: &Grammar, tokens: &[ParsedToken]) -> Result<ParseTree, Vec<SyntaxError>> {
+    let mut parse_tree = ParseTree::new(NonTerminal::Program);
+    let errors = parse_tokens(grammar, tokens, None, Some(&mut parse_tree));
+    if !errors.syntax_errors.is_empty() {
+        return Err(errors.syntax_errors);
+    }
+    parse_tree.compute_spans();
+    Ok(parse_tree)
+}
 
+/**
+ * Parse the tokens with the LL(1) table, performing the semantic actions if `semantic_info` is given
+ * and adding the productions applied to `parse_tree` if it is given.
+ * Syntax errors are recovered from at the phrase level, so that parsing goes on after them:
+ * - A stray token is deleted when the token after it fits
+ * - A missing `;`, `fi`, `od`, `fed` or `)` is inserted
+ * - Otherwise tokens are skipped until parsing can resume (see `error_recovery`)
+ */
+fn parse_tokens(
+    grammar: &Grammar,
+    tokens: &[ParsedToken],
+    mut semantic_info: Option<&mut semantic_analysis::SemanticInfo>,
+    mut parse_tree: Option<&mut ParseTree>
+) -> SyntaxSemanticErrors {
     let mut errors: SyntaxSemanticErrors = SyntaxSemanticErrors {
         syntax_errors: Vec::new(),
         semantic_errors: Vec::new(),
+        semantic_warnings: Vec::new(),
     };
 
+    if tokens.is_empty() {
+        errors.syntax_errors.push(SyntaxError::new(SyntaxErrorType::UnexpectedEndOfFile, Span::default()));
+        return errors;
+    }
+
     let mut stack = Stack::new();
-    stack.push(ProductionType::NonTerminal(NonTerminal::Program));
+    stack.push(ProductionType::NonTerminal(NonTerminal::Program), None, parse_tree.as_ref().map(|_| 0));
+
+    let mut position = 0;
+    let mut prev_terminal: Option<ParsedToken> = None;
+    // Number of tokens matched since the last syntax error, if there was one
+    let mut matched_since_error: Option<usize> = None;
+    // Tokens that non terminals left out since the last match could have started with
+    let mut expected_since_match: Vec<Token> = Vec::new();
 
-    let mut token_iter = tokens.iter();
-    let mut curr_token: &ParsedToken = &token_iter.next().unwrap();
-    let mut prev_terminal: Option<Token> = None;
+    while let Some((top, origin, node)) = stack.pop() {
+        let curr_token = &tokens[position];
+        let next_token = tokens.get(position + 1);
+        let context = origin.and_then(|origin| {
+            syntax_analysis::get_context(&grammar.productions[origin.production_index], origin.position)
+        });
 
-    while !stack.is_empty() {
-        match stack.pop().unwrap() {
+        match top {
             ProductionType::Terminal(token) => {
                 if !token.equals_type(&curr_token.token) {
-                    // Unexpected terminal found
-                    errors.syntax_errors.push(SyntaxError::new(
-                        SyntaxErrorType::ExpectedToken(token.clone(), curr_token.token.clone()),
-                        curr_token.line
-                    ));
+                    let mut expected = vec![token.clone()];
+                    add_expected_tokens(&mut expected, &expected_since_match);
+                    expected_since_match.clear();
+
+                    if next_token.is_some_and(|next| token.equals_type(&next.token)) {
+                        // Stray token, the expected one comes right after it
+                        report_syntax_error(
+                            &mut errors,
+                            &mut matched_since_error,
+                            SyntaxError::new(SyntaxErrorType::UnexpectedToken {
+                                expected,
+                                found: curr_token.token.clone(),
+                                context,
+                            }, curr_token.span)
+                        );
+                        position += 1;
+                    } else {
+                        report_syntax_error(
+                            &mut errors,
+                            &mut matched_since_error,
+                            SyntaxError::new(SyntaxErrorType::ExpectedToken {
+                                expected,
+                                found: curr_token.token.clone(),
+                                context,
+                            }, curr_token.span)
+                        );
+
+                        // Missing token, go on as if it was there (the current token is kept)
+                        let is_insertable = INSERTABLE_TOKENS.iter().any(|t| t.equals_type(&token));
+                        let is_sync = SYNC_TOKENS.iter().any(|t| t.equals_type(&curr_token.token));
+                        if is_insertable || is_sync || error_recovery::is_accepted_later(stack.iter(), &curr_token.token, grammar) {
+                            continue;
+                        }
+                        // Otherwise the current token is used in its place
+                    }
                 }
 
-                prev_terminal = Some(curr_token.token.clone());
+                if let Some(matched) = matched_since_error.as_mut() {
+                    if token.equals_type(&tokens[position].token) {
+                        *matched += 1;
+                    }
+                }
+                expected_since_match.clear();
+                prev_terminal = Some(tokens[position].clone());
+                if let (Some(parse_tree), Some(node)) = (parse_tree.as_mut(), node) {
+                    parse_tree.match_token(node, &tokens[position]);
+                }
 
                 // Move to next token
-                if let Some(next_token) = token_iter.next() {
-                    curr_token = next_token;
-                } else {
+                position += 1;
+                if position == tokens.len() {
                     // No more tokens
                     if !stack.is_empty() {
                         // Unexpected end of file
-                        errors.syntax_errors.push(SyntaxError::new(
-                            SyntaxErrorType::UnexpectedEndOfFile,
-                            curr_token.line
-                        ));
-                        return Err(errors);
+                        report_syntax_error(
+                            &mut errors,
+                            &mut matched_since_error,
+                            SyntaxError::new(SyntaxErrorType::UnexpectedEndOfFile, tokens[position - 1].span)
+                        );
                     }
+                    return errors;
                 }
             },
             ProductionType::NonTerminal(non_terminal) => {
-                let production_index = table[non_terminal.to_index()][curr_token.token.to_index()];
-                match production_index {
-                    Some(prod_index) => {
-                        if prod_index >= productions.len() {
-                            // Epsilon production
-                            continue;
+                let first_set = &grammar.first_sets[non_terminal.to_index()];
+                let production_index = grammar.table[non_terminal.to_index()][curr_token.token.to_index()];
+                if let Some(prod_index) = production_index {
+                    if !error_recovery::starts_production(non_terminal, &curr_token.token, grammar) {
+                        // Left out, but the next token could have been one of its own
+                        add_expected_tokens(&mut expected_since_match, &first_set.get_tokens());
+                    }
+                    push_production_elems(&mut stack, &grammar.productions, prod_index, 0, parse_tree.as_deref_mut(), node);
+                    if let (Some(parse_tree), Some(node)) = (parse_tree.as_mut(), node) {
+                        if grammar.productions[prod_index].right.iter().all(|elem| matches!(elem, ProductionType::Action(_))) {
+                            parse_tree.set_empty_span(node, curr_token);
                         }
+                    }
+                    continue;
+                }
 
-                        let production = &productions[prod_index];
-                        for prod_elem in production.right.iter().rev() {
-                            stack.push(prod_elem.clone());
-                        }
+                // Current non terminal does not have a production for the current token
+                let mut expected = first_set.get_tokens();
+                add_expected_tokens(&mut expected, &expected_since_match);
+                expected_since_match.clear();
+
+                if let RecoveryAction::SkipBlock(end) = &grammar.recovery_table[non_terminal.to_index()][curr_token.token.to_index()] {
+                    report_syntax_error(
+                        &mut errors,
+                        &mut matched_since_error,
+                        SyntaxError::new(SyntaxErrorType::UnexpectedToken {
+                            expected,
+                            found: curr_token.token.clone(),
+                            context,
+                        }, curr_token.span)
+                    );
+
+                    // The content of the block would only give more errors here
+                    while position < tokens.len() && !end.equals_type(&tokens[position].token) {
+                        position += 1;
+                    }
+                    position += 1;
+                    if tokens.get(position).is_some_and(|token| token.token == Token::Ssemicolon) {
+                        position += 1;
+                    }
+                    if position >= tokens.len() {
+                        return errors;
+                    }
+                    stack.push(ProductionType::NonTerminal(non_terminal), origin, node);
+                    continue;
+                }
+
+                let is_stray = next_token.is_some_and(|next| error_recovery::starts_production(non_terminal, &next.token, grammar))
+                    && !error_recovery::is_accepted_later(stack.iter(), &curr_token.token, grammar);
+                if is_stray {
+                    // Stray token, the non terminal can continue from the token after it
+                    report_syntax_error(
+                        &mut errors,
+                        &mut matched_since_error,
+                        SyntaxError::new(SyntaxErrorType::UnexpectedToken {
+                            expected,
+                            found: curr_token.token.clone(),
+                            context,
+                        }, curr_token.span)
+                    );
+                    stack.push(ProductionType::NonTerminal(non_terminal), origin, node);
+                    position += 1;
+                    continue;
+                }
+
+                match &grammar.recovery_table[non_terminal.to_index()][curr_token.token.to_index()] {
+                    RecoveryAction::Insert(_, prod_index) => {
+                        report_syntax_error(
+                            &mut errors,
+                            &mut matched_since_error,
+                            SyntaxError::new(SyntaxErrorType::ExpectedToken {
+                                expected,
+                                found: curr_token.token.clone(),
+                                context,
+                            }, curr_token.span)
+                        );
+                        push_production_elems(&mut stack, &grammar.productions, *prod_index, 1, parse_tree.as_deref_mut(), node);
                     },
-                    None => {
-                        // println!("No production found for {:?} and {:?}", non_terminal, curr_token.token);
-                        // Current non terminal does not have a production for the current token
-                        errors.syntax_errors.push(SyntaxError::new(
-                            SyntaxErrorType::UnexpectedToken(curr_token.token.clone()),
-                            curr_token.line
-                        ));
-
-                        // Find next token in the follow set of the current non terminal
-                        let mut token_found = false;
-                        while let Some(next_token) = token_iter.next() {
-                            for follow_elem in follow_sets[non_terminal.to_index()].follow_set.iter() {
-                                if let FollowSetType::Terminal(follow_token) = follow_elem {
-                                    if follow_token == &next_token.token {
-                                        // Resume from token found in follow set
-                                        curr_token = next_token;
-                                        token_found = true;
-                                        break;
-                                    }
-                                }
-                            }
+                    RecoveryAction::UseEpsilon(prod_index) => {
+                        // The error is reported by whatever comes after the non terminal
+                        expected_since_match = expected;
+                        push_production_elems(&mut stack, &grammar.productions, *prod_index, 0, parse_tree.as_deref_mut(), node);
+                    },
+                    RecoveryAction::Synchronize | RecoveryAction::SkipBlock(_) => {
+                        report_syntax_error(
+                            &mut errors,
+                            &mut matched_since_error,
+                            SyntaxError::new(SyntaxErrorType::UnexpectedToken {
+                                expected,
+                                found: curr_token.token.clone(),
+                                context,
+                            }, curr_token.span)
+                        );
 
-                            if token_found {
+                        // Skip tokens until the non terminal can go on, or one that can end it is found
+                        loop {
+                            let token = &tokens[position].token;
+                            if grammar.table[non_terminal.to_index()][token.to_index()].is_some() {
+                                stack.push(ProductionType::NonTerminal(non_terminal), origin, node);
+                                break;
+                            }
+                            if error_recovery::is_sync_token(non_terminal, token, grammar) {
                                 break;
                             }
-                        }
 
-                        if !token_found {
-                            // No more tokens
-                            if !stack.is_empty() {
-                                return Err(errors);
+                            position += 1;
+                            if position == tokens.len() {
+                                // No more tokens
+                                return errors;
                             }
                         }
                     },
                 }
             },
             ProductionType::Action(action) => {
-                let action_result = semantic_info.perform_action(&action, &prev_terminal);
-                match action_result {
-                    Ok(_) => {},
-                    Err(err) => {
-                        errors.semantic_errors.push(SemanticError::new(err, curr_token.line));
-                    },
+                // After a syntax error the actions would run on the recovered parse, which the symbol table can't be built from
+                if !errors.syntax_errors.is_empty() {
+                    continue;
+                }
+                if let Some(semantic_info) = semantic_info.as_mut() {
+                    let action_result = semantic_info.perform_action(&action, &prev_terminal);
+                    match action_result {
+                        Ok(_) => {},
+                        Err(err) => {
+                            errors.semantic_errors.push(err);
+                        },
+                    }
                 }
             },
         }
     }
 
-    if token_iter.next().is_some() {
+    if let Some(extra_token) = tokens.get(position) {
         // There are more tokens after parsing
-        errors.syntax_errors.push(SyntaxError::new(
-            SyntaxErrorType::UnexpectedEndOfFile,
-            curr_token.line
-        ));
+        report_syntax_error(
+            &mut errors,
+            &mut matched_since_error,
+            SyntaxError::new(SyntaxErrorType::UnexpectedToken {
+                expected: Vec::new(),
+                found: extra_token.token.clone(),
+                context: Some(String::from("after the end of the program")),
+            }, extra_token.span)
+        );
     }
-    
-    // dbg!(&semantic_info.symbol_table);
-    if !errors.syntax_errors.is_empty() || !errors.semantic_errors.is_empty() {
-        return Err(errors);
+
+    errors
+}
+
+/**
+ * Push the elements of a production from `start` onwards, remembering where each one comes from.
+ * If a parse tree is built, the elements are added as children of the node being expanded.
+ */
+fn push_production_elems(
+    stack: &mut Stack,
+    productions: &[Production],
+    production_index: usize,
+    start: usize,
+    parse_tree: Option<&mut ParseTree>,
+    node: Option<usize>
+) {
+    let right = &productions[production_index].right;
+    let nodes = match (parse_tree, node) {
+        (Some(parse_tree), Some(node)) => parse_tree.expand(node, production_index, &productions[production_index], start),
+        _ => vec![None; right.len() - start],
+    };
+    for position in (start..right.len()).rev() {
+        stack.push(right[position].clone(), Some(ProductionPosition { production_index, position }), nodes[position - start]);
     }
+}
 
-    Ok(semantic_info.symbol_table)
-}
\ No newline at end of file
+fn add_expected_tokens(expected: &mut Vec<Token>, tokens: &[Token]) {
+    for token in tokens {
+        if !expected.iter().any(|t| t.equals_type(token)) {
+            expected.push(token.clone());
+        }
+    }
+}
+
+/**
+ * Add a syntax error, unless it is part of a cascade: errors found before `ERROR_SUPPRESSION_TOKENS` tokens
+ * have been matched since the previous one are most likely caused by the recovery from it.
+ */
+fn report_syntax_error(errors: &mut SyntaxSemanticErrors, matched_since_error: &mut Option<usize>, error: SyntaxError) {
+    let is_cascade = matched_since_error.is_some_and(|matched| matched < ERROR_SUPPRESSION_TOKENS);
+    if !is_cascade {
+        errors.syntax_errors.push(error);
+    }
+    *matched_since_error = Some(0);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::session::{CompileError, Session};
+    use super::syntax_analysis::SyntaxError;
+
+    fn get_syntax_errors(source: &str) -> Vec<SyntaxError> {
+        match Session::from_bytes("test.cp", source.as_bytes()).check_syntax() {
+            Err(CompileError::Syntax(errors)) => errors,
+            result => panic!("expected syntax errors, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn missing_semicolon_is_one_error() {
+        let errors = get_syntax_errors("int a, b;\na = 1\nb = 2;\nprint a + b\n.");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].get_span().line, 3);
+    }
+
+    #[test]
+    fn missing_paren_is_one_error() {
+        let errors = get_syntax_errors("def int f(int x) return x fed;\nint a;\na = f(1;\nprint a\n.");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].get_span().line, 3);
+    }
+
+    #[test]
+    fn missing_comma_is_one_error() {
+        for source in [
+            "int a b;\na = 1;\nprint a\n.",
+            "def int f(int x, int y) return x + y fed;\nint a;\na = f(1 2);\nprint a\n.",
+            "def int f(int x int y) return x + y fed;\nint a;\na = f(1, 2);\nprint a\n.",
+        ] {
+            assert_eq!(get_syntax_errors(source).len(), 1, "{}", source);
+        }
+    }
+
+    #[test]
+    fn misplaced_function_is_skipped() {
+        let errors = get_syntax_errors("int s;\ndef int f(int x, int y)\nint a;\na = x;\nreturn a\nfed;\ns = 1;\nprint s\n.");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].get_span().line, 2);
+    }
+}
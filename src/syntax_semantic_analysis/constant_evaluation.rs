@@ -0,0 +1,346 @@
+use crate::{lexical_analysis::Token, logger::Loggable};
+
+use super::semantic_analysis::{SemanticError, SemanticErrorType, SemanticWarning, SemanticWarningType};
+use super::statement_tree::{StatementSymbol, StatementTree};
+use super::symbol_table::{ConditionalStatementType, SymbolEntry, SymbolTable};
+
+/**
+ * The value of an expression known at compile time.
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConstantValue {
+    Int(i32),
+    Double(f64),
+}
+
+impl ConstantValue {
+    fn to_double(self) -> f64 {
+        match self {
+            ConstantValue::Int(value) => value as f64,
+            ConstantValue::Double(value) => value,
+        }
+    }
+
+    /**
+     * Whether `and`, `or` and `not` see the value as true: like the generated code, only positive values are.
+     */
+    fn is_positive(self) -> bool {
+        self.to_double() > 0.0
+    }
+
+    /**
+     * Whether an `if` or `while` sees the value as false.
+     */
+    pub fn is_zero(self) -> bool {
+        self.to_double() == 0.0
+    }
+}
+
+impl Loggable for ConstantValue {
+    fn to_log_message(&self) -> String {
+        match self {
+            ConstantValue::Int(value) => value.to_string(),
+            ConstantValue::Double(value) => value.to_string(),
+        }
+    }
+}
+
+/**
+ * Evaluates the parts of statement trees made of literals only, following the typing rules of the language:
+ * an int operand is promoted when the other one is a double, and int arithmetic is checked for overflow.
+ *
+ * Errors (division by zero, overflow, int literals out of range) are collected rather than stopping the evaluation,
+ * so that every constant part of a tree is checked. A division by a constant zero is reported even if the dividend
+ * isn't constant.
+ */
+pub struct ConstantEvaluator {
+    errors: Vec<SemanticError>,
+}
+
+impl Default for ConstantEvaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConstantEvaluator {
+    pub fn new() -> ConstantEvaluator {
+        ConstantEvaluator {
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn take_errors(&mut self) -> Vec<SemanticError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    /**
+     * Evaluate a tree, returning its value if it is constant.
+     */
+    pub fn evaluate(&mut self, tree: &StatementTree) -> Option<ConstantValue> {
+        self.evaluate_node(tree, tree.start?)
+    }
+
+    fn evaluate_node(&mut self, tree: &StatementTree, index: usize) -> Option<ConstantValue> {
+        let node = &tree.nodes[index];
+        match &node.symbol {
+            StatementSymbol::Literal(Token::Tint(value)) => match i32::try_from(*value) {
+                Ok(value) => Some(ConstantValue::Int(value)),
+                Err(_) => {
+                    self.errors.push(
+                        SemanticError::new(SemanticErrorType::IntLiteralOutOfRange(*value), node.span)
+                            .with_help(Some(format!("the largest int is {}, use a double literal for larger values", i32::MAX)))
+                    );
+                    None
+                },
+            },
+            StatementSymbol::Literal(Token::Tdouble(value)) => Some(ConstantValue::Double(*value)),
+            StatementSymbol::Literal(_) | StatementSymbol::Decl(_) => None,
+            StatementSymbol::FunctionCall(_, params) => {
+                for param in params.iter() {
+                    self.evaluate(param);
+                }
+                None
+            },
+            StatementSymbol::ArrayAccess(_, array_index) => {
+                self.evaluate(array_index);
+                None
+            },
+            StatementSymbol::Operator(op) => {
+                // Both operands are evaluated, for their errors to be reported
+                let left = node.left.and_then(|left| self.evaluate_node(tree, left));
+                let right = node.right.and_then(|right| self.evaluate_node(tree, right));
+
+                if matches!(op, Token::Odivide | Token::Omod) && right.is_some_and(|right| right.is_zero()) {
+                    self.errors.push(SemanticError::new(
+                        SemanticErrorType::DivisionByZero(op.clone()),
+                        tree.get_span(index),
+                    ));
+                    return None;
+                }
+
+                let (left, right) = (left?, right?);
+                let value = apply_operator(op, left, right);
+                if value.is_none() {
+                    self.errors.push(SemanticError::new(
+                        SemanticErrorType::ArithmeticOverflow(format!(
+                            "{} {} {}",
+                            left.to_log_message(),
+                            op,
+                            right.to_log_message()
+                        )),
+                        tree.get_span(index),
+                    ));
+                }
+                value
+            },
+            StatementSymbol::SingleChildOperator(op) => {
+                // The smallest int is written as the negation of a literal one past the largest
+                if let (Token::Ominus, StatementSymbol::Literal(Token::Tint(value))) = (op, &tree.nodes[node.left?].symbol) {
+                    if *value == i32::MIN.unsigned_abs() {
+                        return Some(ConstantValue::Int(i32::MIN));
+                    }
+                }
+                let child = self.evaluate_node(tree, node.left?)?;
+                let value = apply_single_child_operator(op, child);
+                if value.is_none() {
+                    let operation = match op {
+                        Token::Kint => format!("int({})", child.to_log_message()),
+                        _ => format!("-({})", child.to_log_message()),
+                    };
+                    self.errors.push(SemanticError::new(
+                        SemanticErrorType::ArithmeticOverflow(operation),
+                        tree.get_span(index),
+                    ));
+                }
+                value
+            },
+        }
+    }
+}
+
+/**
+ * Apply a binary operator to constant operands.
+ * Returns `None` if the result doesn't fit in an int.
+ */
+fn apply_operator(op: &Token, left: ConstantValue, right: ConstantValue) -> Option<ConstantValue> {
+    let from_bool = |value: bool| Some(ConstantValue::Int(value as i32));
+    match op {
+        // The operands of `and` and `or` aren't promoted
+        Token::Kand => return from_bool(left.is_positive() && right.is_positive()),
+        Token::Kor => return from_bool(left.is_positive() || right.is_positive()),
+        _ => {},
+    }
+
+    match (left, right) {
+        (ConstantValue::Int(left), ConstantValue::Int(right)) => match op {
+            Token::Oplus => left.checked_add(right).map(ConstantValue::Int),
+            Token::Ominus => left.checked_sub(right).map(ConstantValue::Int),
+            Token::Omultiply => left.checked_mul(right).map(ConstantValue::Int),
+            Token::Odivide => left.checked_div(right).map(ConstantValue::Int),
+            Token::Omod => left.checked_rem(right).map(ConstantValue::Int),
+            _ => compare(op, left.partial_cmp(&right)),
+        },
+        (left, right) => {
+            let (left, right) = (left.to_double(), right.to_double());
+            match op {
+                Token::Oplus => Some(ConstantValue::Double(left + right)),
+                Token::Ominus => Some(ConstantValue::Double(left - right)),
+                Token::Omultiply => Some(ConstantValue::Double(left * right)),
+                Token::Odivide => Some(ConstantValue::Double(left / right)),
+                Token::Omod => Some(ConstantValue::Double(left % right)),
+                _ => compare(op, left.partial_cmp(&right)),
+            }
+        },
+    }
+}
+
+/**
+ * Get the value of a comparison, as an int.
+ */
+fn compare(op: &Token, ordering: Option<std::cmp::Ordering>) -> Option<ConstantValue> {
+    let result = match (op, ordering) {
+        (Token::Oequal, ordering) => ordering.is_some_and(|ordering| ordering.is_eq()),
+        (Token::Onot, ordering) => !ordering.is_some_and(|ordering| ordering.is_eq()),
+        (Token::Olt, Some(ordering)) => ordering.is_lt(),
+        (Token::Ogt, Some(ordering)) => ordering.is_gt(),
+        (Token::Olte, Some(ordering)) => ordering.is_le(),
+        (Token::Ogte, Some(ordering)) => ordering.is_ge(),
+        _ => false,
+    };
+    Some(ConstantValue::Int(result as i32))
+}
+
+/**
+ * Apply a single child operator (a parenthesis, a cast, `-` or `not`) to a constant.
+ * Returns `None` if the result doesn't fit in an int.
+ */
+fn apply_single_child_operator(op: &Token, value: ConstantValue) -> Option<ConstantValue> {
+    match (op, value) {
+        (Token::Kint, ConstantValue::Double(value)) => {
+            // Dropping the fractional part
+            let value = value.trunc();
+            if value >= i32::MIN as f64 && value <= i32::MAX as f64 {
+                Some(ConstantValue::Int(value as i32))
+            } else {
+                None
+            }
+        },
+        (Token::Kdouble, value) => Some(ConstantValue::Double(value.to_double())),
+        (Token::Ominus, ConstantValue::Int(value)) => value.checked_neg().map(ConstantValue::Int),
+        (Token::Ominus, ConstantValue::Double(value)) => Some(ConstantValue::Double(-value)),
+        (Token::Knot, value) => Some(ConstantValue::Int(!value.is_positive() as i32)),
+        _ => Some(value),
+    }
+}
+
+/**
+ * Evaluate the constant parts of every statement of the program, reporting divisions by zero, overflows and
+ * int literals out of range, and warn about `if` and `while` conditions whose value is known at compile time.
+ */
+pub fn check_constants(table: &SymbolTable) -> (Vec<SemanticError>, Vec<SemanticWarning>) {
+    let mut evaluator = ConstantEvaluator::new();
+    let mut warnings = Vec::new();
+
+    // Every statement belongs to exactly one scope
+    for scope in 0..table.get_scope_count() {
+        for symbol in table.get_scope(scope).get_symbols() {
+            match symbol {
+                SymbolEntry::Assignment(assignment) => {
+                    if let Some(index) = &assignment.index {
+                        evaluator.evaluate(index);
+                    }
+                    evaluator.evaluate(&assignment.assignment);
+                },
+                SymbolEntry::StatementTree(tree) => {
+                    evaluator.evaluate(tree);
+                },
+                SymbolEntry::BuiltInFunction(func) => {
+                    evaluator.evaluate(&func.statement);
+                },
+                SymbolEntry::ConditionalStatement(statement) => {
+                    let condition = match &statement.condition {
+                        Some(condition) => condition,
+                        None => continue,
+                    };
+                    if let (Some(value), Some(span)) = (evaluator.evaluate(condition), condition.get_tree_span()) {
+                        let keyword = match statement.statement_type {
+                            ConditionalStatementType::While => "while",
+                            _ => "if",
+                        };
+                        warnings.push(SemanticWarning::new(
+                            SemanticWarningType::ConstantCondition(String::from(keyword), !value.is_zero()),
+                            span,
+                        ));
+                    }
+                },
+                SymbolEntry::Decl(_) | SymbolEntry::Scope(_) | SymbolEntry::Parameter(_) => {},
+            }
+        }
+    }
+    (evaluator.take_errors(), warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::session::{CompileError, Session};
+    use crate::syntax_semantic_analysis::semantic_analysis::{SemanticError, SemanticErrorType, SemanticWarningType};
+
+    fn get_semantic_errors(source: &str) -> Vec<SemanticError> {
+        match Session::from_bytes("test.cp", source.as_bytes()).symbol_table() {
+            Err(CompileError::Semantic(errors)) => errors,
+            result => panic!("expected semantic errors, got {:?}", result.err()),
+        }
+    }
+
+    #[test]
+    fn division_by_zero_is_an_error() {
+        for source in ["int x;\nx = 1;\nx = x / 0;\nprint x\n.", "int x;\nx = 1;\nx = x % (2 - 2);\nprint x\n."] {
+            let errors = get_semantic_errors(source);
+            assert_eq!(errors.len(), 1, "{}", source);
+            assert!(matches!(errors[0].get_error_type(), SemanticErrorType::DivisionByZero(_)), "{}", source);
+        }
+    }
+
+    #[test]
+    fn overflow_is_an_error() {
+        let errors = get_semantic_errors("int x;\nx = 2147483647 + 1;\nprint x\n.");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].get_error_type(), SemanticErrorType::ArithmeticOverflow(_)));
+    }
+
+    #[test]
+    fn int_literal_out_of_range_is_an_error() {
+        let errors = get_semantic_errors("int x;\nx = 2147483648;\nprint x\n.");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].get_error_type(), SemanticErrorType::IntLiteralOutOfRange(2147483648)));
+    }
+
+    #[test]
+    fn smallest_int_literal_is_accepted() {
+        let mut session = Session::from_bytes("test.cp", b"int x;\nx = -2147483648;\nprint x\n.");
+        let mut output = Vec::new();
+        session.run(&mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "-2147483648\n");
+    }
+
+    #[test]
+    fn constant_conditions_are_reported() {
+        let mut session = Session::from_bytes("test.cp", b"int x;\nx = 0;\nwhile 1 do x = x + 1 od;\nif 0 then x = 2 fi;\nprint x\n.");
+        session.symbol_table().unwrap();
+        let conditions: Vec<(String, bool)> = session.get_warnings().iter()
+            .filter_map(|warning| match warning.get_warning_type() {
+                SemanticWarningType::ConstantCondition(keyword, value) => Some((keyword.clone(), *value)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(conditions, vec![(String::from("while"), true), (String::from("if"), false)]);
+    }
+
+    #[test]
+    fn empty_array_is_an_error() {
+        let errors = get_semantic_errors("int a[0];\nprint 1\n.");
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].get_error_type(), SemanticErrorType::InvalidArraySize(_)));
+    }
+}
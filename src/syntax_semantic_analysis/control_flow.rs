@@ -0,0 +1,111 @@
+use crate::source_map::Span;
+
+use super::semantic_analysis::{SemanticError, SemanticErrorType, SemanticWarning, SemanticWarningType};
+use super::symbol_declaration::BasicType;
+use super::symbol_table::{BuiltInFuncType, ConditionalStatementType, SymbolEntry, SymbolTable, GLOBAL_SCOPE};
+
+/**
+ * Check that every function returns a value on all of its paths, and warn about statements that can never run
+ * because they come after a `return` (or after an if/else whose branches all return) in the same scope.
+ *
+ * A `while` loop is not known to run, so a `return` in its body doesn't count for the function.
+ */
+pub fn check_control_flow(table: &SymbolTable) -> (Vec<SemanticError>, Vec<SemanticWarning>) {
+    let mut checker = ControlFlowChecker {
+        table,
+        warnings: Vec::new(),
+    };
+    let mut errors = Vec::new();
+
+    for symbol in table.get_scope(GLOBAL_SCOPE).get_symbols() {
+        if let SymbolEntry::Decl(decl_id) = symbol {
+            if let Some(decl) = table.find_decl_by_id(decl_id) {
+                if let BasicType::Function(func_info) = &decl.var_type {
+                    if checker.check_scope(func_info.body_scope).is_none() {
+                        errors.push(SemanticError::new(
+                            SemanticErrorType::MissingReturn(decl.name.clone()),
+                            decl.span,
+                        ).with_help(Some(String::from("add a `return` at the end of the function"))));
+                    }
+                }
+            }
+        }
+    }
+    (errors, checker.warnings)
+}
+
+struct ControlFlowChecker<'a> {
+    table: &'a SymbolTable,
+    warnings: Vec<SemanticWarning>,
+}
+
+impl ControlFlowChecker<'_> {
+    /**
+     * Check the statements of a scope, warning about the first one that can't be reached.
+     * Returns the note explaining why the end of the scope is never reached, if every path through it returns.
+     */
+    fn check_scope(&mut self, scope: usize) -> Option<(&'static str, Span)> {
+        let symbols = self.table.get_scope(scope).get_symbols();
+        let mut returned: Option<(&'static str, Span)> = None;
+
+        let mut i = 0;
+        while i < symbols.len() {
+            let statement_span = match get_statement_span(&symbols[i]) {
+                Some(span) => span,
+                None => {
+                    // Declarations, and the `else` of an `if` already handled with it
+                    i += 1;
+                    continue;
+                },
+            };
+            if let Some((note, return_span)) = returned {
+                self.warnings.push(
+                    SemanticWarning::new(SemanticWarningType::UnreachableCode, statement_span)
+                        .with_note(note, return_span)
+                );
+                break;
+            }
+
+            match &symbols[i] {
+                SymbolEntry::BuiltInFunction(func) if matches!(func.func_type, BuiltInFuncType::Return) => {
+                    returned = Some(("any code following this `return` is unreachable", func.span));
+                },
+                SymbolEntry::ConditionalStatement(statement) => match statement.statement_type {
+                    ConditionalStatementType::If => {
+                        let then_returns = self.check_scope(statement.body_scope).is_some();
+                        // The `else` comes right after its `if`
+                        if let Some(SymbolEntry::ConditionalStatement(else_statement)) = symbols.get(i + 1) {
+                            if matches!(else_statement.statement_type, ConditionalStatementType::Else) {
+                                let else_returns = self.check_scope(else_statement.body_scope).is_some();
+                                if then_returns && else_returns {
+                                    returned = Some(("both branches of this `if` return", statement_span));
+                                }
+                                i += 1;
+                            }
+                        }
+                    },
+                    ConditionalStatementType::Else | ConditionalStatementType::While => {
+                        self.check_scope(statement.body_scope);
+                    },
+                },
+                _ => {},
+            }
+            i += 1;
+        }
+        returned
+    }
+}
+
+/**
+ * Get the span of a statement of a scope, or `None` if the entry isn't one.
+ * An `if` or `while` is spanned by its condition.
+ */
+fn get_statement_span(symbol: &SymbolEntry) -> Option<Span> {
+    match symbol {
+        SymbolEntry::Assignment(assignment) => Some(assignment.span),
+        SymbolEntry::ConditionalStatement(statement) => statement.condition.as_ref()?.get_tree_span(),
+        SymbolEntry::BuiltInFunction(func) => Some(func.span),
+        SymbolEntry::StatementTree(tree) => tree.get_tree_span(),
+        SymbolEntry::Decl(_) | SymbolEntry::Scope(_) | SymbolEntry::Parameter(_) => None,
+    }
+}
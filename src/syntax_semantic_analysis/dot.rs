@@ -0,0 +1,215 @@
+use crate::{lexical_analysis::Token, logger::Loggable};
+
+use super::grammar::get_terminal_name;
+use super::parse_tree::{ParseSymbol, ParseTree};
+use super::statement_tree::{StatementSymbol, StatementTree};
+use super::symbol_declaration::{to_var_name, BasicType};
+use super::symbol_table::{SymbolEntry, SymbolTable};
+
+/**
+ * A Graphviz graph written in the DOT language, built a node and an edge at a time.
+ */
+struct DotGraph {
+    dot: String,
+    indent: usize,
+}
+
+impl DotGraph {
+    fn new(name: &str, attributes: &[&str]) -> DotGraph {
+        let mut dot = format!("digraph {} {{\n", name);
+        for attribute in attributes {
+            dot.push_str(&format!("\t{};\n", attribute));
+        }
+        DotGraph { dot, indent: 1 }
+    }
+
+    fn add_line(&mut self, line: &str) {
+        self.dot.push_str(&"\t".repeat(self.indent));
+        self.dot.push_str(line);
+        self.dot.push('\n');
+    }
+
+    fn add_node(&mut self, id: &str, label: &str, attributes: &str) {
+        let attributes = if attributes.is_empty() { String::new() } else { format!(", {}", attributes) };
+        self.add_line(&format!("{} [label=\"{}\"{}];", id, escape_dot(label), attributes));
+    }
+
+    /**
+     * Add a node whose label is made of left aligned lines.
+     */
+    fn add_left_aligned_node(&mut self, id: &str, lines: &[String]) {
+        let label: String = lines.iter().map(|line| format!("{}\\l", escape_dot(line))).collect();
+        self.add_line(&format!("{} [label=\"{}\"];", id, label));
+    }
+
+    fn add_edge(&mut self, from: &str, to: &str, label: Option<&str>) {
+        match label {
+            Some(label) => self.add_line(&format!("{} -> {} [label=\"{}\"];", from, to, escape_dot(label))),
+            None => self.add_line(&format!("{} -> {};", from, to)),
+        }
+    }
+
+    /**
+     * Start a subgraph whose nodes are drawn in a labelled box, until `end_cluster`.
+     */
+    fn start_cluster(&mut self, id: &str, label: &str) {
+        self.add_line(&format!("subgraph cluster_{} {{", id));
+        self.indent += 1;
+        self.add_line(&format!("label=\"{}\";", escape_dot(label)));
+    }
+
+    fn end_cluster(&mut self) {
+        self.indent -= 1;
+        self.add_line("}");
+    }
+
+    fn finish(mut self) -> String {
+        self.dot.push_str("}\n");
+        self.dot
+    }
+}
+
+/**
+ * Escape a label for a quoted DOT string, where `\n` ends a centered line.
+ */
+fn escape_dot(label: &str) -> String {
+    label.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/**
+ * Draw every statement tree of the program, each in its own box named after the statement it belongs to.
+ * Nodes are labelled with their symbol and the type the semantic analysis gave them.
+ */
+pub fn statement_trees_to_dot(table: &SymbolTable) -> String {
+    let mut graph = DotGraph::new("statement_trees", &["ordering=out", "node [fontname=\"monospace\"]"]);
+    for (i, (title, tree)) in table.get_statement_trees().into_iter().enumerate() {
+        let id = format!("t{}", i);
+        graph.start_cluster(&id, &title);
+        add_statement_tree(&mut graph, tree, &id);
+        graph.end_cluster();
+    }
+    graph.finish()
+}
+
+/**
+ * Add the nodes of a statement tree, with ids starting with `prefix`.
+ * Returns the id of its root, if the tree isn't empty.
+ */
+fn add_statement_tree(graph: &mut DotGraph, tree: &StatementTree, prefix: &str) -> Option<String> {
+    let start = tree.start?;
+    for (i, node) in tree.nodes.iter().enumerate() {
+        let id = format!("{}_{}", prefix, i);
+        let (name, attributes) = match &node.symbol {
+            StatementSymbol::Decl(decl) => (to_var_name(decl), "shape=box, style=rounded"),
+            StatementSymbol::Literal(_) => (node.symbol.to_log_message(), "shape=box"),
+            StatementSymbol::Operator(_) | StatementSymbol::SingleChildOperator(_) => (node.symbol.to_log_message(), "shape=ellipse"),
+            StatementSymbol::FunctionCall(decl, _) => (format!("call {}", to_var_name(decl)), "shape=hexagon"),
+            StatementSymbol::ArrayAccess(decl, _) => (format!("{}[]", to_var_name(decl)), "shape=box3d"),
+        };
+        let node_type = match &node.node_type {
+            Some(node_type) => node_type.to_log_message(),
+            None => String::from("untyped"),
+        };
+        graph.add_node(&id, &format!("{}\n{}", name, node_type), attributes);
+
+        // Arguments and indices are trees of their own, hanging from the node
+        match &node.symbol {
+            StatementSymbol::FunctionCall(_, params) => {
+                for (j, param) in params.iter().enumerate() {
+                    if let Some(param_root) = add_statement_tree(graph, param, &format!("{}_arg{}", id, j)) {
+                        graph.add_edge(&id, &param_root, Some(&format!("arg {}", j + 1)));
+                    }
+                }
+            },
+            StatementSymbol::ArrayAccess(_, index) => {
+                if let Some(index_root) = add_statement_tree(graph, index, &format!("{}_index", id)) {
+                    graph.add_edge(&id, &index_root, Some("index"));
+                }
+            },
+            _ => {},
+        }
+    }
+
+    for (i, node) in tree.nodes.iter().enumerate() {
+        for child in [node.left, node.right].into_iter().flatten() {
+            graph.add_edge(&format!("{}_{}", prefix, i), &format!("{}_{}", prefix, child), None);
+        }
+    }
+    Some(format!("{}_{}", prefix, start))
+}
+
+/**
+ * Draw the scopes of the symbol table with an edge from each scope to its parent.
+ * Every scope lists its type and the parameters and declarations it holds.
+ */
+pub fn scope_tree_to_dot(table: &SymbolTable) -> String {
+    let mut graph = DotGraph::new("scopes", &["rankdir=BT", "node [shape=box, fontname=\"monospace\"]"]);
+
+    // The function whose body each scope is
+    let mut function_names: Vec<Option<String>> = vec![None; table.get_scope_count()];
+    for scope_index in 0..table.get_scope_count() {
+        for symbol in table.get_scope(scope_index).get_symbols() {
+            if let SymbolEntry::Decl(decl_id) = symbol {
+                if let Some(BasicType::Function(func)) = table.find_decl_by_id(decl_id).map(|decl| &decl.var_type) {
+                    function_names[func.body_scope] = Some(decl_id.0.clone());
+                }
+            }
+        }
+    }
+
+    for (scope_index, function_name) in function_names.iter().enumerate() {
+        let scope = table.get_scope(scope_index);
+        let mut title = format!("scope {}: {}", scope.get_scope_id(), scope.get_scope_type().to_log_message());
+        if let Some(name) = function_name {
+            title.push_str(&format!(" ({})", name));
+        }
+        let mut lines = vec![title];
+        for symbol in scope.get_symbols() {
+            let (kind, decl_id) = match symbol {
+                SymbolEntry::Parameter(decl_id) => ("param ", decl_id),
+                SymbolEntry::Decl(decl_id) => ("", decl_id),
+                _ => continue,
+            };
+            if let Some(decl) = table.find_decl_by_id(decl_id) {
+                lines.push(format!("{}{}: {}", kind, decl.name, decl.var_type.to_log_message()));
+            }
+        }
+
+        let id = format!("s{}", scope_index);
+        graph.add_left_aligned_node(&id, &lines);
+        if scope_index != scope.get_parent_scope() {
+            graph.add_edge(&id, &format!("s{}", scope.get_parent_scope()), Some("parent"));
+        }
+    }
+    graph.finish()
+}
+
+/**
+ * Draw the parse tree, with non terminals as ellipses and tokens as boxes.
+ * Non terminals expanded to nothing get an `e` child.
+ */
+pub fn parse_tree_to_dot(tree: &ParseTree) -> String {
+    let mut graph = DotGraph::new("parse_tree", &["ordering=out", "node [fontname=\"monospace\"]"]);
+    for (i, node) in tree.nodes.iter().enumerate() {
+        let id = format!("n{}", i);
+        match &node.symbol {
+            ParseSymbol::NonTerminal(non_terminal) => {
+                graph.add_node(&id, &format!("<{}>", non_terminal.get_name()), "shape=ellipse");
+                if node.children.is_empty() {
+                    graph.add_node(&format!("{}_e", id), "e", "shape=plaintext");
+                    graph.add_edge(&id, &format!("{}_e", id), None);
+                }
+            },
+            ParseSymbol::Terminal(token @ (Token::Identifier(_) | Token::Tint(_) | Token::Tdouble(_))) => {
+                graph.add_node(&id, &format!("{}\n{}", get_terminal_name(token), token), "shape=box");
+            },
+            ParseSymbol::Terminal(token) => graph.add_node(&id, &token.to_string(), "shape=box"),
+        }
+        for child in node.children.iter() {
+            graph.add_edge(&id, &format!("n{}", child), None);
+        }
+    }
+    graph.finish()
+}
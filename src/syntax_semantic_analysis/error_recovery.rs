@@ -0,0 +1,173 @@
+use crate::lexical_analysis::Token;
+use super::first_set::{get_sequence_first_set, FirstSet};
+use super::follow_set::FollowSetType;
+use super::grammar::{Grammar, TERMINAL_NAMES};
+use super::ll1_table::LL1Table;
+use super::non_terminals::{self, NonTerminal, NON_TERMINALS};
+use super::productions::{Production, ProductionType};
+
+/**
+ * Terminals that are often forgotten, and are inserted when they are expected but missing.
+ */
+pub const INSERTABLE_TOKENS: [Token; 5] = [
+    Token::Ssemicolon,
+    Token::Kfi,
+    Token::Kod,
+    Token::Kfed,
+    Token::Scparen,
+];
+
+/**
+ * Tokens that end a statement or a block, where parsing can resume after skipping tokens.
+ */
+pub const SYNC_TOKENS: [Token; 5] = [
+    Token::Ssemicolon,
+    Token::Kfi,
+    Token::Kod,
+    Token::Kfed,
+    Token::Speriod,
+];
+
+/**
+ * Tokens that start a block, with the token that ends it.
+ * A block found where it doesn't fit (e.g. a function declared after the variables) is skipped as a whole,
+ * rather than parsing its content as if it belonged there.
+ */
+pub const BLOCKS: [(Token, Token); 1] = [
+    (Token::Kdef, Token::Kfed),
+];
+
+/**
+ * What to do when a non terminal has no production for the current token (an empty cell of the LL(1) table).
+ */
+#[derive(Debug, Clone)]
+pub enum RecoveryAction {
+    // A terminal is missing: insert it and expand the production that starts with it
+    Insert(Token, usize),
+    // Expand the non terminal to epsilon, and let what comes after it handle the token
+    UseEpsilon(usize),
+    // Skip tokens until the non terminal can continue or a synchronising token is found
+    Synchronize,
+    // Skip a whole block up to its end token, and the `;` after it
+    SkipBlock(Token),
+}
+
+/**
+ * The recovery action of every cell, indexed like the LL(1) table.
+ */
+pub type RecoveryTable = Box<[Box<[RecoveryAction]>]>;
+
+/**
+ * Pick a recovery action for every empty cell of the LL(1) table.
+ * Cells that have a production are never recovered from, and are left as `Synchronize`.
+ */
+pub fn generate_recovery_table(
+    productions: &[Production],
+    first_sets: &[FirstSet],
+    table: &LL1Table
+) -> RecoveryTable {
+    NON_TERMINALS.iter()
+        .map(|non_terminal| {
+            let mut row = vec![RecoveryAction::Synchronize; non_terminals::NUM_TERMINALS + 1];
+            for (_, token) in TERMINAL_NAMES.iter() {
+                if table[non_terminal.to_index()][token.to_index()].is_none() {
+                    row[token.to_index()] = get_recovery_action(*non_terminal, token, productions, first_sets, table);
+                }
+            }
+            row.into_boxed_slice()
+        })
+        .collect()
+}
+
+fn get_recovery_action(
+    non_terminal: NonTerminal,
+    token: &Token,
+    productions: &[Production],
+    first_sets: &[FirstSet],
+    table: &LL1Table
+) -> RecoveryAction {
+    if let Some((_, end)) = BLOCKS.iter().find(|(start, _)| start.equals_type(token)) {
+        return RecoveryAction::SkipBlock(end.clone());
+    }
+
+    // Insert a missing terminal if the token can start what comes after it
+    for insertable in INSERTABLE_TOKENS.iter() {
+        let production_index = match table[non_terminal.to_index()][insertable.to_index()] {
+            Some(production_index) => production_index,
+            None => continue,
+        };
+        let production = &productions[production_index];
+        if let Some(ProductionType::Terminal(first)) = production.right.first() {
+            let (tokens, _) = get_sequence_first_set(&production.right[1..], first_sets);
+            if first.equals_type(insertable) && tokens.iter().any(|t| t.equals_type(token)) {
+                return RecoveryAction::Insert(insertable.clone(), production_index);
+            }
+        }
+    }
+
+    // Otherwise the non terminal can be left out if it can derive epsilon
+    let epsilon_production = productions.iter()
+        .position(|production| production.left == non_terminal && get_sequence_first_set(&production.right, first_sets).1);
+    match epsilon_production {
+        Some(production_index) => RecoveryAction::UseEpsilon(production_index),
+        None => RecoveryAction::Synchronize,
+    }
+}
+
+/**
+ * Whether the token is the first terminal of the production the non terminal expands to for it.
+ * Unlike a production chosen because the token follows the non terminal, this means the token really fits here.
+ */
+pub fn starts_production(non_terminal: NonTerminal, token: &Token, grammar: &Grammar) -> bool {
+    match grammar.table[non_terminal.to_index()][token.to_index()] {
+        Some(production_index) => get_sequence_first_set(&grammar.productions[production_index].right, &grammar.first_sets).0
+            .iter()
+            .any(|t| t.equals_type(token)),
+        None => false,
+    }
+}
+
+/**
+ * Whether the token fits somewhere further down the stack, if what is above that point is left out
+ * (non terminals that derive epsilon and missing terminals that would be inserted).
+ * A token that fits later is kept rather than deleted as a stray token.
+ */
+pub fn is_accepted_later<'a>(stack: impl Iterator<Item = &'a ProductionType>, token: &Token, grammar: &Grammar) -> bool {
+    for elem in stack {
+        match elem {
+            ProductionType::Terminal(terminal) => {
+                if terminal.equals_type(token) {
+                    return true;
+                }
+                if !INSERTABLE_TOKENS.iter().any(|t| t.equals_type(terminal)) {
+                    return false;
+                }
+            },
+            ProductionType::NonTerminal(non_terminal) => {
+                if starts_production(*non_terminal, token, grammar) {
+                    return true;
+                }
+                match grammar.recovery_table[non_terminal.to_index()][token.to_index()] {
+                    RecoveryAction::Insert(_, _) => return true,
+                    RecoveryAction::UseEpsilon(_) => {},
+                    RecoveryAction::Synchronize | RecoveryAction::SkipBlock(_) => {
+                        if !grammar.first_sets[non_terminal.to_index()].contains_epsilon() {
+                            return false;
+                        }
+                    },
+                }
+            },
+            ProductionType::Action(_) => {},
+        }
+    }
+    false
+}
+
+/**
+ * Whether skipping tokens can stop at the token when recovering from an error in the non terminal.
+ */
+pub fn is_sync_token(non_terminal: NonTerminal, token: &Token, grammar: &Grammar) -> bool {
+    SYNC_TOKENS.iter().any(|t| t.equals_type(token))
+        || grammar.follow_sets[non_terminal.to_index()].follow_set.iter()
+            .any(|elem| matches!(elem, FollowSetType::Terminal(t) if t.equals_type(token)))
+}
@@ -1,389 +1,103 @@
 use crate::lexical_analysis::Token;
-use super::non_terminals::NonTerminal;
+use super::non_terminals::{NonTerminal, NON_TERMINALS};
+use super::productions::{Production, ProductionType};
 
+#[derive(Debug, Clone)]
 pub enum FirstSetType {
     Terminal(Token),
     Epsilon,
 }
 
+#[derive(Debug)]
 pub struct FirstSet {
     pub non_terminal: NonTerminal,
     pub first_set: Box<[FirstSetType]>,
 }
 
-pub fn get_constant_first_sets() -> Box<[FirstSet]> {
-    Box::new([
-        // <program>: def, int, double, IDENTIFIER, if, while, print, return, ;, .
-        FirstSet {
-            non_terminal: NonTerminal::Program,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kdef),
-                FirstSetType::Terminal(Token::Kint),
-                FirstSetType::Terminal(Token::Kdouble),
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Kif),
-                FirstSetType::Terminal(Token::Kwhile),
-                FirstSetType::Terminal(Token::Kprint),
-                FirstSetType::Terminal(Token::Kreturn),
-                FirstSetType::Terminal(Token::Ssemicolon),
-                FirstSetType::Terminal(Token::Speriod),
-            ]),
-        },
-        // <fdecls>: def, e
-        FirstSet {
-            non_terminal: NonTerminal::Fdecls,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kdef),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <fdec>: def
-        FirstSet {
-            non_terminal: NonTerminal::Fdec,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kdef),
-            ]),
-        },
-        // <params>: int, double
-        FirstSet {
-            non_terminal: NonTerminal::Params,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kint),
-                FirstSetType::Terminal(Token::Kdouble),
-            ]),
-        },
-        // <params2>: COMMA, e
-        FirstSet {
-            non_terminal: NonTerminal::Params2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Scomma),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <type_var>: int, double
-        FirstSet {
-            non_terminal: NonTerminal::TypeVar,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kint),
-                FirstSetType::Terminal(Token::Kdouble),
-            ]),
-        },
-        // <fname>: IDENTIFIER
-        FirstSet {
-            non_terminal: NonTerminal::Fname,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-            ]),
-        },
-        // <declarations>: int, double, e
-        FirstSet {
-            non_terminal: NonTerminal::Declarations,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kint),
-                FirstSetType::Terminal(Token::Kdouble),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <declarations_seq>: int, double, IDENTIFIER, if, while, print, return, ;, e
-        FirstSet {
-            non_terminal: NonTerminal::DeclarationsSeq,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kint),
-                FirstSetType::Terminal(Token::Kdouble),
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Kif),
-                FirstSetType::Terminal(Token::Kwhile),
-                FirstSetType::Terminal(Token::Kprint),
-                FirstSetType::Terminal(Token::Kreturn),
-                FirstSetType::Terminal(Token::Ssemicolon),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <decl>: int, double
-        FirstSet {
-            non_terminal: NonTerminal::Decl,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kint),
-                FirstSetType::Terminal(Token::Kdouble),
-            ]),
-        },
-        // <type>: int, double
-        FirstSet {
-            non_terminal: NonTerminal::Type,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kint),
-                FirstSetType::Terminal(Token::Kdouble),
-            ]),
-        },
-        // <varlist>: IDENTIFIER
-        FirstSet {
-            non_terminal: NonTerminal::VarList,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-            ]),
-        },
-        // <varlist2>: COMMA, e
-        FirstSet {
-            non_terminal: NonTerminal::VarList2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Scomma),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <statement_seq>: IDENTIFIER, if, while, print, return, ;, e
-        FirstSet {
-            non_terminal: NonTerminal::StatementSeq,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Kif),
-                FirstSetType::Terminal(Token::Kwhile),
-                FirstSetType::Terminal(Token::Kprint),
-                FirstSetType::Terminal(Token::Kreturn),
-                FirstSetType::Terminal(Token::Ssemicolon),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <statement_seq2>: ;, e
-        FirstSet {
-            non_terminal: NonTerminal::StatementSeq2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Ssemicolon),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <statement>: IDENTIFIER, if, while, print, return, e
-        FirstSet {
-            non_terminal: NonTerminal::Statement,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Kif),
-                FirstSetType::Terminal(Token::Kwhile),
-                FirstSetType::Terminal(Token::Kprint),
-                FirstSetType::Terminal(Token::Kreturn),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <if>: if
-        FirstSet {
-            non_terminal: NonTerminal::If,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kif),
-            ]),
-        },
-        // <else>: else, e
-        FirstSet {
-            non_terminal: NonTerminal::Else,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kelse),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <built_in>: print, return
-        FirstSet {
-            non_terminal: NonTerminal::BuiltIn,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kprint),
-                FirstSetType::Terminal(Token::Kreturn),
-            ]),
-        },
-        // <bexpr>: -, IDENTIFIER, T_INT, T_DOUBLE, (, not
-        FirstSet {
-            non_terminal: NonTerminal::Bexpr,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Terminal(Token::Knot),
-                FirstSetType::Terminal(Token::Ominus),
-            ]),
-        },
-        // <bexpr2>: or, e
-        FirstSet {
-            non_terminal: NonTerminal::Bexpr2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kor),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <bterm>: -, IDENTIFIER, T_INT, T_DOUBLE, (, not
-        FirstSet {
-            non_terminal: NonTerminal::Bterm,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Terminal(Token::Knot),
-                FirstSetType::Terminal(Token::Ominus),
-            ]),
-        },
-        // <bterm2>: and, e
-        FirstSet {
-            non_terminal: NonTerminal::Bterm2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Kand),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <bfactor>: -, IDENTIFIER, T_INT, T_DOUBLE, (, not
-        FirstSet {
-            non_terminal: NonTerminal::Bfactor,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Terminal(Token::Knot),
-                FirstSetType::Terminal(Token::Ominus),
-            ]),
-        },
-        // <bfactor2>: LT, GT, EQUAL, LTE, GTE, NOT, e
-        FirstSet {
-            non_terminal: NonTerminal::Bfactor2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Oequal),
-                FirstSetType::Terminal(Token::Olt),
-                FirstSetType::Terminal(Token::Ogt),
-                FirstSetType::Terminal(Token::Olte),
-                FirstSetType::Terminal(Token::Ogte),
-                FirstSetType::Terminal(Token::Onot),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <expr>: -, IDENTIFIER, T_INT, T_DOUBLE, (
-        FirstSet {
-            non_terminal: NonTerminal::Expr,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Terminal(Token::Ominus),
-            ]),
-        },
-        // <expr2>: +, -, e
-        FirstSet {
-            non_terminal: NonTerminal::Expr2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Oplus),
-                FirstSetType::Terminal(Token::Ominus),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <term>: -, IDENTIFIER, T_INT, T_DOUBLE, (
-        FirstSet {
-            non_terminal: NonTerminal::Term,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Terminal(Token::Ominus),
-            ]),
-        },
-        // <term2>: *, /, %, e
-        FirstSet {
-            non_terminal: NonTerminal::Term2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Omultiply),
-                FirstSetType::Terminal(Token::Odivide),
-                FirstSetType::Terminal(Token::Omod),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <neg_factor>: -, IDENTIFIER, T_INT, T_DOUBLE, (
-        FirstSet {
-            non_terminal: NonTerminal::NegFactor,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Terminal(Token::Ominus),
-            ]),
-        },
-        // <factor>: IDENTIFIER, T_INT, T_DOUBLE, (
-        FirstSet {
-            non_terminal: NonTerminal::Factor,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-            ]),
-        },
-        // <factor2>: [, (, e
-        FirstSet {
-            non_terminal: NonTerminal::Factor2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Sobracket),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <exprseq>: -, IDENTIFIER, T_INT, T_DOUBLE, (, not, e
-        FirstSet {
-            non_terminal: NonTerminal::ExprSeq,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-                FirstSetType::Terminal(Token::Soparen),
-                FirstSetType::Terminal(Token::Knot),
-                FirstSetType::Terminal(Token::Ominus),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <exprseq2>: COMMA, e
-        FirstSet {
-            non_terminal: NonTerminal::ExprSeq2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Scomma),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <comp>: LT, GT, EQUAL, LTE, GTE, NOT
-        FirstSet {
-            non_terminal: NonTerminal::Comp,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Oequal),
-                FirstSetType::Terminal(Token::Olt),
-                FirstSetType::Terminal(Token::Ogt),
-                FirstSetType::Terminal(Token::Olte),
-                FirstSetType::Terminal(Token::Ogte),
-                FirstSetType::Terminal(Token::Onot),
-            ]),
-        },
-        // <var>: IDENTIFIER
-        FirstSet {
-            non_terminal: NonTerminal::Var,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-            ]),
-        },
-        // <var2>: [, e
-        FirstSet {
-            non_terminal: NonTerminal::Var2,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Sobracket),
-                FirstSetType::Epsilon,
-            ]),
-        },
-        // <id>: IDENTIFIER
-        FirstSet {
-            non_terminal: NonTerminal::Id,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Identifier(String::new())),
-            ]),
-        },
-        // <number>: T_INT, T_DOUBLE
-        FirstSet {
-            non_terminal: NonTerminal::Number,
-            first_set: Box::new([
-                FirstSetType::Terminal(Token::Tint(0)),
-                FirstSetType::Terminal(Token::Tdouble(0.0)),
-            ]),
-        },
-    ])
-}
\ No newline at end of file
+impl FirstSet {
+    pub fn contains_epsilon(&self) -> bool {
+        self.first_set.iter().any(|elem| matches!(elem, FirstSetType::Epsilon))
+    }
+
+    /**
+     * Get the terminals of the set, without epsilon.
+     */
+    pub fn get_tokens(&self) -> Vec<Token> {
+        self.first_set.iter()
+            .filter_map(|elem| match elem {
+                FirstSetType::Terminal(token) => Some(token.clone()),
+                FirstSetType::Epsilon => None,
+            })
+            .collect()
+    }
+}
+
+/**
+ * Compute the FIRST set of every non terminal (indexed by `NonTerminal::to_index`).
+ * The sets are grown until none of them change anymore.
+ */
+pub fn compute_first_sets(productions: &[Production]) -> Box<[FirstSet]> {
+    let mut first_sets: Vec<FirstSet> = NON_TERMINALS.iter()
+        .map(|non_terminal| FirstSet {
+            non_terminal: *non_terminal,
+            first_set: Box::new([]),
+        })
+        .collect();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for production in productions.iter() {
+            let (tokens, nullable) = get_sequence_first_set(&production.right, &first_sets);
+
+            let mut first_set = first_sets[production.left.to_index()].first_set.to_vec();
+            for token in tokens {
+                if !first_set.iter().any(|elem| matches!(elem, FirstSetType::Terminal(t) if t.equals_type(&token))) {
+                    first_set.push(FirstSetType::Terminal(token));
+                    changed = true;
+                }
+            }
+            if nullable && !first_set.iter().any(|elem| matches!(elem, FirstSetType::Epsilon)) {
+                first_set.push(FirstSetType::Epsilon);
+                changed = true;
+            }
+            first_sets[production.left.to_index()].first_set = first_set.into_boxed_slice();
+        }
+    }
+
+    first_sets.into_boxed_slice()
+}
+
+/**
+ * Get the terminals that can start a sequence of production elements,
+ * and whether the whole sequence can derive epsilon. Semantic actions are skipped.
+ */
+pub fn get_sequence_first_set(sequence: &[ProductionType], first_sets: &[FirstSet]) -> (Vec<Token>, bool) {
+    let mut tokens: Vec<Token> = Vec::new();
+    for elem in sequence.iter() {
+        match elem {
+            ProductionType::Terminal(token) => {
+                if !tokens.iter().any(|t| t.equals_type(token)) {
+                    tokens.push(token.clone());
+                }
+                return (tokens, false);
+            },
+            ProductionType::NonTerminal(non_terminal) => {
+                let first_set = &first_sets[non_terminal.to_index()];
+                for first_elem in first_set.first_set.iter() {
+                    if let FirstSetType::Terminal(token) = first_elem {
+                        if !tokens.iter().any(|t| t.equals_type(token)) {
+                            tokens.push(token.clone());
+                        }
+                    }
+                }
+
+                if !first_set.contains_epsilon() {
+                    return (tokens, false);
+                }
+            },
+            ProductionType::Action(_) => {},
+        }
+    }
+    (tokens, true)
+}
@@ -0,0 +1,353 @@
+use std::fmt::{self, Display, Formatter};
+use std::sync::LazyLock;
+
+use crate::lexical_analysis::Token;
+use super::error_recovery::{generate_recovery_table, RecoveryTable};
+use super::first_set::{compute_first_sets, FirstSet};
+use super::follow_set::{compute_follow_sets, FollowSet};
+use super::ll1_table::{generate_ll1_table, LL1Conflict, LL1Table};
+use super::non_terminals::NonTerminal;
+use super::productions::{Production, ProductionType, GRAMMAR};
+use super::semantic_actions::SemanticAction;
+
+/**
+ * The productions of a grammar with everything the parser derives from them,
+ * including what to do on a syntax error.
+ */
+pub struct Grammar {
+    pub productions: Box<[Production]>,
+    pub first_sets: Box<[FirstSet]>,
+    pub follow_sets: Box<[FollowSet]>,
+    pub table: LL1Table,
+    pub recovery_table: RecoveryTable,
+}
+
+impl Grammar {
+    /**
+     * Compute the FIRST and FOLLOW sets and the LL(1) table of the productions.
+     */
+    pub fn from_productions(productions: Box<[Production]>) -> Result<Grammar, Vec<LL1Conflict>> {
+        let first_sets = compute_first_sets(&productions);
+        let follow_sets = compute_follow_sets(&productions, &first_sets);
+        let table = generate_ll1_table(&productions, &first_sets, &follow_sets)?;
+        let recovery_table = generate_recovery_table(&productions, &first_sets, &table);
+        Ok(Grammar {
+            productions,
+            first_sets,
+            follow_sets,
+            table,
+            recovery_table,
+        })
+    }
+
+    /**
+     * Get the grammar of the language (`GRAMMAR`), which is only built the first time it is needed.
+     * It being invalid or not LL(1) is a bug in the compiler, the error listing every problem found.
+     */
+    pub fn get_constant_grammar() -> Result<&'static Grammar, String> {
+        CONSTANT_GRAMMAR.as_ref().map_err(|e| e.clone())
+    }
+}
+
+static CONSTANT_GRAMMAR: LazyLock<Result<Grammar, String>> = LazyLock::new(|| {
+    let productions = parse_grammar(GRAMMAR)
+        .map_err(|e| format!("invalid grammar in simplified_productions.txt: {}", e))?;
+    Grammar::from_productions(productions.clone()).map_err(|conflicts| {
+        let messages: Vec<String> = conflicts.iter().map(|conflict| conflict.to_message(&productions)).collect();
+        format!("the grammar in simplified_productions.txt is not LL(1):\n{}", messages.join("\n"))
+    })
+});
+
+/**
+ * An error in a grammar file, with the line (starting at 1) it was found on.
+ */
+#[derive(Debug)]
+pub struct GrammarError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum GrammarSymbol {
+    NonTerminal(String),
+    Action(String),
+    Terminal(String),
+    Define,
+    Alternative,
+}
+
+/**
+ * Parse a grammar written in BNF with semantic actions, like `simplified_productions.txt`.
+ *
+ * - `<name> ::= ...` defines the productions of a non terminal, and may span several lines
+ * - `|` separates the alternatives, and `e` is an empty alternative
+ * - `[NAME]` is a semantic action, while a `[` on its own is the terminal
+ * - Keywords and punctuation are written as they appear in programs. Identifiers, literals and
+ *   the comparison operators are written `IDENTIFIER`, `T_INT`, `T_DOUBLE`, `LT`, `GT`, `EQUAL`, `LTE`, `GTE` and `NOT`
+ *
+ * The productions are returned in the order they are written, and the first non terminal is the start symbol.
+ */
+pub fn parse_grammar(grammar: &str) -> Result<Box<[Production]>, GrammarError> {
+    let symbols = tokenize_grammar(grammar)?;
+    let mut productions: Vec<Production> = Vec::new();
+    let mut used_non_terminals: Vec<(NonTerminal, usize)> = Vec::new();
+
+    let mut i = 0;
+    while i < symbols.len() {
+        // Every rule starts with `<name> ::=`
+        let (left, line) = match (&symbols[i], symbols.get(i + 1)) {
+            ((GrammarSymbol::NonTerminal(name), line), Some((GrammarSymbol::Define, _))) => (get_non_terminal(name, *line)?, *line),
+            ((symbol, line), _) => return Err(GrammarError {
+                line: *line,
+                message: format!("expected a rule like `<name> ::= ...`, found {}", symbol),
+            }),
+        };
+        i += 2;
+
+        // The rule ends where the next one starts
+        let mut alternatives: Vec<(Vec<&GrammarSymbol>, usize)> = vec![(Vec::new(), line)];
+        while i < symbols.len() {
+            if let (GrammarSymbol::NonTerminal(_), Some((GrammarSymbol::Define, _))) = (&symbols[i].0, symbols.get(i + 1)) {
+                break;
+            }
+            match &symbols[i] {
+                (GrammarSymbol::Alternative, line) => alternatives.push((Vec::new(), *line)),
+                (GrammarSymbol::Define, line) => return Err(GrammarError {
+                    line: *line,
+                    message: String::from("unexpected `::=`, rules must start with a non terminal"),
+                }),
+                (symbol, _) => alternatives.last_mut().unwrap().0.push(symbol),
+            }
+            i += 1;
+        }
+
+        for (alternative, line) in alternatives {
+            let right = match alternative.as_slice() {
+                [] => return Err(GrammarError {
+                    line,
+                    message: format!("empty alternative for <{}>, write `e` for an epsilon production", left.get_name()),
+                }),
+                [GrammarSymbol::Terminal(name)] if name == "e" => Vec::new(),
+                _ => {
+                    let mut right = Vec::new();
+                    for symbol in alternative {
+                        right.push(match symbol {
+                            GrammarSymbol::NonTerminal(name) => {
+                                let non_terminal = get_non_terminal(name, line)?;
+                                used_non_terminals.push((non_terminal, line));
+                                ProductionType::NonTerminal(non_terminal)
+                            },
+                            GrammarSymbol::Action(name) => ProductionType::Action(
+                                SemanticAction::from_name(name).ok_or_else(|| GrammarError {
+                                    line,
+                                    message: format!("unknown semantic action [{}]", name),
+                                })?
+                            ),
+                            GrammarSymbol::Terminal(name) if name == "e" => return Err(GrammarError {
+                                line,
+                                message: format!("`e` must be the only symbol of an alternative of <{}>", left.get_name()),
+                            }),
+                            GrammarSymbol::Terminal(name) => ProductionType::Terminal(
+                                get_terminal(name).ok_or_else(|| GrammarError {
+                                    line,
+                                    message: format!("unknown terminal `{}`", name),
+                                })?
+                            ),
+                            GrammarSymbol::Define | GrammarSymbol::Alternative => unreachable!(),
+                        });
+                    }
+                    right
+                },
+            };
+            productions.push(Production {
+                left,
+                right: right.into_boxed_slice(),
+            });
+        }
+    }
+
+    if productions.is_empty() {
+        return Err(GrammarError {
+            line: 1,
+            message: String::from("the grammar has no productions"),
+        });
+    }
+
+    // Every non terminal that is used needs a way to be expanded
+    for (non_terminal, line) in used_non_terminals {
+        if !productions.iter().any(|production| production.left == non_terminal) {
+            return Err(GrammarError {
+                line,
+                message: format!("<{}> is used but has no productions", non_terminal.get_name()),
+            });
+        }
+    }
+
+    Ok(productions.into_boxed_slice())
+}
+
+/**
+ * Split the grammar into symbols, each with its line.
+ */
+fn tokenize_grammar(grammar: &str) -> Result<Vec<(GrammarSymbol, usize)>, GrammarError> {
+    let mut symbols = Vec::new();
+
+    for (line_index, line) in grammar.lines().enumerate() {
+        let line_number = line_index + 1;
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            if c.is_whitespace() {
+                i += 1;
+                continue;
+            }
+
+            // Length of the name that follows the current character
+            let name_length = |is_name_char: fn(&char) -> bool| {
+                chars[i + 1..].iter().take_while(|c| is_name_char(c)).count()
+            };
+            let action_length = name_length(|c| c.is_ascii_uppercase() || *c == '_');
+
+            let symbol = if chars[i..].starts_with(&[':', ':', '=']) {
+                i += 3;
+                GrammarSymbol::Define
+            } else if c == '|' {
+                i += 1;
+                GrammarSymbol::Alternative
+            } else if c == '<' {
+                let length = name_length(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '_');
+                if length == 0 || chars.get(i + 1 + length) != Some(&'>') {
+                    return Err(GrammarError {
+                        line: line_number,
+                        message: String::from("unclosed non terminal, write `LT` for the < terminal"),
+                    });
+                }
+                let name: String = chars[i + 1..i + 1 + length].iter().collect();
+                i += length + 2;
+                GrammarSymbol::NonTerminal(name)
+            } else if c == '[' && action_length > 0 && chars.get(i + 1 + action_length) == Some(&']') {
+                let name: String = chars[i + 1..i + 1 + action_length].iter().collect();
+                i += action_length + 2;
+                GrammarSymbol::Action(name)
+            } else if c.is_ascii_alphanumeric() || c == '_' {
+                let length = 1 + name_length(|c| c.is_ascii_alphanumeric() || *c == '_');
+                let name: String = chars[i..i + length].iter().collect();
+                i += length;
+                GrammarSymbol::Terminal(name)
+            } else {
+                i += 1;
+                GrammarSymbol::Terminal(c.to_string())
+            };
+            symbols.push((symbol, line_number));
+        }
+    }
+
+    Ok(symbols)
+}
+
+fn get_non_terminal(name: &str, line: usize) -> Result<NonTerminal, GrammarError> {
+    NonTerminal::from_name(name).ok_or_else(|| GrammarError {
+        line,
+        message: format!("unknown non terminal <{}>", name),
+    })
+}
+
+/**
+ * The name of every terminal in the grammar file, with the token it stands for (in token index order).
+ */
+pub const TERMINAL_NAMES: [(&str, Token); 38] = [
+    ("IDENTIFIER", Token::Identifier(String::new())),
+    ("T_INT", Token::Tint(0)),
+    ("T_DOUBLE", Token::Tdouble(0.0)),
+    ("if", Token::Kif),
+    ("then", Token::Kthen),
+    ("else", Token::Kelse),
+    ("fi", Token::Kfi),
+    ("while", Token::Kwhile),
+    ("do", Token::Kdo),
+    ("od", Token::Kod),
+    ("def", Token::Kdef),
+    ("fed", Token::Kfed),
+    ("return", Token::Kreturn),
+    ("and", Token::Kand),
+    ("or", Token::Kor),
+    ("not", Token::Knot),
+    ("int", Token::Kint),
+    ("double", Token::Kdouble),
+    ("print", Token::Kprint),
+    ("+", Token::Oplus),
+    ("-", Token::Ominus),
+    ("*", Token::Omultiply),
+    ("/", Token::Odivide),
+    ("%", Token::Omod),
+    ("=", Token::Oassign),
+    ("EQUAL", Token::Oequal),
+    ("LT", Token::Olt),
+    ("LTE", Token::Olte),
+    ("GT", Token::Ogt),
+    ("GTE", Token::Ogte),
+    ("NOT", Token::Onot),
+    (",", Token::Scomma),
+    (";", Token::Ssemicolon),
+    (".", Token::Speriod),
+    ("(", Token::Soparen),
+    (")", Token::Scparen),
+    ("[", Token::Sobracket),
+    ("]", Token::Scbracket),
+];
+
+/**
+ * Get the token a terminal of the grammar file stands for.
+ */
+fn get_terminal(name: &str) -> Option<Token> {
+    TERMINAL_NAMES.iter()
+        .find(|(terminal_name, _)| *terminal_name == name)
+        .map(|(_, token)| token.clone())
+}
+
+/**
+ * Get the name of a token's terminal in the grammar file.
+ */
+pub fn get_terminal_name(token: &Token) -> &'static str {
+    TERMINAL_NAMES.iter()
+        .find(|(_, terminal)| terminal.equals_type(token))
+        .map(|(name, _)| *name)
+        .unwrap()
+}
+
+impl Display for GrammarSymbol {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GrammarSymbol::NonTerminal(name) => write!(f, "<{}>", name),
+            GrammarSymbol::Action(name) => write!(f, "[{}]", name),
+            GrammarSymbol::Terminal(name) => write!(f, "`{}`", name),
+            GrammarSymbol::Define => write!(f, "`::=`"),
+            GrammarSymbol::Alternative => write!(f, "`|`"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_grammar_is_built_once() {
+        let first = Grammar::get_constant_grammar().unwrap();
+        let second = Grammar::get_constant_grammar().unwrap();
+        assert!(std::ptr::eq(first, second));
+    }
+
+    #[test]
+    fn invalid_grammar_is_an_error() {
+        assert!(parse_grammar("<program> ::= <unknown> .").is_err());
+        assert!(parse_grammar("::= .").is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use super::first_set::FirstSetType;
+use super::follow_set::FollowSetType;
+use super::grammar::{get_terminal_name, Grammar, TERMINAL_NAMES};
+use super::non_terminals::{NUM_TERMINALS, NON_TERMINALS};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GrammarFormat {
+    Csv,
+    Markdown,
+    Html,
+}
+
+/**
+ * The grammar laid out as tables of text, before being written in a format.
+ */
+struct GrammarTables {
+    // One column per terminal, plus the end of input
+    columns: Vec<String>,
+    // The non terminal and the production chosen for each column
+    table_rows: Vec<(String, Vec<String>)>,
+    // The non terminal, its FIRST set and its FOLLOW set
+    set_rows: Vec<[String; 3]>,
+}
+
+/**
+ * Write the LL(1) table generated for the grammar and its FIRST and FOLLOW sets.
+ * Table cells hold the text of the production chosen, and columns are named by terminal,
+ * the way they are written in the grammar file (`$` is the end of input).
+ */
+pub fn dump_grammar(grammar: &Grammar, format: GrammarFormat) -> String {
+    let tables = get_grammar_tables(grammar);
+    match format {
+        GrammarFormat::Csv => dump_csv(&tables),
+        GrammarFormat::Markdown => dump_markdown(&tables),
+        GrammarFormat::Html => dump_html(&tables),
+    }
+}
+
+fn get_grammar_tables(grammar: &Grammar) -> GrammarTables {
+    // Column index in the LL(1) table, and its name
+    let mut columns: Vec<(usize, String)> = TERMINAL_NAMES.iter()
+        .map(|(name, token)| (token.to_index(), name.to_string()))
+        .collect();
+    columns.push((NUM_TERMINALS, String::from("$")));
+
+    let table_rows = NON_TERMINALS.iter()
+        .map(|non_terminal| {
+            let row = &grammar.table[non_terminal.to_index()];
+            let cells = columns.iter()
+                .map(|(column, _)| match row[*column] {
+                    Some(production_index) => grammar.productions[production_index].to_string(),
+                    None => String::new(),
+                })
+                .collect();
+            (format!("<{}>", non_terminal.get_name()), cells)
+        })
+        .collect();
+
+    let set_rows = NON_TERMINALS.iter()
+        .map(|non_terminal| {
+            let first_set: Vec<&str> = grammar.first_sets[non_terminal.to_index()].first_set.iter()
+                .map(|elem| match elem {
+                    FirstSetType::Terminal(token) => get_terminal_name(token),
+                    FirstSetType::Epsilon => "e",
+                })
+                .collect();
+            let follow_set: Vec<&str> = grammar.follow_sets[non_terminal.to_index()].follow_set.iter()
+                .map(|elem| match elem {
+                    FollowSetType::Terminal(token) => get_terminal_name(token),
+                    FollowSetType::EndOfInput => "$",
+                })
+                .collect();
+            [format!("<{}>", non_terminal.get_name()), first_set.join(" "), follow_set.join(" ")]
+        })
+        .collect();
+
+    GrammarTables {
+        columns: columns.into_iter().map(|(_, name)| name).collect(),
+        table_rows,
+        set_rows,
+    }
+}
+
+/**
+ * The LL(1) table, an empty line, then the FIRST and FOLLOW sets (space separated).
+ */
+fn dump_csv(tables: &GrammarTables) -> String {
+    let mut csv = String::new();
+
+    let header: Vec<String> = std::iter::once("non_terminal")
+        .chain(tables.columns.iter().map(|column| column.as_str()))
+        .map(escape_csv)
+        .collect();
+    csv.push_str(&format!("{}\n", header.join(",")));
+    for (non_terminal, cells) in tables.table_rows.iter() {
+        let row: Vec<String> = std::iter::once(non_terminal)
+            .chain(cells.iter())
+            .map(|cell| escape_csv(cell))
+            .collect();
+        csv.push_str(&format!("{}\n", row.join(",")));
+    }
+
+    csv.push_str("\nnon_terminal,first,follow\n");
+    for row in tables.set_rows.iter() {
+        let row: Vec<String> = row.iter().map(|cell| escape_csv(cell)).collect();
+        csv.push_str(&format!("{}\n", row.join(",")));
+    }
+
+    csv
+}
+
+fn escape_csv(cell: &str) -> String {
+    if cell.contains([',', '"', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+fn dump_markdown(tables: &GrammarTables) -> String {
+    let mut markdown = String::from("# LL(1) table\n\n");
+
+    let header: Vec<String> = tables.columns.iter().map(|column| escape_markdown(column)).collect();
+    markdown.push_str(&format!("| Non terminal | {} |\n", header.join(" | ")));
+    markdown.push_str(&format!("|---|{}\n", "---|".repeat(tables.columns.len())));
+    for (non_terminal, cells) in tables.table_rows.iter() {
+        let row: Vec<String> = cells.iter().map(|cell| escape_markdown(cell)).collect();
+        markdown.push_str(&format!("| {} | {} |\n", escape_markdown(non_terminal), row.join(" | ")));
+    }
+
+    markdown.push_str("\n# FIRST and FOLLOW sets\n\n");
+    markdown.push_str("| Non terminal | FIRST | FOLLOW |\n|---|---|---|\n");
+    for row in tables.set_rows.iter() {
+        let row: Vec<String> = row.iter().map(|cell| escape_markdown(cell)).collect();
+        markdown.push_str(&format!("| {} |\n", row.join(" | ")));
+    }
+
+    markdown
+}
+
+/**
+ * Put the text in a code span, so grammar symbols like `<id>` and `*` are shown as written.
+ */
+fn escape_markdown(cell: &str) -> String {
+    if cell.is_empty() {
+        return String::new();
+    }
+    format!("`{}`", cell.replace('|', "\\|"))
+}
+
+fn dump_html(tables: &GrammarTables) -> String {
+    let mut html = String::from("<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>EZSharp grammar</title>
+<style>
+body { font-family: sans-serif; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #999; padding: 2px 6px; font-family: monospace; white-space: nowrap; }
+th { background: #eee; position: sticky; top: 0; }
+td:empty { background: #f8f8f8; }
+</style>
+</head>
+<body>
+<h1>LL(1) table</h1>
+<table>
+");
+
+    html.push_str("<tr><th>Non terminal</th>");
+    for column in tables.columns.iter() {
+        html.push_str(&format!("<th>{}</th>", escape_html(column)));
+    }
+    html.push_str("</tr>\n");
+    for (non_terminal, cells) in tables.table_rows.iter() {
+        html.push_str(&format!("<tr><th>{}</th>", escape_html(non_terminal)));
+        for cell in cells.iter() {
+            html.push_str(&format!("<td>{}</td>", escape_html(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>\n");
+
+    html.push_str("<h1>FIRST and FOLLOW sets</h1>\n<table>\n");
+    html.push_str("<tr><th>Non terminal</th><th>FIRST</th><th>FOLLOW</th></tr>\n");
+    for [non_terminal, first_set, follow_set] in tables.set_rows.iter() {
+        html.push_str(&format!(
+            "<tr><th>{}</th><td>{}</td><td>{}</td></tr>\n",
+            escape_html(non_terminal),
+            escape_html(first_set),
+            escape_html(follow_set)
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    html
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,137 @@
+use std::fmt::{self, Display, Formatter};
+
+use super::semantic_analysis::{SemanticError, SemanticErrorType, SemanticWarning};
+
+/**
+ * A check whose findings are warnings (or not reported, for the lints allowed by default),
+ * but that can be allowed (not reported) or denied (reported as errors).
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Lint {
+    UnreachableCode,
+    UninitializedVariable,
+    UnusedVariable,
+    UnusedParameter,
+    UnusedFunction,
+    Shadowing,
+    ConstantCondition,
+    UncheckedArrayIndex,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 8] = [
+        Lint::UnreachableCode,
+        Lint::UninitializedVariable,
+        Lint::UnusedVariable,
+        Lint::UnusedParameter,
+        Lint::UnusedFunction,
+        Lint::Shadowing,
+        Lint::ConstantCondition,
+        Lint::UncheckedArrayIndex,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::UninitializedVariable => "uninitialized-variable",
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnusedParameter => "unused-parameter",
+            Lint::UnusedFunction => "unused-function",
+            Lint::Shadowing => "shadowing",
+            Lint::ConstantCondition => "constant-condition",
+            Lint::UncheckedArrayIndex => "unchecked-array-index",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.get_name() == name)
+    }
+
+    /**
+     * Get the code of the diagnostics reported by the lint.
+     */
+    pub fn get_code(&self) -> &'static str {
+        match self {
+            Lint::UnreachableCode => "W0001",
+            Lint::UninitializedVariable => "W0002",
+            Lint::UnusedVariable => "W0003",
+            Lint::UnusedParameter => "W0004",
+            Lint::UnusedFunction => "W0005",
+            Lint::Shadowing => "W0006",
+            Lint::ConstantCondition => "W0007",
+            Lint::UncheckedArrayIndex => "W0008",
+        }
+    }
+
+    /**
+     * Get the level of the lint when it isn't set. Shadowing is legal and often intended, and most array indices
+     * aren't constant, so these lints have to be asked for.
+     */
+    pub fn get_default_level(&self) -> LintLevel {
+        match self {
+            Lint::Shadowing | Lint::UncheckedArrayIndex => LintLevel::Allow,
+            _ => LintLevel::Warn,
+        }
+    }
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.get_name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/**
+ * The level of every lint, its default level unless set otherwise.
+ */
+#[derive(Debug, Clone)]
+pub struct LintLevels {
+    levels: [LintLevel; Lint::ALL.len()],
+}
+
+impl Default for LintLevels {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LintLevels {
+    pub fn new() -> LintLevels {
+        LintLevels {
+            levels: Lint::ALL.map(|lint| lint.get_default_level()),
+        }
+    }
+
+    pub fn set_level(&mut self, lint: Lint, level: LintLevel) {
+        self.levels[lint as usize] = level;
+    }
+
+    pub fn get_level(&self, lint: Lint) -> LintLevel {
+        self.levels[lint as usize]
+    }
+
+    /**
+     * Sort warnings by the level of their lint: allowed ones are dropped and denied ones become errors.
+     * Returns the errors and the warnings that are left.
+     */
+    pub fn apply(&self, warnings: Vec<SemanticWarning>) -> (Vec<SemanticError>, Vec<SemanticWarning>) {
+        let mut errors = Vec::new();
+        let mut kept_warnings = Vec::new();
+        for warning in warnings {
+            let span = *warning.get_span();
+            match self.get_level(warning.get_lint()) {
+                LintLevel::Allow => {},
+                LintLevel::Warn => kept_warnings.push(warning),
+                LintLevel::Deny => errors.push(SemanticError::new(SemanticErrorType::DeniedLint(Box::new(warning)), span)),
+            }
+        }
+        (errors, kept_warnings)
+    }
+}
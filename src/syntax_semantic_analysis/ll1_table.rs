@@ -1,95 +1,146 @@
-use super::first_set;
-use super::first_set::FirstSetType;
+use std::fmt::{self, Display, Formatter};
+
+use super::first_set::{get_sequence_first_set, FirstSet};
 use super::follow_set::{FollowSet, FollowSetType};
-use super::productions::{Production, ProductionType};
+use super::grammar::get_terminal_name;
+use super::productions::Production;
 use super::non_terminals;
 
 pub type LL1Table = [[Option<usize>; non_terminals::NUM_TERMINALS + 1]; non_terminals::NUM_NON_TERMINALS];
 
-fn set_table_entry(
-    table: &mut LL1Table,
-    non_terminal: &non_terminals::NonTerminal,
-    token_index: usize,
-    production_index: usize
-) {
-    assert!(
-        table[non_terminal.to_index()][token_index].is_none(),
-        "Conflict in LL(1) table at non terminal {:?} and token {:?}",
-        non_terminal,
-        token_index
-    );
-    table[non_terminal.to_index()][token_index] = Some(production_index);
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictKind {
+    // Several productions can start with the lookahead
+    FirstFirst,
+    // A production can start with the lookahead, and another can derive epsilon and be followed by it
+    FirstFollow,
 }
 
-pub fn generate_ll1_table(productions: &Box<[Production]>, follow_sets: &Box<[FollowSet]>) -> LL1Table {
-    let first_sets = first_set::get_constant_first_sets();
-    let mut table: LL1Table = [[None; (non_terminals::NUM_TERMINALS + 1)]; non_terminals::NUM_NON_TERMINALS];
-    
-    // Loop thru first sets
-    for first_set::FirstSet{ non_terminal, first_set } in first_sets.iter() {
-        for fs_type in first_set.iter() {
-            if let FirstSetType::Terminal(token) = fs_type {
-                // If first set contains terminal, add to table
-                for (i, production) in productions.iter().enumerate() {
-                    // Find non_terminal production that matches first set
-                    if *non_terminal == production.left {
-                        // Go through production to ensure first set terminal is part of it
-                        let mut prod_elem_contains_epsilon = false;
-                        for prod_elem in production.right.iter() {
-                            if let ProductionType::NonTerminal(prod_non_terminal) = prod_elem {
-                                // Check if in first set of production non terminal
-                                for prod_first_elem in first_sets[prod_non_terminal.to_index()].first_set.iter() {
-                                    if let FirstSetType::Terminal(prod_token) = prod_first_elem {
-                                        if token == prod_token {
-                                            set_table_entry(&mut table, non_terminal, token.to_index(), i);
-                                            break;
-                                        }
-                                    } else {
-                                        // If non terminal first set contains epsilon, might need to check next element in production
-                                        prod_elem_contains_epsilon = true;
-                                    }
-                                }
-                            } else if let ProductionType::Terminal(prod_token) = prod_elem {
-                                // Check if terminal in first set matches first terminal in production
-                                if token == prod_token {
-                                    set_table_entry(&mut table, non_terminal, token.to_index(), i);
-                                    break;
-                                }
-                            } else {
-                                // ProductionType::Action
-                                continue;
-                            }
+/**
+ * A cell of the LL(1) table that more than one production wants.
+ * Productions are indices into the productions the table was generated from.
+ */
+#[derive(Debug)]
+pub struct LL1Conflict {
+    pub kind: ConflictKind,
+    pub non_terminal: non_terminals::NonTerminal,
+    pub lookahead: FollowSetType,
+    pub productions: Vec<usize>,
+}
+
+impl LL1Conflict {
+    /**
+     * Describe the conflict, with the text of each competing production.
+     */
+    pub fn to_message(&self, productions: &[Production]) -> String {
+        let mut message = self.to_string();
+        for production_index in self.productions.iter() {
+            message.push_str(&format!("\n    {}: {}", production_index, productions[*production_index]));
+        }
+        message
+    }
+}
+
+impl Display for LL1Conflict {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let kind = match self.kind {
+            ConflictKind::FirstFirst => "FIRST/FIRST",
+            ConflictKind::FirstFollow => "FIRST/FOLLOW",
+        };
+        let lookahead = match &self.lookahead {
+            FollowSetType::Terminal(token) => format!("`{}`", get_terminal_name(token)),
+            FollowSetType::EndOfInput => String::from("end of input"),
+        };
+        write!(
+            f,
+            "{} conflict for <{}> on {}, {} productions apply",
+            kind,
+            self.non_terminal.get_name(),
+            lookahead,
+            self.productions.len()
+        )
+    }
+}
+
+/**
+ * A production that can be chosen for a cell of the table.
+ */
+#[derive(Clone)]
+struct TableEntry {
+    production_index: usize,
+    // Whether the production was chosen because the lookahead can follow its non terminal
+    from_follow: bool,
+}
+
+#[derive(Clone, Default)]
+struct TableCell {
+    lookahead: Option<FollowSetType>,
+    entries: Vec<TableEntry>,
+}
+
+fn add_table_entry(cells: &mut [Vec<TableCell>], production: &Production, lookahead: FollowSetType, entry: TableEntry) {
+    let token_index = match &lookahead {
+        FollowSetType::Terminal(token) => token.to_index(),
+        FollowSetType::EndOfInput => non_terminals::NUM_TERMINALS,
+    };
+    let cell = &mut cells[production.left.to_index()][token_index];
+    if !cell.entries.iter().any(|e| e.production_index == entry.production_index) {
+        cell.entries.push(entry);
+    }
+    cell.lookahead = Some(lookahead);
+}
 
-                            if !prod_elem_contains_epsilon {
-                                break;
-                            }
-                        }
+/**
+ * Generate the LL(1) table of the productions.
+ * Every cell that more than one production competes for is reported, instead of only the first one.
+ */
+pub fn generate_ll1_table(productions: &[Production], first_sets: &[FirstSet], follow_sets: &[FollowSet]) -> Result<LL1Table, Vec<LL1Conflict>> {
+    let mut cells: Vec<Vec<TableCell>> = vec![vec![TableCell::default(); non_terminals::NUM_TERMINALS + 1]; non_terminals::NUM_NON_TERMINALS];
 
-                        if table[non_terminal.to_index()][token.to_index()].is_some() {
-                            // Production for token in this first set has been found
-                            break;
-                        }
-                    }
-                }                
-                assert!(
-                    table[non_terminal.to_index()][token.to_index()].is_some(),
-                    "No entry added to table for {:?} -> {:?}",
-                    non_terminal,
-                    token
-                );
-            } else if let FirstSetType::Epsilon = fs_type {
-                // If first set contains Epsilon, add follow set to table
-                for follow_elem in follow_sets[non_terminal.to_index()].follow_set.iter() {
-                    if let FollowSetType::Terminal(token) = follow_elem {
-                        set_table_entry(&mut table, non_terminal, token.to_index(), productions.len());
+    for (i, production) in productions.iter().enumerate() {
+        // A production is chosen when the next token can start it
+        let (tokens, nullable) = get_sequence_first_set(&production.right, first_sets);
+        for token in tokens {
+            add_table_entry(&mut cells, production, FollowSetType::Terminal(token), TableEntry {
+                production_index: i,
+                from_follow: false,
+            });
+        }
+
+        // If the production can derive epsilon, it is also chosen for the tokens that follow its non terminal
+        if nullable {
+            for follow_elem in follow_sets[production.left.to_index()].follow_set.iter() {
+                add_table_entry(&mut cells, production, follow_elem.clone(), TableEntry {
+                    production_index: i,
+                    from_follow: true,
+                });
+            }
+        }
+    }
+
+    let mut table: LL1Table = [[None; (non_terminals::NUM_TERMINALS + 1)]; non_terminals::NUM_NON_TERMINALS];
+    let mut conflicts: Vec<LL1Conflict> = Vec::new();
+    for (non_terminal, row) in non_terminals::NON_TERMINALS.iter().zip(cells) {
+        for (token_index, cell) in row.into_iter().enumerate() {
+            match cell.entries.as_slice() {
+                [] => {},
+                [entry] => table[non_terminal.to_index()][token_index] = Some(entry.production_index),
+                entries => conflicts.push(LL1Conflict {
+                    kind: if entries.iter().any(|entry| entry.from_follow) {
+                        ConflictKind::FirstFollow
                     } else {
-                        // End of input
-                        set_table_entry(&mut table, non_terminal, non_terminals::NUM_TERMINALS, productions.len());
-                    }
-                }
+                        ConflictKind::FirstFirst
+                    },
+                    non_terminal: *non_terminal,
+                    lookahead: cell.lookahead.unwrap(),
+                    productions: entries.iter().map(|entry| entry.production_index).collect(),
+                }),
             }
         }
     }
 
-    table
-}
\ No newline at end of file
+    if !conflicts.is_empty() {
+        return Err(conflicts);
+    }
+    Ok(table)
+}
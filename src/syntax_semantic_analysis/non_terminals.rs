@@ -44,8 +44,152 @@ pub enum NonTerminal {
     Number,
 }
 
+/**
+ * Every non terminal, in index order.
+ */
+pub const NON_TERMINALS: [NonTerminal; NUM_NON_TERMINALS] = [
+    NonTerminal::Program,
+    NonTerminal::Fdecls,
+    NonTerminal::Fdec,
+    NonTerminal::Params,
+    NonTerminal::Params2,
+    NonTerminal::TypeVar,
+    NonTerminal::Fname,
+    NonTerminal::Declarations,
+    NonTerminal::DeclarationsSeq,
+    NonTerminal::Decl,
+    NonTerminal::Type,
+    NonTerminal::VarList,
+    NonTerminal::VarList2,
+    NonTerminal::StatementSeq,
+    NonTerminal::StatementSeq2,
+    NonTerminal::Statement,
+    NonTerminal::If,
+    NonTerminal::Else,
+    NonTerminal::BuiltIn,
+    NonTerminal::Bexpr,
+    NonTerminal::Bexpr2,
+    NonTerminal::Bterm,
+    NonTerminal::Bterm2,
+    NonTerminal::Bfactor,
+    NonTerminal::Bfactor2,
+    NonTerminal::Expr,
+    NonTerminal::Expr2,
+    NonTerminal::Term,
+    NonTerminal::Term2,
+    NonTerminal::NegFactor,
+    NonTerminal::Factor,
+    NonTerminal::Factor2,
+    NonTerminal::ExprSeq,
+    NonTerminal::ExprSeq2,
+    NonTerminal::Comp,
+    NonTerminal::Var,
+    NonTerminal::Var2,
+    NonTerminal::Id,
+    NonTerminal::Number,
+];
+
 impl NonTerminal {
-    pub fn to_index(&self) -> usize {
-        *self as usize
+    pub fn to_index(self) -> usize {
+        self as usize
     }
-}
\ No newline at end of file
+}
+impl NonTerminal {
+    /**
+     * Get the name used for the non terminal in the grammar file, without the angle brackets.
+     */
+    pub fn get_name(self) -> &'static str {
+        match self {
+            NonTerminal::Program => "program",
+            NonTerminal::Fdecls => "fdecls",
+            NonTerminal::Fdec => "fdec",
+            NonTerminal::Params => "params",
+            NonTerminal::Params2 => "params2",
+            NonTerminal::TypeVar => "type_var",
+            NonTerminal::Fname => "fname",
+            NonTerminal::Declarations => "declarations",
+            NonTerminal::DeclarationsSeq => "declarations_seq",
+            NonTerminal::Decl => "decl",
+            NonTerminal::Type => "type",
+            NonTerminal::VarList => "varlist",
+            NonTerminal::VarList2 => "varlist2",
+            NonTerminal::StatementSeq => "statement_seq",
+            NonTerminal::StatementSeq2 => "statement_seq2",
+            NonTerminal::Statement => "statement",
+            NonTerminal::If => "if",
+            NonTerminal::Else => "else",
+            NonTerminal::BuiltIn => "built_in",
+            NonTerminal::Bexpr => "bexpr",
+            NonTerminal::Bexpr2 => "bexpr2",
+            NonTerminal::Bterm => "bterm",
+            NonTerminal::Bterm2 => "bterm2",
+            NonTerminal::Bfactor => "bfactor",
+            NonTerminal::Bfactor2 => "bfactor2",
+            NonTerminal::Expr => "expr",
+            NonTerminal::Expr2 => "expr2",
+            NonTerminal::Term => "term",
+            NonTerminal::Term2 => "term2",
+            NonTerminal::NegFactor => "neg_factor",
+            NonTerminal::Factor => "factor",
+            NonTerminal::Factor2 => "factor2",
+            NonTerminal::ExprSeq => "exprseq",
+            NonTerminal::ExprSeq2 => "exprseq2",
+            NonTerminal::Comp => "comp",
+            NonTerminal::Var => "var",
+            NonTerminal::Var2 => "var2",
+            NonTerminal::Id => "id",
+            NonTerminal::Number => "number",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<NonTerminal> {
+        NON_TERMINALS.iter().find(|non_terminal| non_terminal.get_name() == name).copied()
+    }
+
+    /**
+     * Describe the non terminal in human terms, for syntax errors.
+     */
+    pub fn get_description(self) -> &'static str {
+        match self {
+            NonTerminal::Program => "program",
+            NonTerminal::Fdecls => "function declarations",
+            NonTerminal::Fdec => "function declaration",
+            NonTerminal::Params => "parameters",
+            NonTerminal::Params2 => "parameters",
+            NonTerminal::TypeVar => "parameter",
+            NonTerminal::Fname => "function name",
+            NonTerminal::Declarations => "declarations",
+            NonTerminal::DeclarationsSeq => "declarations and statements",
+            NonTerminal::Decl => "declaration",
+            NonTerminal::Type => "type",
+            NonTerminal::VarList => "variable list",
+            NonTerminal::VarList2 => "variable list",
+            NonTerminal::StatementSeq => "statements",
+            NonTerminal::StatementSeq2 => "statements",
+            NonTerminal::Statement => "statement",
+            NonTerminal::If => "if statement",
+            NonTerminal::Else => "else branch",
+            NonTerminal::BuiltIn => "`print` or `return`",
+            NonTerminal::Bexpr => "expression",
+            NonTerminal::Bexpr2 => "expression",
+            NonTerminal::Bterm => "expression",
+            NonTerminal::Bterm2 => "expression",
+            NonTerminal::Bfactor => "expression",
+            NonTerminal::Bfactor2 => "expression",
+            NonTerminal::Expr => "expression",
+            NonTerminal::Expr2 => "expression",
+            NonTerminal::Term => "expression",
+            NonTerminal::Term2 => "expression",
+            NonTerminal::NegFactor => "expression",
+            NonTerminal::Factor => "expression",
+            NonTerminal::Factor2 => "expression",
+            NonTerminal::ExprSeq => "arguments",
+            NonTerminal::ExprSeq2 => "arguments",
+            NonTerminal::Comp => "comparison operator",
+            NonTerminal::Var => "variable",
+            NonTerminal::Var2 => "variable",
+            NonTerminal::Id => "identifier",
+            NonTerminal::Number => "number",
+        }
+    }
+}
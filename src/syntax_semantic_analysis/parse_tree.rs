@@ -0,0 +1,151 @@
+use crate::{lexical_analysis::{ParsedToken, Token}, logger::Loggable, source_map::Span};
+
+use super::grammar::get_terminal_name;
+use super::non_terminals::NonTerminal;
+use super::productions::{Production, ProductionType};
+
+#[derive(Debug, Clone)]
+pub enum ParseSymbol {
+    NonTerminal(NonTerminal),
+    // The token matched, with its value
+    Terminal(Token),
+}
+
+/**
+ * A node of the parse tree: a non terminal with the production it was expanded with, or a matched token.
+ * Semantic actions are not part of the tree.
+ */
+#[derive(Debug, Clone)]
+pub struct ParseNode {
+    pub symbol: ParseSymbol,
+    pub production_index: Option<usize>,
+    pub span: Span,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+/**
+ * The concrete syntax tree of a program, stored as a flat list of nodes.
+ * The root is the first node, and children always come after their parent.
+ */
+#[derive(Debug, Clone)]
+pub struct ParseTree {
+    pub nodes: Vec<ParseNode>,
+}
+
+impl ParseTree {
+    pub fn new(start: NonTerminal) -> ParseTree {
+        ParseTree {
+            nodes: vec![ParseNode {
+                symbol: ParseSymbol::NonTerminal(start),
+                production_index: None,
+                span: Span::default(),
+                parent: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    pub fn get_root(&self) -> &ParseNode {
+        &self.nodes[0]
+    }
+
+    pub fn get_node(&self, index: usize) -> &ParseNode {
+        &self.nodes[index]
+    }
+
+    /**
+     * Get the children of a node that are non terminals or tokens.
+     */
+    pub fn get_children(&self, index: usize) -> Vec<&ParseNode> {
+        self.nodes[index].children.iter().map(|child| &self.nodes[*child]).collect()
+    }
+
+    /**
+     * Expand a non terminal node with a production, adding a child for each element from `start` onwards.
+     * Returns the node of each of these elements (`None` for semantic actions).
+     */
+    pub fn expand(&mut self, index: usize, production_index: usize, production: &Production, start: usize) -> Vec<Option<usize>> {
+        self.nodes[index].production_index = Some(production_index);
+        production.right[start..].iter()
+            .map(|elem| {
+                let symbol = match elem {
+                    ProductionType::NonTerminal(non_terminal) => ParseSymbol::NonTerminal(*non_terminal),
+                    ProductionType::Terminal(token) => ParseSymbol::Terminal(token.clone()),
+                    ProductionType::Action(_) => return None,
+                };
+                let child = self.nodes.len();
+                self.nodes.push(ParseNode {
+                    symbol,
+                    production_index: None,
+                    span: Span::default(),
+                    parent: Some(index),
+                    children: Vec::new(),
+                });
+                self.nodes[index].children.push(child);
+                Some(child)
+            })
+            .collect()
+    }
+
+    /**
+     * Set the token a terminal node was matched with.
+     */
+    pub fn match_token(&mut self, index: usize, token: &ParsedToken) {
+        self.nodes[index].symbol = ParseSymbol::Terminal(token.token.clone());
+        self.nodes[index].span = token.span;
+    }
+
+    /**
+     * Give a non terminal that derives nothing an empty span, where the token after it starts.
+     */
+    pub fn set_empty_span(&mut self, index: usize, next_token: &ParsedToken) {
+        self.nodes[index].span = Span {
+            end: next_token.span.start,
+            ..next_token.span
+        };
+    }
+
+    /**
+     * Give every non terminal the span covering its tokens, once the whole program is parsed.
+     */
+    pub fn compute_spans(&mut self) {
+        // Children come after their parent, so going backwards visits them first
+        for index in (0..self.nodes.len()).rev() {
+            if self.nodes[index].children.is_empty() {
+                continue;
+            }
+            let spans: Vec<Span> = self.nodes[index].children.iter()
+                .map(|child| self.nodes[*child].span)
+                .collect();
+            let non_empty: Vec<&Span> = spans.iter().filter(|span| span.start < span.end).collect();
+            self.nodes[index].span = match (non_empty.first(), non_empty.last()) {
+                (Some(first), Some(last)) => first.to(last),
+                _ => spans[0],
+            };
+        }
+    }
+
+    fn node_to_log_message(&self, index: usize, depth: usize, message: &mut String) {
+        let node = &self.nodes[index];
+        let name = match &node.symbol {
+            ParseSymbol::NonTerminal(non_terminal) => format!("<{}>", non_terminal.get_name()),
+            ParseSymbol::Terminal(token @ (Token::Identifier(_) | Token::Tint(_) | Token::Tdouble(_))) => {
+                format!("{} `{}`", get_terminal_name(token), token)
+            },
+            ParseSymbol::Terminal(token) => format!("`{}`", token),
+        };
+        message.push_str(&format!("{}{} {}:{}\n", "\t".repeat(depth), name, node.span.line, node.span.column));
+        for child in node.children.iter() {
+            self.node_to_log_message(*child, depth + 1, message);
+        }
+    }
+}
+
+impl Loggable for ParseTree {
+    fn to_log_message(&self) -> String {
+        let mut message = String::new();
+        self.node_to_log_message(0, 0, &mut message);
+        message
+    }
+}
@@ -30,4 +30,84 @@ pub enum SemanticAction {
     StartWhile,
     StartPrint,
     StartReturn,
-}
\ No newline at end of file
+}
+
+/**
+ * Every semantic action, in declaration order.
+ */
+pub const SEMANTIC_ACTIONS: [SemanticAction; 30] = [
+    SemanticAction::SetFunc,
+    SemanticAction::AddFuncDecl,
+    SemanticAction::NewScope,
+    SemanticAction::PopScope,
+    SemanticAction::PopFunc,
+    SemanticAction::AddParam,
+    SemanticAction::SetType,
+    SemanticAction::AddVarDecl,
+    SemanticAction::ClearVarDecl,
+    SemanticAction::StartTypeTree,
+    SemanticAction::CheckVarType,
+    SemanticAction::CheckType,
+    SemanticAction::SplitTree,
+    SemanticAction::AddOperator,
+    SemanticAction::AddTypeTree,
+    SemanticAction::SetLiteral,
+    SemanticAction::AddFuncCheck,
+    SemanticAction::PopFuncCheck,
+    SemanticAction::CheckParamType,
+    SemanticAction::SetArray,
+    SemanticAction::SetId,
+    SemanticAction::StartAssignment,
+    SemanticAction::AddAssignment,
+    SemanticAction::AddCondition,
+    SemanticAction::AddCondStatement,
+    SemanticAction::StartIf,
+    SemanticAction::StartElse,
+    SemanticAction::StartWhile,
+    SemanticAction::StartPrint,
+    SemanticAction::StartReturn,
+];
+
+impl SemanticAction {
+    /**
+     * Get the name used for the action in the grammar file, without the square brackets.
+     */
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            SemanticAction::SetFunc => "SET_FUNC",
+            SemanticAction::AddFuncDecl => "ADD_FUNC_DECL",
+            SemanticAction::NewScope => "NEW_SCOPE",
+            SemanticAction::PopScope => "POP_SCOPE",
+            SemanticAction::PopFunc => "POP_FUNC",
+            SemanticAction::AddParam => "ADD_PARAM",
+            SemanticAction::SetType => "SET_TYPE",
+            SemanticAction::AddVarDecl => "ADD_VAR_DECL",
+            SemanticAction::ClearVarDecl => "CLEAR_VAR_DECL",
+            SemanticAction::StartTypeTree => "START_TYPE_TREE",
+            SemanticAction::CheckVarType => "CHECK_VAR_TYPE",
+            SemanticAction::CheckType => "CHECK_TYPE",
+            SemanticAction::SplitTree => "SPLIT_TREE",
+            SemanticAction::AddOperator => "ADD_OPERATOR",
+            SemanticAction::AddTypeTree => "ADD_TYPE_TREE",
+            SemanticAction::SetLiteral => "SET_LITERAL",
+            SemanticAction::AddFuncCheck => "ADD_FUNC_CHECK",
+            SemanticAction::PopFuncCheck => "POP_FUNC_CHECK",
+            SemanticAction::CheckParamType => "CHECK_PARAM_TYPE",
+            SemanticAction::SetArray => "SET_ARRAY",
+            SemanticAction::SetId => "SET_ID",
+            SemanticAction::StartAssignment => "START_ASSIGNMENT",
+            SemanticAction::AddAssignment => "ADD_ASSIGNMENT",
+            SemanticAction::AddCondition => "ADD_CONDITION",
+            SemanticAction::AddCondStatement => "ADD_COND_STATEMENT",
+            SemanticAction::StartIf => "START_IF",
+            SemanticAction::StartElse => "START_ELSE",
+            SemanticAction::StartWhile => "START_WHILE",
+            SemanticAction::StartPrint => "START_PRINT",
+            SemanticAction::StartReturn => "START_RETURN",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SemanticAction> {
+        SEMANTIC_ACTIONS.iter().find(|action| action.get_name() == name).cloned()
+    }
+}
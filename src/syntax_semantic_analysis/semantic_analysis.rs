@@ -1,5 +1,10 @@
-use crate::lexical_analysis::Token;
+use crate::diagnostics::{Diagnostic, Note, Severity, ToDiagnostic};
+use crate::intermediate_code_generation::layout::{FrameLayout, SlotKind, TargetLayout, MAX_FRAME_SIZE};
+use crate::lexical_analysis::{ParsedToken, Token};
 use crate::logger::Loggable;
+use crate::source_map::Span;
+use super::constant_evaluation::{ConstantEvaluator, ConstantValue};
+use super::lints::Lint;
 use super::semantic_actions::SemanticAction;
 
 use super::symbol_declaration::DeclId;
@@ -26,38 +31,173 @@ pub enum SemanticErrorType {
     UndefinedVariable(String),
     TypeMismatch(String),
     InvalidType(String),
-    DuplicateDeclaration(String),
+    // The name, and the kind of scope it is declared twice in
+    DuplicateDeclaration(String, ScopeType),
     MissingParameters(String),
     InvalidArraySize(String),
+    MissingReturn(String),
+    // The operator, `/` or `%`
+    DivisionByZero(Token),
+    // The operation, e.g. `2147483647 + 1`
+    ArithmeticOverflow(String),
+    IntLiteralOutOfRange(u32),
+    IndexOutOfBounds(String),
+    // The function whose variables don't fit in its frame, `main` for the main program
+    FrameTooLarge(String),
+    // A warning whose lint is denied
+    DeniedLint(Box<SemanticWarning>),
 }
 
+#[derive(Debug)]
 pub struct SemanticError {
     error_type: SemanticErrorType,
-    line: usize,
+    span: Span,
+    notes: Vec<Note>,
+    help: Vec<String>,
 }
 
 impl SemanticError {
-    pub fn new(error_type: SemanticErrorType, line: usize) -> Self {
+    pub fn new(error_type: SemanticErrorType, span: Span) -> Self {
         Self {
             error_type,
-            line,
+            span,
+            notes: Vec::new(),
+            help: Vec::new(),
         }
     }
+
+    /**
+     * Attach a note pointing at a related part of the source (e.g. a declaration).
+     */
+    pub fn with_note(mut self, message: &str, span: Span) -> Self {
+        self.notes.push(Note { message: message.to_string(), span: Some(span) });
+        self
+    }
+
+    /**
+     * Attach a suggestion on how to fix the error.
+     */
+    pub fn with_help(mut self, help: Option<String>) -> Self {
+        self.help.extend(help);
+        self
+    }
+
+    pub fn get_error_type(&self) -> &SemanticErrorType {
+        &self.error_type
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.span.line
+    }
+
+    pub fn get_notes(&self) -> &Vec<Note> {
+        &self.notes
+    }
 }
 
 impl Loggable for SemanticError {
     fn to_log_message(&self) -> String {
         match &self.error_type {
-            SemanticErrorType::DuplicateDeclaration(id) => format!("Duplicate declaration on line {}: {}", self.line, id),
-            SemanticErrorType::UndefinedVariable(id) => format!("Undefined variable on line {}: {}", self.line, id),
-            SemanticErrorType::TypeMismatch(types_comp) => format!("Type mismatch on line {}: {}", self.line, types_comp),
-            SemanticErrorType::InvalidType(msg) => format!("Invalid type on line {}: {}", self.line, msg),
-            SemanticErrorType::MissingParameters(msg) => format!("Missing parameters on line {}: {}", self.line, msg),
-            SemanticErrorType::InvalidArraySize(msg) => format!("Invalid array size on line {}: {}", self.line, msg),
+            SemanticErrorType::DuplicateDeclaration(id, scope_type) => format!("Duplicate declaration on line {}, column {}: Symbol {} already declared in {} scope", self.span.line, self.span.column, id, scope_type.to_log_message()),
+            SemanticErrorType::UndefinedVariable(id) => format!("Undefined variable on line {}, column {}: {}", self.span.line, self.span.column, id),
+            SemanticErrorType::TypeMismatch(types_comp) => format!("Type mismatch on line {}, column {}: {}", self.span.line, self.span.column, types_comp),
+            SemanticErrorType::InvalidType(msg) => format!("Invalid type on line {}, column {}: {}", self.span.line, self.span.column, msg),
+            SemanticErrorType::MissingParameters(msg) => format!("Missing parameters on line {}, column {}: {}", self.span.line, self.span.column, msg),
+            SemanticErrorType::InvalidArraySize(msg) => format!("Invalid array size on line {}, column {}: {}", self.span.line, self.span.column, msg),
+            SemanticErrorType::MissingReturn(id) => format!("Missing return on line {}, column {}: {}", self.span.line, self.span.column, id),
+            SemanticErrorType::DivisionByZero(op) => format!("Division by zero on line {}, column {}: {}", self.span.line, self.span.column, op),
+            SemanticErrorType::ArithmeticOverflow(operation) => format!("Arithmetic overflow on line {}, column {}: {}", self.span.line, self.span.column, operation),
+            SemanticErrorType::IntLiteralOutOfRange(value) => format!("Int literal out of range on line {}, column {}: {}", self.span.line, self.span.column, value),
+            SemanticErrorType::IndexOutOfBounds(msg) => format!("Index out of bounds on line {}, column {}: {}", self.span.line, self.span.column, msg),
+            SemanticErrorType::FrameTooLarge(id) => format!("Frame too large on line {}, column {}: {}", self.span.line, self.span.column, id),
+            SemanticErrorType::DeniedLint(warning) => warning.to_log_message(),
         }
     }
 }
 
+impl ToDiagnostic for SemanticError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = match &self.error_type {
+            SemanticErrorType::UndefinedVariable(id) => Diagnostic::error(
+                "E0201",
+                format!("cannot find `{}` in this scope", id),
+                Some(self.span),
+            ).with_label(String::from("not found in this scope")),
+            SemanticErrorType::TypeMismatch(types_comp) => Diagnostic::error(
+                "E0202",
+                format!("mismatched types: {}", types_comp),
+                Some(self.span),
+            ),
+            SemanticErrorType::InvalidType(msg) => Diagnostic::error(
+                "E0203",
+                format!("invalid type: {}", msg),
+                Some(self.span),
+            ),
+            SemanticErrorType::DuplicateDeclaration(id, scope_type) => Diagnostic::error(
+                "E0204",
+                format!("`{}` is already declared in the {}", id, scope_type.get_description()),
+                Some(self.span),
+            ).with_label(String::from("redeclared here")),
+            SemanticErrorType::MissingParameters(msg) => Diagnostic::error(
+                "E0205",
+                format!("wrong number of arguments: {}", msg),
+                Some(self.span),
+            ),
+            SemanticErrorType::InvalidArraySize(msg) => Diagnostic::error(
+                "E0206",
+                format!("invalid array size: {}", msg),
+                Some(self.span),
+            ),
+            SemanticErrorType::MissingReturn(id) => Diagnostic::error(
+                "E0207",
+                format!("function `{}` may end without returning a value", id),
+                Some(self.span),
+            ).with_label(String::from("not every path through this function ends with a `return`")),
+            SemanticErrorType::DivisionByZero(op) => Diagnostic::error(
+                "E0208",
+                String::from("this expression always divides by zero"),
+                Some(self.span),
+            ).with_label(String::from(match op {
+                Token::Omod => "attempt to calculate the remainder with a divisor of zero",
+                _ => "attempt to divide by zero",
+            })),
+            SemanticErrorType::ArithmeticOverflow(operation) => Diagnostic::error(
+                "E0209",
+                format!("this expression always overflows: `{}` doesn't fit in an int", operation),
+                Some(self.span),
+            ).with_label(String::from("attempt to compute a value out of the range of an int")),
+            SemanticErrorType::IntLiteralOutOfRange(value) => Diagnostic::error(
+                "E0210",
+                format!("int literal `{}` is out of range", value),
+                Some(self.span),
+            ).with_label(String::from("doesn't fit in an int")),
+            SemanticErrorType::IndexOutOfBounds(msg) => Diagnostic::error(
+                "E0211",
+                format!("index out of bounds: {}", msg),
+                Some(self.span),
+            ).with_label(String::from("index out of bounds")),
+            SemanticErrorType::FrameTooLarge(id) => Diagnostic::error(
+                "E0212",
+                format!("the variables of `{}` don't fit in its frame", id),
+                Some(self.span),
+            ).with_label(String::from("makes the frame too large"))
+                .with_help(format!("a frame can take at most {} bytes of variables, and {} bytes with its temporaries", MAX_FRAME_SIZE, u32::MAX)),
+            SemanticErrorType::DeniedLint(warning) => {
+                let mut diagnostic = warning.to_diagnostic();
+                diagnostic.severity = Severity::Error;
+                diagnostic.with_note(format!("the `{}` lint is denied", warning.get_lint()), None)
+            },
+        };
+        diagnostic.notes.extend(self.notes.iter().cloned());
+        diagnostic.help.extend(self.help.iter().cloned());
+        diagnostic
+    }
+}
+
 impl Loggable for Box<[SemanticError]> {
     fn to_log_message(&self) -> String {
         let mut msg = String::new();
@@ -69,9 +209,219 @@ impl Loggable for Box<[SemanticError]> {
     }
 }
 
+#[derive(Debug)]
+pub enum SemanticWarningType {
+    UnreachableCode,
+    UninitializedVariable(String),
+    // Assigned on some paths only
+    MaybeUninitializedVariable(String),
+    UnusedVariable(String),
+    // Assigned, but never read
+    UnreadVariable(String),
+    UnusedParameter(String),
+    UnusedFunction(String),
+    // The name, and the kind of scope of the declaration it shadows
+    Shadowing(String, ScopeType),
+    // The keyword of the statement, and the value of its condition
+    ConstantCondition(String, bool),
+    // The array indexed with a value that isn't constant
+    UncheckedArrayIndex(String),
+}
+
+/**
+ * Something suspicious about a program that doesn't stop it from being compiled.
+ */
+#[derive(Debug)]
+pub struct SemanticWarning {
+    warning_type: SemanticWarningType,
+    span: Span,
+    notes: Vec<Note>,
+    help: Vec<String>,
+}
+
+impl SemanticWarning {
+    pub fn new(warning_type: SemanticWarningType, span: Span) -> Self {
+        Self {
+            warning_type,
+            span,
+            notes: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    /**
+     * Attach a note pointing at a related part of the source.
+     */
+    pub fn with_note(mut self, message: &str, span: Span) -> Self {
+        self.notes.push(Note { message: message.to_string(), span: Some(span) });
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.help.push(help);
+        self
+    }
+
+    pub fn get_warning_type(&self) -> &SemanticWarningType {
+        &self.warning_type
+    }
+
+    pub fn get_lint(&self) -> Lint {
+        match &self.warning_type {
+            SemanticWarningType::UnreachableCode => Lint::UnreachableCode,
+            SemanticWarningType::UninitializedVariable(_)
+                | SemanticWarningType::MaybeUninitializedVariable(_) => Lint::UninitializedVariable,
+            SemanticWarningType::UnusedVariable(_)
+                | SemanticWarningType::UnreadVariable(_) => Lint::UnusedVariable,
+            SemanticWarningType::UnusedParameter(_) => Lint::UnusedParameter,
+            SemanticWarningType::UnusedFunction(_) => Lint::UnusedFunction,
+            SemanticWarningType::Shadowing(..) => Lint::Shadowing,
+            SemanticWarningType::ConstantCondition(..) => Lint::ConstantCondition,
+            SemanticWarningType::UncheckedArrayIndex(_) => Lint::UncheckedArrayIndex,
+        }
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl Loggable for SemanticWarning {
+    fn to_log_message(&self) -> String {
+        match &self.warning_type {
+            SemanticWarningType::UnreachableCode => format!("Unreachable code on line {}, column {}", self.span.line, self.span.column),
+            SemanticWarningType::UninitializedVariable(id)
+                | SemanticWarningType::MaybeUninitializedVariable(id) => format!("Uninitialized variable on line {}, column {}: {}", self.span.line, self.span.column, id),
+            SemanticWarningType::UnusedVariable(id)
+                | SemanticWarningType::UnreadVariable(id) => format!("Unused variable on line {}, column {}: {}", self.span.line, self.span.column, id),
+            SemanticWarningType::UnusedParameter(id) => format!("Unused parameter on line {}, column {}: {}", self.span.line, self.span.column, id),
+            SemanticWarningType::UnusedFunction(id) => format!("Unused function on line {}, column {}: {}", self.span.line, self.span.column, id),
+            SemanticWarningType::Shadowing(id, scope_type) => format!("Shadowing on line {}, column {}: {} shadows a declaration in {} scope", self.span.line, self.span.column, id, scope_type.to_log_message()),
+            SemanticWarningType::ConstantCondition(keyword, value) => format!("Constant condition on line {}, column {}: {} condition is always {}", self.span.line, self.span.column, keyword, value),
+            SemanticWarningType::UncheckedArrayIndex(id) => format!("Unchecked array index on line {}, column {}: {}", self.span.line, self.span.column, id),
+        }
+    }
+}
+
+impl ToDiagnostic for SemanticWarning {
+    fn to_diagnostic(&self) -> Diagnostic {
+        let (message, label) = match &self.warning_type {
+            SemanticWarningType::UnreachableCode => (String::from("unreachable statement"), "unreachable statement"),
+            SemanticWarningType::UninitializedVariable(id) => (format!("`{}` is read before being assigned a value", id), "read before being assigned"),
+            SemanticWarningType::MaybeUninitializedVariable(id) => (format!("`{}` may be read before being assigned a value", id), "not assigned on every path to here"),
+            SemanticWarningType::UnusedVariable(id) => (format!("unused variable `{}`", id), "never used"),
+            SemanticWarningType::UnreadVariable(id) => (format!("variable `{}` is assigned to, but never read", id), "never read"),
+            SemanticWarningType::UnusedParameter(id) => (format!("unused parameter `{}`", id), "never used"),
+            SemanticWarningType::UnusedFunction(id) => (format!("function `{}` is never called", id), "never called"),
+            SemanticWarningType::Shadowing(id, scope_type) => (format!("`{}` shadows a declaration in the {}", id, scope_type.get_description()), "shadows an outer declaration"),
+            SemanticWarningType::ConstantCondition(keyword, true) => (format!("this `{}` condition is always true", keyword), "always true"),
+            SemanticWarningType::ConstantCondition(keyword, false) => (format!("this `{}` condition is always false", keyword), "always false"),
+            SemanticWarningType::UncheckedArrayIndex(id) => (format!("cannot prove this index of `{}` is in bounds", id), "not a constant"),
+        };
+        let mut diagnostic = Diagnostic::new(Severity::Warning, self.get_lint().get_code(), message, Some(self.span))
+            .with_label(String::from(label));
+        diagnostic.notes.extend(self.notes.iter().cloned());
+        diagnostic.help.extend(self.help.iter().cloned());
+        diagnostic
+    }
+}
+
+impl Loggable for Vec<SemanticWarning> {
+    fn to_log_message(&self) -> String {
+        let mut msg = String::new();
+        for warning in self.iter() {
+            msg.push_str(&warning.to_log_message());
+            msg.push('\n');
+        }
+        msg
+    }
+}
+
+/**
+ * Make the value of a tree usable where a value of `expected_type` is, promoting an int to a double.
+ * Returns the type of the value if it can't be, e.g. a double where an int is expected.
+ */
+fn promote_tree(tree: &mut StatementTree, expected_type: &BasicType) -> Result<(), BasicType> {
+    let start = match tree.start {
+        Some(start) => start,
+        None => return Ok(()),
+    };
+    let node_type = match &tree.nodes[start].node_type {
+        Some(node_type) => node_type.clone(),
+        None => return Ok(()),
+    };
+
+    if node_type == *expected_type {
+        Ok(())
+    } else if node_type.is_promotable_to(expected_type) {
+        tree.add_conversion(start, None, expected_type.clone());
+        Ok(())
+    } else {
+        Err(node_type)
+    }
+}
+
+/**
+ * Suggest an explicit conversion when a double is used where an int is expected.
+ */
+fn get_conversion_help(found_type: &BasicType, expected_type: &BasicType) -> Option<String> {
+    if *found_type == BasicType::Double && *expected_type == BasicType::Int {
+        return Some(String::from("use `int(...)` to convert the double to an int, dropping its fractional part"));
+    }
+    None
+}
+
+/**
+ * Evaluate the size of an array declaration, which must be a positive constant int.
+ */
+fn get_array_size(tree: &StatementTree, span: Span) -> Result<u32, SemanticError> {
+    let mut evaluator = ConstantEvaluator::new();
+    let size = evaluator.evaluate(tree);
+    if let Some(error) = evaluator.take_errors().into_iter().next() {
+        return Err(error);
+    }
+
+    let message = match size {
+        Some(ConstantValue::Int(size)) if size > 0 => return Ok(size as u32),
+        Some(ConstantValue::Int(_)) => "Array size must be a positive value",
+        Some(ConstantValue::Double(_)) => "Array size must be an int, not a double",
+        None => "Array size must be a constant value",
+    };
+    Err(SemanticError::new(SemanticErrorType::InvalidArraySize(message.to_string()), span))
+}
+
+/**
+ * Check an index of an array against its size when the index is constant.
+ * Returns a warning if it isn't, as it can only be checked when the program runs.
+ */
+fn check_array_index(array: &SymbolDecl, index: &StatementTree, span: Span) -> Result<Option<SemanticWarning>, SemanticError> {
+    let size = match &array.var_type {
+        BasicType::Array(_, size) => *size,
+        _ => return Ok(None),
+    };
+
+    let note = format!("`{}` declared here with {} element{}", array.name, size, if size == 1 { "" } else { "s" });
+
+    // Errors in the index are reported with the rest of the program's constants
+    match ConstantEvaluator::new().evaluate(index) {
+        Some(ConstantValue::Int(value)) if value < 0 || value as u32 >= size => Err(
+            SemanticError::new(SemanticErrorType::IndexOutOfBounds(
+                format!("the size of {} is {} but the index is {}", array.name, size, value)
+            ), span)
+                .with_note(&note, array.span)
+        ),
+        Some(_) => Ok(None),
+        None => Ok(Some(
+            SemanticWarning::new(SemanticWarningType::UncheckedArrayIndex(array.name.clone()), span)
+                .with_note(&note, array.span)
+        )),
+    }
+}
+
 #[derive(Debug)]
 struct FuncCheck {
     func_id: DeclId,
+    span: Span,
     param_index: usize,
     params: Vec<StatementTree>,
 }
@@ -95,10 +445,11 @@ impl PartialConditionalStatement {
     }
 }
 
-pub struct SemanticInfo {
+pub(crate) struct SemanticInfo {
     pub symbol_table: SymbolTable,
     curr_scope: usize,
     curr_id: Option<String>,
+    curr_id_span: Span,
     curr_type: Option<BasicType>,
     curr_func: Option<SymbolDecl>,
     func_scope: usize,
@@ -110,15 +461,23 @@ pub struct SemanticInfo {
     curr_var: Option<SymbolDecl>,
     curr_conditional_statements: Vec<PartialConditionalStatement>,
     curr_builtin_func: Option<BuiltInFuncType>,
+    curr_builtin_span: Span,
     curr_array_index: Option<StatementTree>,
+    // The frames the variables are laid out in, to catch those that don't fit before the code generation
+    target: TargetLayout,
+    main_frame: FrameLayout,
+    func_frame: FrameLayout,
+    // Found while parsing, before the lint levels are applied
+    pub warnings: Vec<SemanticWarning>,
 }
 
 impl SemanticInfo {
-    pub fn new() -> Self {
+    pub fn new(target: &TargetLayout) -> Self {
         Self {
             symbol_table: SymbolTable::new(),
             curr_scope: GLOBAL_SCOPE,
             curr_id: None,
+            curr_id_span: Span::default(),
             curr_type: None,
             curr_func: None,
             func_scope: 0,
@@ -130,11 +489,16 @@ impl SemanticInfo {
             curr_var: None,
             curr_conditional_statements: Vec::new(),
             curr_builtin_func: None,
+            curr_builtin_span: Span::default(),
             curr_array_index: None,
+            target: target.clone(),
+            main_frame: FrameLayout::new(String::from("main")),
+            func_frame: FrameLayout::new(String::from("main")),
+            warnings: Vec::new(),
         }
     }
 
-    pub fn perform_action(&mut self, action: &SemanticAction, prev_terminal: &Option<Token>) -> Result<(), SemanticErrorType> {
+    pub fn perform_action(&mut self, action: &SemanticAction, prev_terminal: &Option<ParsedToken>) -> Result<(), SemanticError> {
         match action {
             SemanticAction::SetId => {
                 self.set_id(prev_terminal)?;
@@ -187,7 +551,7 @@ impl SemanticInfo {
                 self.add_operator(prev_terminal);
             },
             SemanticAction::CheckType => {
-                self.check_type()?;
+                self.check_type(prev_terminal)?;
             },
             SemanticAction::CheckVarType => {
                 self.check_var_type()?;
@@ -196,7 +560,7 @@ impl SemanticInfo {
                 self.add_func_check()?;
             },
             SemanticAction::PopFuncCheck => {
-                self.pop_func_check()?;
+                self.pop_func_check(prev_terminal)?;
             },
             SemanticAction::CheckParamType => {
                 self.check_param_type()?;
@@ -224,22 +588,23 @@ impl SemanticInfo {
             },
             SemanticAction::StartReturn => {
                 self.check_return_type = true;
-                self.curr_builtin_func = Some(BuiltInFuncType::Return);
+                self.start_builtin_func(BuiltInFuncType::Return, prev_terminal);
             },
             SemanticAction::StartPrint => {
-                self.curr_builtin_func = Some(BuiltInFuncType::Print);
+                self.start_builtin_func(BuiltInFuncType::Print, prev_terminal);
             },
         }
         Ok(())
     }
 
-    fn set_id(&mut self, prev_terminal: &Option<Token>) -> Result<(), SemanticErrorType> {
-        if let Some(Token::Identifier(id)) = prev_terminal {
+    fn set_id(&mut self, prev_terminal: &Option<ParsedToken>) -> Result<(), SemanticError> {
+        if let Some(ParsedToken { token: Token::Identifier(id), span }) = prev_terminal {
             if let Some(tree_info) = self.type_trees.last_mut() {
                 let symbol_found = self.symbol_table.find_decl(id, self.curr_scope);
                 if let Some(symbol) = symbol_found {
                     let new_node = tree_info.tree.add_node(
                         StatementSymbol::Decl(symbol.get_id()),
+                        *span,
                         tree_info.curr_node
                     );
 
@@ -254,15 +619,16 @@ impl SemanticInfo {
                     }
                 } else {
                     self.type_trees.pop();
-                    return Err(SemanticErrorType::UndefinedVariable(id.clone()));
+                    return Err(SemanticError::new(SemanticErrorType::UndefinedVariable(id.clone()), *span));
                 }
             } else {
                 self.curr_id = Some(id.clone());
+                self.curr_id_span = *span;
                 if self.build_assignment && self.curr_var.is_none() {
                     if let Some(decl) = self.symbol_table.find_decl(id, self.curr_scope) {
                         self.curr_var = Some(decl.clone());
                     } else {
-                        return Err(SemanticErrorType::UndefinedVariable(id.clone()));
+                        return Err(SemanticError::new(SemanticErrorType::UndefinedVariable(id.clone()), *span));
                     }
                 }
             }
@@ -270,9 +636,9 @@ impl SemanticInfo {
         Ok(())
     }
 
-    fn set_type(&mut self, prev_terminal: &Option<Token>) {
-        if let Some(token) = prev_terminal {
-            match token {
+    fn set_type(&mut self, prev_terminal: &Option<ParsedToken>) {
+        if let Some(parsed_token) = prev_terminal {
+            match parsed_token.token {
                 Token::Kint => self.curr_type = Some(BasicType::Int),
                 Token::Kdouble => self.curr_type = Some(BasicType::Double),
                 _ => {}
@@ -280,35 +646,48 @@ impl SemanticInfo {
         }
     }
 
-    fn set_array(&mut self) -> Result<(), SemanticErrorType> {
+    fn set_array(&mut self) -> Result<(), SemanticError> {
         if let Some(index_tree_info) = self.type_trees.pop() {
+            let index_span = index_tree_info.tree.get_tree_span().unwrap_or(self.curr_id_span);
+
             // Check that index type is Int
             if let Some(start) = index_tree_info.tree.start {
                 if let Some(node_type) = &index_tree_info.tree.nodes[start].node_type {
                     if *node_type != BasicType::Int {
-                        return Err(SemanticErrorType::TypeMismatch(
-                            format!("Array index type must be int, found {}", node_type)
+                        return Err(SemanticError::new(
+                            SemanticErrorType::TypeMismatch(
+                                format!("Array index type must be int, found {}", node_type)
+                            ),
+                            index_span,
                         ));
                     }
                 }
             }
 
-            if self.type_trees.len() > 0 {
+            if !self.type_trees.is_empty() {
                 // Using array value in expression
-                let mut err: Option<Result<(), SemanticErrorType>> = None;
+                let mut err: Option<SemanticError> = None;
+                // The access is still built when its index is out of bounds, for the expression to be checked
+                let mut index_check: Result<Option<SemanticWarning>, SemanticError> = Ok(None);
                 if let Some(arr_tree_info) = self.type_trees.last_mut() {
-                    if arr_tree_info.tree.nodes.len() > 0 {
+                    if !arr_tree_info.tree.nodes.is_empty() {
                         let curr_node = arr_tree_info.tree.nodes.len() - 1;
                         if let StatementSymbol::Decl(id) = &arr_tree_info.tree.nodes[curr_node].symbol {
                             if let Some(decl) = self.symbol_table.find_decl_by_id(id) {
+                                let arr_span = arr_tree_info.tree.nodes[curr_node].span;
                                 if let BasicType::Array(inner_type, _) = &decl.var_type {
+                                    index_check = check_array_index(decl, &index_tree_info.tree, index_span);
                                     arr_tree_info.tree.nodes[curr_node].symbol =
                                         StatementSymbol::ArrayAccess(id.clone(), index_tree_info.tree);
                                     arr_tree_info.tree.nodes[curr_node].node_type = Some(*inner_type.clone());
+                                    arr_tree_info.tree.nodes[curr_node].span = arr_span.to(&index_span);
                                 } else {
-                                    err = Some(Err(SemanticErrorType::InvalidType(
-                                        format!("Variable {} is not an array", id.0.clone())
-                                    )));
+                                    err = Some(SemanticError::new(
+                                        SemanticErrorType::InvalidType(
+                                            format!("Variable {} is not an array", id.0.clone())
+                                        ),
+                                        arr_span,
+                                    ));
                                 }
                             }
                         }
@@ -317,53 +696,57 @@ impl SemanticInfo {
 
                 if let Some(error) = err {
                     self.type_trees.pop();
-                    return error;
+                    return Err(error);
                 }
-            } else {
-                if self.build_assignment {
-                    // Assigning value to array
-                    if let Some(var) = &self.curr_var {
-                        if let BasicType::Array(arr_type, _) = &var.var_type {
-                            self.curr_var = Some(SymbolDecl::new(
-                                var.name.clone(),
-                                *arr_type.clone(),
-                                var.scope,
-                            ));
-                            self.curr_array_index = Some(index_tree_info.tree);
-                        } else {
-                            return Err(SemanticErrorType::InvalidType(
+                self.warnings.extend(index_check?);
+            } else if self.build_assignment {
+                // Assigning value to array
+                if let Some(var) = &self.curr_var {
+                    if let BasicType::Array(arr_type, _) = &var.var_type {
+                        let index_check = check_array_index(var, &index_tree_info.tree, index_span);
+                        self.curr_var = Some(SymbolDecl::new(
+                            var.name.clone(),
+                            *arr_type.clone(),
+                            var.scope,
+                            var.span,
+                        ));
+                        self.curr_array_index = Some(index_tree_info.tree);
+                        self.warnings.extend(index_check?);
+                    } else {
+                        return Err(SemanticError::new(
+                            SemanticErrorType::InvalidType(
                                 "Variable is not an array".to_string()
-                            ));
-                        }
-                    }
-                } else {
-                    // Defining array
-                    if let Some(basic_type) = &self.curr_type {
-                        let size = index_tree_info.tree
-                                .calculate_array_size()
-                                .map_err(|e| SemanticErrorType::InvalidArraySize(e))?;
-                        self.curr_type = Some(
-                            BasicType::Array(
-                                Box::new(basic_type.clone()),
-                                size,
-                            )
-                        );
+                            ),
+                            self.curr_id_span,
+                        ));
                     }
                 }
+            } else {
+                // Defining array
+                if let Some(basic_type) = &self.curr_type {
+                    let size = get_array_size(&index_tree_info.tree, index_span)?;
+                    self.curr_type = Some(
+                        BasicType::Array(
+                            Box::new(basic_type.clone()),
+                            size,
+                        )
+                    );
+                }
             }
         }
         Ok(())
     }
 
-    fn set_literal(&mut self, prev_terminal: &Option<Token>) {
-        if let Some(token) = prev_terminal {
+    fn set_literal(&mut self, prev_terminal: &Option<ParsedToken>) {
+        if let Some(parsed_token) = prev_terminal {
             if let Some(tree_info) = self.type_trees.last_mut() {
                 let new_node = tree_info.tree.add_node(
-                    StatementSymbol::Literal(token.clone()),
+                    StatementSymbol::Literal(parsed_token.token.clone()),
+                    parsed_token.span,
                     tree_info.curr_node
                 );
 
-                match token {
+                match parsed_token.token {
                     Token::Tint(_) => tree_info.tree.nodes[new_node].node_type = Some(BasicType::Int),
                     Token::Tdouble(_) => tree_info.tree.nodes[new_node].node_type = Some(BasicType::Double),
                     _ => {}
@@ -380,28 +763,27 @@ impl SemanticInfo {
         }
     }
 
-    fn add_var_decl(&mut self) -> Result<(), SemanticErrorType> {
-        if let Some(id) = &self.curr_id {
-            if let Some(basic_type) = &self.curr_type {
-                // Add variable to symbol table
-                self.symbol_table.add_declaration(SymbolDecl::new(
-                    id.clone(),
-                    basic_type.clone(),
-                    self.curr_scope,
-                ))?;
+    fn add_var_decl(&mut self) -> Result<(), SemanticError> {
+        if let (Some(id), Some(basic_type)) = (self.curr_id.clone(), self.curr_type.clone()) {
+            // Add variable to symbol table
+            let decl = SymbolDecl::new(id, basic_type.clone(), self.curr_scope, self.curr_id_span);
+            self.check_shadowing(&decl);
+            // Declared even if it doesn't fit, so that its uses aren't reported as well
+            let frame_check = self.add_to_frame(&decl);
+            self.symbol_table.add_declaration(decl)?;
 
-                // Go back to regular type after array assignment
-                if let BasicType::Array(old_type, _) = basic_type {
-                    self.curr_type = Some(*old_type.clone());
-                }
+            // Go back to regular type after array assignment
+            if let BasicType::Array(old_type, _) = basic_type {
+                self.curr_type = Some(*old_type);
             }
+            return frame_check;
         }
         Ok(())
     }
 
     fn new_scope(&mut self) {
         self.curr_scope = self.symbol_table.add_scope(ScopeType::Local, self.curr_scope);
-        if self.curr_conditional_statements.len() > 0 {
+        if !self.curr_conditional_statements.is_empty() {
             if let Some(cond_info) = self.curr_conditional_statements.last_mut() {
                 if cond_info.body_scope.is_none() {
                     cond_info.body_scope = Some(self.curr_scope);
@@ -414,45 +796,83 @@ impl SemanticInfo {
         self.curr_scope = self.symbol_table.get_parent_scope(self.curr_scope);
     }
 
-    fn set_func(&mut self, prev_terminal: &Option<Token>) {
-        if let Some(Token::Identifier(id)) = prev_terminal {
+    fn set_func(&mut self, prev_terminal: &Option<ParsedToken>) {
+        if let Some(ParsedToken { token: Token::Identifier(id), span }) = prev_terminal {
             if let Some(basic_type) = &self.curr_type {
                 // Create new scope for function
                 self.func_scope = self.symbol_table.add_scope(ScopeType::Function, self.curr_scope);
 
                 // Set current function
+                self.func_frame = FrameLayout::new(id.clone());
                 self.curr_func = Some(SymbolDecl::new_func(
                     id.clone(),
                     basic_type.clone(),
                     self.func_scope,
                     self.curr_scope,
+                    *span,
                 ));
             }
         }
     }
 
-    fn add_param(&mut self) -> Result<(), SemanticErrorType> {
-        if let Some(func) = &mut self.curr_func {
-            if let SymbolDecl{ var_type: BasicType::Function(func_info), .. } = func {
-                if let Some(basic_type) = &self.curr_type {
-                    if let Some(id) = &self.curr_id {
-                        // Add parameter type to current function
-                        func_info.param_types.push(basic_type.clone());
-
-                        // Add parameter to function scope
-                        self.symbol_table.add_parameter(SymbolDecl::new(
-                            id.clone(),
-                            basic_type.clone(),
-                            self.func_scope,
-                        ))?;
-                    }
+    fn add_param(&mut self) -> Result<(), SemanticError> {
+        if let Some(SymbolDecl{ var_type: BasicType::Function(func_info), .. }) = &mut self.curr_func {
+            if let Some(basic_type) = &self.curr_type {
+                if let Some(id) = &self.curr_id {
+                    // Add parameter type to current function
+                    func_info.param_types.push(basic_type.clone());
+
+                    // Add parameter to function scope
+                    let decl = SymbolDecl::new(id.clone(), basic_type.clone(), self.func_scope, self.curr_id_span);
+                    self.check_shadowing(&decl);
+                    let frame_check = self.add_to_frame(&decl);
+                    self.symbol_table.add_parameter(decl)?;
+                    return frame_check;
                 }
             }
         }
         Ok(())
     }
 
-    fn add_func_decl(&mut self) -> Result<(), SemanticErrorType> {
+    /**
+     * Lay out a variable in the frame of its function (or of the main program), as the code generation will.
+     * Fails if the frame grows past MAX_FRAME_SIZE, e.g. for a huge array.
+     */
+    fn add_to_frame(&mut self, decl: &SymbolDecl) -> Result<(), SemanticError> {
+        let frame = match self.curr_func.is_some() || self.is_in_function(decl.scope) {
+            true => &mut self.func_frame,
+            false => &mut self.main_frame,
+        };
+        match frame.add_slot_within(decl.to_var_name(), decl.name.clone(), SlotKind::Local, decl.var_type.clone(), &self.target, MAX_FRAME_SIZE) {
+            Some(_) => Ok(()),
+            None => Err(SemanticError::new(SemanticErrorType::FrameTooLarge(frame.function.clone()), decl.span)),
+        }
+    }
+
+    fn is_in_function(&self, mut scope: usize) -> bool {
+        while scope != GLOBAL_SCOPE {
+            if *self.symbol_table.get_scope(scope).get_scope_type() == ScopeType::Function {
+                return true;
+            }
+            scope = self.symbol_table.get_parent_scope(scope);
+        }
+        false
+    }
+
+    /**
+     * Warn about a declaration hiding one of a parent scope, e.g. a local variable named after a function.
+     */
+    fn check_shadowing(&mut self, decl: &SymbolDecl) {
+        if let Some(shadowed) = self.symbol_table.find_shadowed_decl(&decl.name, decl.scope) {
+            let scope_type = *self.symbol_table.get_scope(shadowed.scope).get_scope_type();
+            self.warnings.push(
+                SemanticWarning::new(SemanticWarningType::Shadowing(decl.name.clone(), scope_type), decl.span)
+                    .with_note(&format!("`{}` declared here, in the {}", shadowed.name, scope_type.get_description()), shadowed.span)
+            );
+        }
+    }
+
+    fn add_func_decl(&mut self) -> Result<(), SemanticError> {
         if let Some(func) = &self.curr_func {
             // Add function to symbol table
             self.symbol_table.add_declaration(func.clone())?;
@@ -468,73 +888,95 @@ impl SemanticInfo {
         Ok(())
     }
 
-    fn add_type_tree(&mut self) -> Result<(), SemanticErrorType> {
+    fn add_type_tree(&mut self) -> Result<(), SemanticError> {
         if let Some(func_check) = self.func_checks.last_mut() {
             if let Some(tree_info) = self.type_trees.pop() {
                 func_check.params.push(tree_info.tree);
             }
-        } else {
-            if let Some(tree_info) = self.type_trees.pop() {
-                if let Some(builtin_type) = &self.curr_builtin_func {
-                    match builtin_type {
-                        BuiltInFuncType::Return => {
-                            self.check_return(tree_info)?;
-                        },
-                        BuiltInFuncType::Print => {
-                            self.check_print(tree_info);
-                        }
+        } else if let Some(tree_info) = self.type_trees.pop() {
+            if let Some(builtin_type) = &self.curr_builtin_func {
+                match builtin_type {
+                    BuiltInFuncType::Return => {
+                        self.check_return(tree_info)?;
+                    },
+                    BuiltInFuncType::Print => {
+                        self.check_print(tree_info);
                     }
-                    self.curr_builtin_func = None;
-                } else {
-                    self.symbol_table.add_type_tree(tree_info.tree, self.curr_scope);
                 }
+                self.curr_builtin_func = None;
+            } else {
+                self.symbol_table.add_type_tree(tree_info.tree, self.curr_scope);
             }
         }
         Ok(())
     }
 
-    fn check_return(&mut self, tree_info: StatementTreeInfo) -> Result<(), SemanticErrorType> {
-        if let Some(func_return_type) = &self.func_return_type {
-            if let Some(node) = tree_info.tree.nodes.last() {
-                if let Some(node_type) = &node.node_type {
-                    if let BasicType::Function(func_info) = &func_return_type {
-                        if *func_info.return_type != *node_type {
-                            return Err(SemanticErrorType::TypeMismatch(
-                                format!("Wrong return type, {} != {}", *func_info.return_type, node_type)
-                            ));
-                        }
+    fn start_builtin_func(&mut self, func_type: BuiltInFuncType, prev_terminal: &Option<ParsedToken>) {
+        self.curr_builtin_func = Some(func_type);
+        if let Some(parsed_token) = prev_terminal {
+            self.curr_builtin_span = parsed_token.span;
+        }
+    }
 
-                        self.symbol_table.add_builtin_func(
-                            BuiltInFunc {
-                                func_type: BuiltInFuncType::Return,
-                                statement: tree_info.tree,
-                            },
-                            self.curr_scope,
-                        );
-                    }
+    /**
+     * Get the span of a `print` or `return` statement, from its keyword to the end of its expression.
+     */
+    fn get_builtin_span(&self, tree: &StatementTree) -> Span {
+        match tree.get_tree_span() {
+            Some(tree_span) => self.curr_builtin_span.to(&tree_span),
+            None => self.curr_builtin_span,
+        }
+    }
+
+    fn check_return(&mut self, mut tree_info: StatementTreeInfo) -> Result<(), SemanticError> {
+        let span = tree_info.tree.get_tree_span().unwrap_or_default();
+        if let Some(func_return_type) = &self.func_return_type {
+            if let BasicType::Function(func_info) = &func_return_type {
+                if let Err(node_type) = promote_tree(&mut tree_info.tree, &func_info.return_type) {
+                    return Err(SemanticError::new(
+                        SemanticErrorType::TypeMismatch(
+                            format!("Wrong return type, {} != {}", *func_info.return_type, node_type)
+                        ),
+                        span,
+                    ).with_help(get_conversion_help(&node_type, &func_info.return_type)));
                 }
+
+                let statement_span = self.get_builtin_span(&tree_info.tree);
+                self.symbol_table.add_builtin_func(
+                    BuiltInFunc {
+                        func_type: BuiltInFuncType::Return,
+                        statement: tree_info.tree,
+                        span: statement_span,
+                    },
+                    self.curr_scope,
+                );
             }
         } else {
-            return Err(SemanticErrorType::InvalidType(
-                "Function return type not found".to_string()
+            return Err(SemanticError::new(
+                SemanticErrorType::InvalidType(
+                    "Function return type not found".to_string()
+                ),
+                span,
             ));
         }
         Ok(())
     }
 
     fn check_print(&mut self, tree_info: StatementTreeInfo) {
+        let span = self.get_builtin_span(&tree_info.tree);
         self.symbol_table.add_builtin_func(
             BuiltInFunc {
                 func_type: BuiltInFuncType::Print,
                 statement: tree_info.tree,
+                span,
             },
             self.curr_scope,
         );
     }
 
-    fn split_trees(&mut self, prev_terminal: &Option<Token>) {
+    fn split_trees(&mut self, prev_terminal: &Option<ParsedToken>) {
         if let Some(tree_info) = self.type_trees.last_mut() {
-            if let Some(token) = prev_terminal {
+            if let Some(parsed_token) = prev_terminal {
                 if let Some(mut node) = tree_info.curr_node {
                     if let StatementSymbol::SingleChildOperator(_) = tree_info.tree.nodes[node].symbol {
                         // If current node is a single child operator, split the tree on the left child
@@ -545,45 +987,46 @@ impl SemanticInfo {
                     }
 
                     tree_info.curr_node = tree_info.tree.split_tree(
-                        StatementSymbol::Operator(token.clone()),
+                        StatementSymbol::Operator(parsed_token.token.clone()),
+                        parsed_token.span,
                         node
                     ).into();
-                } else {
-                    if let Some(start_node) = tree_info.tree.start {
-                        // Create new node as start node
-                        tree_info.curr_node = tree_info.tree.add_node(
-                            StatementSymbol::Operator(token.clone()),
-                            None
-                        ).into();
-                        
-                        // Add prev start node as left child
-                        tree_info.tree.nodes[tree_info.curr_node.unwrap()].left = Some(start_node);
-                    }
+                } else if let Some(start_node) = tree_info.tree.start {
+                    // Create new node as start node
+                    tree_info.curr_node = tree_info.tree.add_node(
+                        StatementSymbol::Operator(parsed_token.token.clone()),
+                        parsed_token.span,
+                        None
+                    ).into();
+
+                    // Add prev start node as left child
+                    tree_info.tree.nodes[tree_info.curr_node.unwrap()].left = Some(start_node);
                 }
             }
         }
     }
 
-    fn add_operator(&mut self, prev_terminal: &Option<Token>) {
+    fn add_operator(&mut self, prev_terminal: &Option<ParsedToken>) {
         if let Some(tree_info) = self.type_trees.last_mut() {
-            if let Some(token) = prev_terminal {
+            if let Some(parsed_token) = prev_terminal {
                 tree_info.curr_node = tree_info.tree.add_node(
-                    StatementSymbol::SingleChildOperator(token.clone()),
+                    StatementSymbol::SingleChildOperator(parsed_token.token.clone()),
+                    parsed_token.span,
                     tree_info.curr_node
                 ).into();
             }
         }
     }
 
-    fn check_type(&mut self) -> Result<(), SemanticErrorType> {
+    fn check_type(&mut self, prev_terminal: &Option<ParsedToken>) -> Result<(), SemanticError> {
         if let Some(tree_info) = self.type_trees.last_mut() {
             if let Some(mut node) = tree_info.curr_node {
-                while match tree_info.tree.nodes[node].symbol {
+                while !matches!(
+                    tree_info.tree.nodes[node].symbol,
                     StatementSymbol::Operator(_)
                         | StatementSymbol::SingleChildOperator(_)
-                        | StatementSymbol::FunctionCall(_, _) => false,
-                    _ => true,
-                } {
+                        | StatementSymbol::FunctionCall(_, _)
+                ) {
                     tree_info.curr_node = tree_info.tree.nodes[node].parent;
                     if tree_info.curr_node.is_none() {
                         break;
@@ -591,32 +1034,48 @@ impl SemanticInfo {
                     node = tree_info.curr_node.unwrap();
                 }
 
+                let node_span = tree_info.tree.nodes[node].span;
                 if let StatementSymbol::Operator(op) = &tree_info.tree.nodes[node].symbol {
+                    let op = op.clone();
                     if let Some(left) = tree_info.tree.nodes[node].left {
                         if let Some(right) = tree_info.tree.nodes[node].right {
                             if left >= tree_info.tree.nodes.len() || right >= tree_info.tree.nodes.len() {
                                 self.type_trees.pop();
-                                return Err(SemanticErrorType::InvalidType(
-                                    "Type not found".to_string()
+                                return Err(SemanticError::new(
+                                    SemanticErrorType::InvalidType(
+                                        "Type not found".to_string()
+                                    ),
+                                    node_span,
                                 ));
                             }
 
                             // Check if left and right nodes have the same type
                             let left_type = tree_info.tree.nodes[left].node_type.clone();
                             let right_type = tree_info.tree.nodes[right].node_type.clone();
-    
-                            if left_type.is_some() && right_type.is_some() {
-                                let left_type = left_type.unwrap();
-                                let right_type = right_type.unwrap();
-                                if *op != Token::Kand && *op != Token::Kor {
-                                    if left_type != right_type {
-                                        self.type_trees.pop();
-                                        return Err(SemanticErrorType::TypeMismatch(
-                                            format!("{} != {}", left_type, right_type)
-                                        ));
+
+                            if let (Some(left_type), Some(right_type)) = (left_type, right_type) {
+                                let mut operand_type = left_type.clone();
+                                if left_type != right_type {
+                                    match left_type.get_promoted_type(&right_type) {
+                                        Some(promoted_type) => {
+                                            // Convert the int operand to a double
+                                            let int_operand = if left_type == promoted_type { right } else { left };
+                                            tree_info.tree.add_conversion(int_operand, Some(node), promoted_type.clone());
+                                            operand_type = promoted_type;
+                                        },
+                                        None => {
+                                            let expr_span = tree_info.tree.get_span(node);
+                                            self.type_trees.pop();
+                                            return Err(SemanticError::new(
+                                                SemanticErrorType::TypeMismatch(
+                                                    format!("{} != {}", left_type, right_type)
+                                                ),
+                                                expr_span,
+                                            ));
+                                        },
                                     }
                                 }
-    
+
                                 // Set current node's type
                                 match op {
                                     Token::Kand |
@@ -630,38 +1089,71 @@ impl SemanticInfo {
                                         tree_info.tree.nodes[node].node_type = Some(BasicType::Int);
                                     },
                                     _ => {
-                                        tree_info.tree.nodes[node].node_type = left_type.clone().into();
+                                        tree_info.tree.nodes[node].node_type = operand_type.into();
                                     }
                                 }
                             } else {
                                 self.type_trees.pop();
-                                return Err(SemanticErrorType::InvalidType(
-                                    "Type not found".to_string()
+                                return Err(SemanticError::new(
+                                    SemanticErrorType::InvalidType(
+                                        "Type not found".to_string()
+                                    ),
+                                    node_span,
                                 ));
                             }
                         }
                     }
                     tree_info.curr_node = tree_info.tree.nodes[node].parent;
-                } else if let StatementSymbol::SingleChildOperator(_) = &tree_info.tree.nodes[node].symbol {
+                } else if let StatementSymbol::SingleChildOperator(op) = &tree_info.tree.nodes[node].symbol {
+                    let op = op.clone();
+                    if matches!(op, Token::Soparen | Token::Kint | Token::Kdouble) {
+                        // Include the closing parenthesis in the node's span
+                        if let Some(parsed_token) = prev_terminal {
+                            tree_info.tree.nodes[node].span = node_span.to(&parsed_token.span);
+                        }
+                    }
+
                     if let Some(left) = tree_info.tree.nodes[node].left {
                         let left_type = tree_info.tree.nodes[left].node_type.clone();
-                        if left_type.is_some() {
-                            tree_info.tree.nodes[node].node_type = left_type.clone().into();
+                        if matches!(op, Token::Kint | Token::Kdouble) {
+                            // A cast converts between ints and doubles only
+                            if !matches!(left_type, Some(BasicType::Int | BasicType::Double)) {
+                                let cast_span = tree_info.tree.nodes[node].span;
+                                self.type_trees.pop();
+                                return Err(SemanticError::new(
+                                    SemanticErrorType::InvalidType(
+                                        format!("Cannot convert {} to {}", left_type.map_or(String::from("an untyped value"), |left_type| left_type.to_string()), op.to_log_message())
+                                    ),
+                                    cast_span,
+                                ));
+                            }
+                            tree_info.tree.nodes[node].node_type = Some(if op == Token::Kint { BasicType::Int } else { BasicType::Double });
+                        } else if op == Token::Knot && left_type.is_some() {
+                            // Like `and` and `or`, `not` gives 1 or 0
+                            tree_info.tree.nodes[node].node_type = Some(BasicType::Int);
+                        } else if left_type.is_some() {
+                            tree_info.tree.nodes[node].node_type = left_type.clone();
                         } else {
                             self.type_trees.pop();
-                            return Err(SemanticErrorType::InvalidType(
-                                "Type not found".to_string()
+                            return Err(SemanticError::new(
+                                SemanticErrorType::InvalidType(
+                                    "Type not found".to_string()
+                                ),
+                                node_span,
                             ));
                         }
                     }
                 } else if let StatementSymbol::FunctionCall(func_id, _) = &tree_info.tree.nodes[node].symbol {
-                    if let Some(decl) = self.symbol_table.find_decl_by_id(&func_id) {
+                    if let Some(decl) = self.symbol_table.find_decl_by_id(func_id) {
                         if let BasicType::Function(func_info) = &decl.var_type {
                             tree_info.tree.nodes[node].node_type = Some(*func_info.return_type.clone());
                         }
                     } else {
-                        let err = Err(SemanticErrorType::UndefinedVariable(
-                            func_id.0.clone()
+                        let err = Err(SemanticError::new(
+                            SemanticErrorType::UndefinedVariable(
+                                func_id.0.clone()
+                            ),
+                            node_span,
                         ));
                         self.type_trees.pop();
                         return err;
@@ -673,28 +1165,37 @@ impl SemanticInfo {
         Ok(())
     }
 
-    fn check_var_type(&mut self) -> Result<(), SemanticErrorType> {
+    fn check_var_type(&mut self) -> Result<(), SemanticError> {
         if let Some(tree_info) = self.type_trees.last_mut() {
             if let Some(node) = tree_info.tree.start {
+                let span = tree_info.tree.get_span(node);
                 if tree_info.tree.nodes[node].node_type.is_none() || self.curr_id.is_none() {
                     self.type_trees.pop();
-                    return Err(SemanticErrorType::InvalidType(
-                        "Type not found".to_string()
+                    return Err(SemanticError::new(
+                        SemanticErrorType::InvalidType(
+                            "Type not found".to_string()
+                        ),
+                        span,
                     ));
                 }
 
-                let node_type = tree_info.tree.nodes[node].node_type.clone().unwrap();
                 if let Some(var_type) = &self.curr_var {
-                    if var_type.var_type != node_type {
+                    if let Err(node_type) = promote_tree(&mut tree_info.tree, &var_type.var_type) {
                         self.type_trees.pop();
-                        return Err(SemanticErrorType::TypeMismatch(
-                            format!("{} != {}", node_type, var_type.var_type)
-                        ));
+                        return Err(SemanticError::new(
+                            SemanticErrorType::TypeMismatch(
+                                format!("{} != {}", node_type, var_type.var_type)
+                            ),
+                            span,
+                        ).with_help(get_conversion_help(&node_type, &var_type.var_type)));
                     }
                 } else {
                     self.type_trees.pop();
-                    return Err(SemanticErrorType::UndefinedVariable(
-                        self.curr_id.as_ref().unwrap().clone()
+                    return Err(SemanticError::new(
+                        SemanticErrorType::UndefinedVariable(
+                            self.curr_id.as_ref().unwrap().clone()
+                        ),
+                        self.curr_id_span,
                     ));
                 }
             }
@@ -702,7 +1203,7 @@ impl SemanticInfo {
         Ok(())
     }
 
-    fn add_func_check(&mut self) -> Result<(), SemanticErrorType> {
+    fn add_func_check(&mut self) -> Result<(), SemanticError> {
         if let Some(tree_info) = self.type_trees.last_mut() {
             if let Some(node) = tree_info.tree.nodes.pop() {
                 // Update parent node and tree's current node
@@ -725,19 +1226,26 @@ impl SemanticInfo {
                         if let BasicType::Function(_) = decl.var_type {
                             self.func_checks.push(FuncCheck {
                                 func_id,
+                                span: node.span,
                                 param_index: 0,
                                 params: Vec::new(),
                             });
                         } else {
                             self.type_trees.pop();
-                            return Err(SemanticErrorType::InvalidType(
-                                format!("{} is not a function", decl.name)
+                            return Err(SemanticError::new(
+                                SemanticErrorType::InvalidType(
+                                    format!("{} is not a function", decl.name)
+                                ),
+                                node.span,
                             ));
                         }
                     } else {
                         self.type_trees.pop();
-                        return Err(SemanticErrorType::UndefinedVariable(
-                            func_id.0.clone()
+                        return Err(SemanticError::new(
+                            SemanticErrorType::UndefinedVariable(
+                                func_id.0.clone()
+                            ),
+                            node.span,
                         ));
                     }
                 }
@@ -746,19 +1254,29 @@ impl SemanticInfo {
         Ok(())
     }
 
-    fn pop_func_check(&mut self) -> Result<(), SemanticErrorType> {
+    fn pop_func_check(&mut self, prev_terminal: &Option<ParsedToken>) -> Result<(), SemanticError> {
         if let Some(tree_info) = self.type_trees.last_mut() {
             if let Some(func_check) = self.func_checks.pop() {
+                // The call spans from the function name to the closing parenthesis
+                let call_span = match prev_terminal {
+                    Some(parsed_token) => func_check.span.to(&parsed_token.span),
+                    None => func_check.span,
+                };
+
                 if let Some(decl) = self.symbol_table.find_decl_by_id(&func_check.func_id) {
                     if let BasicType::Function(func_info) = &decl.var_type {
                         if func_info.param_types.len() != func_check.param_index {
-                            return Err(SemanticErrorType::MissingParameters(
-                                format!("Expected {} parameters, found {}", func_info.param_types.len(), func_check.param_index)
-                            ));
+                            return Err(SemanticError::new(
+                                SemanticErrorType::MissingParameters(
+                                    format!("Expected {} parameters, found {}", func_info.param_types.len(), func_check.param_index)
+                                ),
+                                call_span,
+                            ).with_note("function declared here", decl.span));
                         }
 
                         tree_info.curr_node = tree_info.tree.add_node(
                             StatementSymbol::FunctionCall(func_check.func_id.clone(), func_check.params),
+                            call_span,
                             tree_info.curr_node
                         ).into();
                         tree_info.tree.nodes[tree_info.curr_node.unwrap()].node_type = Some(*func_info.return_type.clone());
@@ -769,30 +1287,34 @@ impl SemanticInfo {
         Ok(())
     }
 
-    fn check_param_type(&mut self) -> Result<(), SemanticErrorType> {
-        let mut err: Option<SemanticErrorType> = None;
+    fn check_param_type(&mut self) -> Result<(), SemanticError> {
+        let mut err: Option<SemanticError> = None;
         if let Some(func_check) = self.func_checks.last_mut() {
             if let Some(decl) = self.symbol_table.find_decl_by_id(&func_check.func_id) {
                 if let BasicType::Function(func_info) = &decl.var_type {
-                    if let Some(tree_info) = self.type_trees.last() {
+                    if let Some(tree_info) = self.type_trees.last_mut() {
                         if let Some(node) = tree_info.tree.start {
-                            if let Some(node_type) = &tree_info.tree.nodes[node].node_type {
-                                if func_info.param_types[func_check.param_index] != *node_type {
-                                    if let BasicType::Function(func_info) = &decl.var_type {
-                                        err = Some(SemanticErrorType::TypeMismatch(
-                                            format!("{} != {}", func_info.param_types[func_check.param_index], node_type)
-                                        ));
-                                    } else {
-                                        err = Some(SemanticErrorType::TypeMismatch(
-                                            format!("{} != {}", decl.var_type, node_type)
-                                        ));
-                                    }
-                                } else {
-                                    func_check.param_index += 1;
+                            let span = tree_info.tree.get_span(node);
+                            if tree_info.tree.nodes[node].node_type.is_some() {
+                                // Extra arguments are reported once the call is complete
+                                let param_type = func_info.param_types.get(func_check.param_index);
+                                match param_type.map(|param_type| (param_type, promote_tree(&mut tree_info.tree, param_type))) {
+                                    Some((param_type, Err(node_type))) => {
+                                        err = Some(SemanticError::new(
+                                            SemanticErrorType::TypeMismatch(
+                                                format!("{} != {}", param_type, node_type)
+                                            ),
+                                            span,
+                                        ).with_help(get_conversion_help(&node_type, param_type)));
+                                    },
+                                    _ => func_check.param_index += 1,
                                 }
                             } else {
-                                err = Some(SemanticErrorType::InvalidType(
-                                    "Type not found".to_string()
+                                err = Some(SemanticError::new(
+                                    SemanticErrorType::InvalidType(
+                                        "Type not found".to_string()
+                                    ),
+                                    span,
                                 ));
                             }
                         }
@@ -812,11 +1334,16 @@ impl SemanticInfo {
     fn add_assignment(&mut self) {
         if let Some(tree_info) = self.type_trees.pop() {
             if let Some(var) = &self.curr_var {
+                // The assigned variable is the last identifier outside of a statement tree
+                let span = match tree_info.tree.get_tree_span() {
+                    Some(value_span) => self.curr_id_span.to(&value_span),
+                    None => self.curr_id_span,
+                };
                 if let Some(arr_index) = &self.curr_array_index {
-                    self.symbol_table.add_assignment(var.clone(), Some(arr_index.clone()), tree_info.tree, self.curr_scope);
+                    self.symbol_table.add_assignment(var.clone(), Some(arr_index.clone()), tree_info.tree, span, self.curr_scope);
                     self.curr_array_index = None;
                 } else {
-                    self.symbol_table.add_assignment(var.clone(), None, tree_info.tree, self.curr_scope);
+                    self.symbol_table.add_assignment(var.clone(), None, tree_info.tree, span, self.curr_scope);
                 }
             }
         }
@@ -848,4 +1375,26 @@ impl SemanticInfo {
             }
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use crate::session::Session;
+
+    fn run(source: &str) -> String {
+        let mut output = Vec::new();
+        Session::from_bytes("test.cp", source.as_bytes()).run(&mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn logical_operators_promote_int_operands() {
+        let source = "int c;\nc = 1 and 1.5;\nprint c;\nc = 0 or 0.5;\nprint c;\nc = 1 and 0.0;\nprint c\n.";
+        assert_eq!(run(source), "1\n1\n0\n");
+    }
+
+    #[test]
+    fn not_gives_an_int() {
+        let source = "int c;\ndouble d;\nd = 0.5;\nc = not d;\nprint c;\nd = 0.0;\nc = (not d) + 1;\nprint c\n.";
+        assert_eq!(run(source), "0\n2\n");
+    }
+}
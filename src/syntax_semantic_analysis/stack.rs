@@ -1,8 +1,20 @@
 use super::productions::ProductionType;
 
+/**
+ * Where an item of the stack comes from: the production that pushed it and its index in the right side.
+ */
+#[derive(Debug, Clone, Copy)]
+pub struct ProductionPosition {
+    pub production_index: usize,
+    pub position: usize,
+}
+
 #[derive(Debug)]
 pub struct StackItem {
     value: ProductionType,
+    origin: Option<ProductionPosition>,
+    // The parse tree node the item becomes, when a parse tree is built
+    node: Option<usize>,
     next: Option<Box<StackItem>>,
 }
 
@@ -20,21 +32,23 @@ impl Stack {
         }
     }
 
-    pub fn push(&mut self, value: ProductionType) {
+    pub fn push(&mut self, value: ProductionType, origin: Option<ProductionPosition>, node: Option<usize>) {
         let new_item = StackItem {
             value,
+            origin,
+            node,
             next: self.top.take(),
         };
         self.top = Some(Box::new(new_item));
         self.size += 1;
     }
 
-    pub fn pop(&mut self) -> Option<ProductionType> {
+    pub fn pop(&mut self) -> Option<(ProductionType, Option<ProductionPosition>, Option<usize>)> {
         match self.top.take() {
             Some(item) => {
                 self.top = item.next;
                 self.size -= 1;
-                Some(item.value)
+                Some((item.value, item.origin, item.node))
             },
             None => None,
         }
@@ -43,4 +57,26 @@ impl Stack {
     pub fn is_empty(&self) -> bool {
         self.size == 0
     }
-}
\ No newline at end of file
+
+    /**
+     * Iterate over the items from the top of the stack to the bottom.
+     */
+    pub fn iter(&self) -> StackIter<'_> {
+        StackIter { next: self.top.as_deref() }
+    }
+}
+
+pub struct StackIter<'a> {
+    next: Option<&'a StackItem>,
+}
+
+impl<'a> Iterator for StackIter<'a> {
+    type Item = &'a ProductionType;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|item| {
+            self.next = item.next.as_deref();
+            &item.value
+        })
+    }
+}
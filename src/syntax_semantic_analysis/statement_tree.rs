@@ -1,4 +1,4 @@
-use crate::{lexical_analysis::Token, logger::Loggable};
+use crate::{lexical_analysis::Token, logger::Loggable, source_map::Span};
 
 use super::symbol_declaration::{BasicType, DeclId};
 
@@ -26,6 +26,8 @@ impl Loggable for StatementSymbol {
             StatementSymbol::Operator(token) | Self::SingleChildOperator(token) => {
                 match token {
                     Token::Soparen => String::from("()"),
+                    Token::Kint => String::from("int()"),
+                    Token::Kdouble => String::from("double()"),
                     Token::Oplus => String::from("+"),
                     Token::Ominus => String::from("-"),
                     Token::Omultiply => String::from("*"),
@@ -49,7 +51,7 @@ impl Loggable for StatementSymbol {
                     message.pop();
                     message.push_str(",\n");
                 }
-                message.push_str(")");
+                message.push(')');
                 message
             },
             StatementSymbol::ArrayAccess(decl, index) => {
@@ -63,6 +65,7 @@ impl Loggable for StatementSymbol {
 pub struct StatementNode {
     pub symbol: StatementSymbol,
     pub node_type: Option<BasicType>,
+    pub span: Span,
     pub parent: Option<usize>,
     pub left: Option<usize>,
     pub right: Option<usize>,
@@ -91,6 +94,12 @@ pub struct StatementTree {
     pub start: Option<usize>,
 }
 
+impl Default for StatementTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StatementTree {
     pub fn new() -> Self {
         Self {
@@ -99,10 +108,11 @@ impl StatementTree {
         }
     }
 
-    pub fn add_node(&mut self, symbol: StatementSymbol, parent: Option<usize>) -> usize {
+    pub fn add_node(&mut self, symbol: StatementSymbol, span: Span, parent: Option<usize>) -> usize {
         self.nodes.push(StatementNode {
             symbol,
             node_type: None,
+            span,
             parent,
             left: None,
             right: None,
@@ -125,12 +135,13 @@ impl StatementTree {
         node_index
     }
 
-    pub fn split_tree(&mut self, symbol: StatementSymbol, node: usize) -> usize {
+    pub fn split_tree(&mut self, symbol: StatementSymbol, span: Span, node: usize) -> usize {
         // Create new node with current node as left child
         let parent_index = self.nodes[node].parent;
         self.nodes.push(StatementNode {
             symbol,
             node_type: None,
+            span,
             parent: parent_index,
             left: Some(node),
             right: None,
@@ -157,77 +168,76 @@ impl StatementTree {
         new_node
     }
 
-    pub fn calculate_array_size(&self) -> Result<u32, String> {
-        if self.start.is_none() {
-            return Err(String::from("No statement provided"));
-        }
+    /**
+     * Convert the value of a node to another type, with a conversion node (`int(...)` or `double(...)`) above it.
+     * `parent` is the node the value is an operand of, `None` for the root of the tree. It is given rather than
+     * read from the node, whose parent link isn't set for every operand.
+     * Returns the conversion node.
+     */
+    pub fn add_conversion(&mut self, node: usize, parent: Option<usize>, target_type: BasicType) -> usize {
+        let token = match target_type {
+            BasicType::Double => Token::Kdouble,
+            _ => Token::Kint,
+        };
+        let span = self.get_span(node);
+        self.nodes.push(StatementNode {
+            symbol: StatementSymbol::SingleChildOperator(token),
+            node_type: Some(target_type),
+            span,
+            parent,
+            left: Some(node),
+            right: None,
+        });
+        let conversion = self.nodes.len() - 1;
 
-        let value = StatementTree::_calculate_array_size(&self.nodes, self.start.unwrap())?;
-        if value < 0 {
-            return Err(format!("Array size must be a positive value"));
+        match parent {
+            Some(parent) => {
+                let parent_node = &mut self.nodes[parent];
+                if parent_node.left == Some(node) {
+                    parent_node.left = Some(conversion);
+                } else {
+                    parent_node.right = Some(conversion);
+                }
+            },
+            None => self.start = Some(conversion),
         }
-        Ok(value as u32)
+        self.nodes[node].parent = Some(conversion);
+        conversion
     }
 
-    fn _calculate_array_size(nodes: &Vec<StatementNode>, start: usize) -> Result<i32, String> {
-        let node = &nodes[start];
-        match &node.symbol {
-            StatementSymbol::Literal(token) => {
-                match token {
-                    Token::Tint(value) => {
-                        Ok(*value as i32)
-                    },
-                    _ => Err(format!("Literal must be an Integer: {:?}", token)),
+    /**
+     * Get the span covering a node and all of its children.
+     */
+    pub fn get_span(&self, node: usize) -> Span {
+        let curr_node = &self.nodes[node];
+        let mut span = curr_node.span;
+        match &curr_node.symbol {
+            StatementSymbol::FunctionCall(_, params) => {
+                for param in params {
+                    if let Some(param_span) = param.get_tree_span() {
+                        span = span.to(&param_span);
+                    }
                 }
             },
-            StatementSymbol::Operator(op) => {
-                if !node.has_both_children() {
-                    return Err(format!("Invalid token: {}", op.to_log_message()));
-                }
-
-                let left = StatementTree::_calculate_array_size(nodes, node.left.unwrap())?;
-                let right = StatementTree::_calculate_array_size(nodes, node.right.unwrap())?;
-                match op {
-                    Token::Oplus => Ok(left + right),
-                    Token::Ominus => Ok(left - right),
-                    Token::Omultiply => Ok(left * right),
-                    Token::Odivide => Ok(left / right),
-                    Token::Omod => Ok(left % right),
-                    Token::Kor => Ok(if left > 0 || right > 0 { 1 } else { 0 }),
-                    Token::Kand => Ok(if left > 0 && right > 0 { 1 } else { 0 }),
-                    Token::Oequal => Ok(if left == right { 1 } else { 0 }),
-                    Token::Onot => Ok(if left != right { 1 } else { 0 }),
-                    Token::Olt => Ok(if left < right { 1 } else { 0 }),
-                    Token::Ogt => Ok(if left > right { 1 } else { 0 }),
-                    Token::Olte => Ok(if left <= right { 1 } else { 0 }),
-                    Token::Ogte => Ok(if left >= right { 1 } else { 0 }),
-                    _ => Err(format!("Invalid operator: {}", op.to_log_message())),
+            StatementSymbol::ArrayAccess(_, index) => {
+                if let Some(index_span) = index.get_tree_span() {
+                    span = span.to(&index_span);
                 }
             },
-            StatementSymbol::SingleChildOperator(op) => {
-                if node.left.is_none() {
-                    return Err(format!("Invalid token: {}", op.to_log_message()));
-                }
+            _ => {},
+        }
 
-                let left = StatementTree::_calculate_array_size(nodes, node.left.unwrap())?;
-                match op {
-                    Token::Soparen => Ok(left),
-                    Token::Knot => Ok(if left > 0 { 0 } else { 1 }),
-                    Token::Ominus => Ok(-left),
-                    _ => Err(format!("Invalid operator: {}", op.to_log_message())),
-                }
-            },
-            _ => Err(format!("Array size must be a constant value")),
+        if let Some(left) = curr_node.left {
+            span = span.to(&self.get_span(left));
         }
+        if let Some(right) = curr_node.right {
+            span = span.to(&self.get_span(right));
+        }
+        span
     }
 
-    pub fn get_type_size(&self) -> u32 {
-        if let Some(start) = self.start {
-            if let Some(node_type) = &self.nodes[start].node_type {
-                return node_type.get_size();
-            }
-        }
-        0
+    pub fn get_tree_span(&self) -> Option<Span> {
+        self.start.map(|start| self.get_span(start))
     }
 }
 
@@ -255,7 +265,7 @@ impl Loggable for StatementTree {
     }
 }
 
-pub struct StatementTreeInfo {
+pub(crate) struct StatementTreeInfo {
     pub tree: StatementTree,
     pub curr_node: Option<usize>,
 }
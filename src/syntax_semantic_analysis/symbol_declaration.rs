@@ -1,6 +1,6 @@
 use std::fmt::{self, Display, Formatter};
 
-use crate::logger::Loggable;
+use crate::{logger::Loggable, source_map::Span};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FuncInfo {
@@ -18,24 +18,25 @@ pub enum BasicType {
 }
 
 impl BasicType {
-    pub fn get_size(&self) -> u32 {
-        match self {
-            BasicType::Int => 4,
-            BasicType::Double => 4,
-            BasicType::Function(_) => 0,
-            BasicType::Array(inner_type, size) => {
-                match **inner_type {
-                    BasicType::Int => {
-                        4 * (*size)
-                    },
-                    BasicType::Double => {
-                        4 * (*size)
-                    },
-                    _ => 0,
-                }
-            }
+    /**
+     * Get the type both operands of an arithmetic or comparison operator are converted to:
+     * an int is promoted to a double when mixed with one. There is none for other mixes of types.
+     */
+    pub fn get_promoted_type(&self, other: &BasicType) -> Option<BasicType> {
+        match (self, other) {
+            (a, b) if a == b => Some(a.clone()),
+            (BasicType::Int, BasicType::Double) | (BasicType::Double, BasicType::Int) => Some(BasicType::Double),
+            _ => None,
         }
     }
+
+    /**
+     * Whether a value of this type can be used where a `target` is expected without an explicit conversion.
+     */
+    pub fn is_promotable_to(&self, target: &BasicType) -> bool {
+        self == target || (*self == BasicType::Int && *target == BasicType::Double)
+    }
+
 }
 
 impl Loggable for BasicType {
@@ -45,7 +46,7 @@ impl Loggable for BasicType {
             BasicType::Double => String::from("double"),
             BasicType::Function(func_info) => {
                 let mut message = String::from("func(");
-                if func_info.param_types.len() > 0 {
+                if !func_info.param_types.is_empty() {
                     for param in &func_info.param_types {
                         message.push_str(&param.to_log_message());
                         message.push_str(", ");
@@ -81,14 +82,16 @@ pub struct SymbolDecl {
     pub name: String,
     pub var_type: BasicType,
     pub scope: usize,
+    pub span: Span,
 }
 
 impl SymbolDecl {
-    pub fn new(name: String, var_type: BasicType, scope: usize) -> SymbolDecl {
+    pub fn new(name: String, var_type: BasicType, scope: usize, span: Span) -> SymbolDecl {
         SymbolDecl {
             name,
-            var_type: var_type,
+            var_type,
             scope,
+            span,
         }
     }
 
@@ -96,7 +99,8 @@ impl SymbolDecl {
         name: String,
         return_type: BasicType,
         body_scope: usize,
-        scope: usize
+        scope: usize,
+        span: Span,
     ) -> SymbolDecl {
         let func_info = FuncInfo {
             return_type: Box::new(return_type),
@@ -107,6 +111,7 @@ impl SymbolDecl {
             name,
             var_type: BasicType::Function(func_info),
             scope,
+            span,
         }
     }
 
@@ -118,28 +123,6 @@ impl SymbolDecl {
         format!("{}{}", self.name, self.scope)
     }
 
-    pub fn get_size(&self) -> u32 {
-        match &self.var_type {
-            BasicType::Array(arr_type, size) => {
-                match **arr_type {
-                    BasicType::Int => {
-                        4 * (*size)
-                    },
-                    BasicType::Double => {
-                        4 * (*size)
-                    },
-                    _ => 0,
-                }
-            },
-            BasicType::Int => {
-                4
-            },
-            BasicType::Double => {
-                4
-            },
-            _ => 0,
-        }
-    }
 }
 
 impl Loggable for SymbolDecl {
@@ -1,14 +1,14 @@
-use crate::{lexical_analysis::Token, logger::Loggable};
+use crate::{logger::Loggable, source_map::Span};
 
 use super::{
-    semantic_analysis::SemanticErrorType,
-    statement_tree::{StatementNode, StatementSymbol, StatementTree},
+    semantic_analysis::{SemanticError, SemanticErrorType},
+    statement_tree::StatementTree,
     symbol_declaration::{
-        BasicType, DeclId, SymbolDecl
+        to_var_name, BasicType, DeclId, SymbolDecl
     }
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ScopeType {
     Global,
     Function,
@@ -25,11 +25,26 @@ impl Loggable for ScopeType {
     }
 }
 
+impl ScopeType {
+    /**
+     * Get how the scope is referred to in diagnostics, e.g. `function scope`.
+     */
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            ScopeType::Global => "global scope",
+            ScopeType::Function => "function scope",
+            ScopeType::Local => "local scope",
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AssignmentInfo {
     pub var: DeclId,
     pub index: Option<StatementTree>,
     pub assignment: StatementTree,
+    // From the assigned variable to the end of the value
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +71,8 @@ pub enum BuiltInFuncType {
 pub struct BuiltInFunc {
     pub func_type: BuiltInFuncType,
     pub statement: StatementTree,
+    // From the keyword to the end of the expression
+    pub span: Span,
 }
 
 #[derive(Debug)]
@@ -98,6 +115,18 @@ impl SymbolScope {
     pub fn get_symbols(&self) -> &Vec<SymbolEntry> {
         &self.symbols
     }
+
+    pub fn get_scope_type(&self) -> &ScopeType {
+        &self.scope_type
+    }
+
+    pub fn get_scope_id(&self) -> usize {
+        self.scope_id
+    }
+
+    pub fn get_parent_scope(&self) -> usize {
+        self.parent_scope
+    }
 }
 
 pub const GLOBAL_SCOPE: usize = 0;
@@ -108,6 +137,12 @@ pub struct SymbolTable {
     decls: Vec<(String, Vec<SymbolDecl>)>,
 }
 
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SymbolTable {
     pub fn new() -> SymbolTable {
         SymbolTable {
@@ -133,13 +168,17 @@ impl SymbolTable {
         self.scopes.len()
     }
 
-    pub fn add_declaration(&mut self, symbol_decl: SymbolDecl) -> Result<(), SemanticErrorType> {
+    pub fn add_declaration(&mut self, symbol_decl: SymbolDecl) -> Result<(), SemanticError> {
         self.insert_decl(symbol_decl.clone())?;
         self.scopes[symbol_decl.scope].add_declaration(symbol_decl.get_id());
         Ok(())
     }
 
-    fn insert_decl(&mut self, decl: SymbolDecl) -> Result<(), SemanticErrorType> {
+    /**
+     * Insert a declaration, keeping the declarations of each name sorted by scope.
+     * A name can only be declared once per scope: the error points at the new declaration, with a note at the first one.
+     */
+    fn insert_decl(&mut self, decl: SymbolDecl) -> Result<(), SemanticError> {
         let mut low: usize = 0;
         let mut high: usize = self.decls.len();
 
@@ -151,10 +190,12 @@ impl SymbolTable {
 
                 while low < high {
                     let decl_index = (high + low) / 2;
-                    if self.decls[index].1[decl_index].scope == decl.scope {
-                        return Err(SemanticErrorType::DuplicateDeclaration(
-                            format!("Symbol {} already declared in scope {}", decl.name, decl.scope)
-                        ));
+                    let original = &self.decls[index].1[decl_index];
+                    if original.scope == decl.scope {
+                        return Err(SemanticError::new(
+                            SemanticErrorType::DuplicateDeclaration(decl.name.clone(), self.scopes[decl.scope].scope_type),
+                            decl.span,
+                        ).with_note(&format!("`{}` first declared here", original.name), original.span));
                     } else if self.decls[index].1[decl_index].scope < decl.scope {
                         low = decl_index + 1;
                     } else {
@@ -205,11 +246,21 @@ impl SymbolTable {
         None
     }
 
+    /**
+     * Find the declaration a new declaration of `name` in `curr_scope` would shadow, declared in one of its parent scopes.
+     */
+    pub fn find_shadowed_decl(&self, name: &String, curr_scope: usize) -> Option<&SymbolDecl> {
+        if curr_scope == GLOBAL_SCOPE {
+            return None;
+        }
+        self.find_decl(name, self.get_parent_scope(curr_scope))
+    }
+
     pub fn find_decl_by_id(&self, id: &DeclId) -> Option<&SymbolDecl> {
         self.find_decl(&id.0, id.1)
     }
 
-    pub fn add_parameter(&mut self, symbol_decl: SymbolDecl) -> Result<(), SemanticErrorType> {
+    pub fn add_parameter(&mut self, symbol_decl: SymbolDecl) -> Result<(), SemanticError> {
         self.insert_decl(symbol_decl.clone())?;
         self.scopes[symbol_decl.scope].add_parameter(symbol_decl.get_id());
         Ok(())
@@ -219,13 +270,14 @@ impl SymbolTable {
         self.scopes[scope].symbols.push(SymbolEntry::StatementTree(tree));
     }
 
-    pub fn add_assignment(&mut self, var: SymbolDecl, index: Option<StatementTree>, assignment: StatementTree, scope: usize) {
+    pub fn add_assignment(&mut self, var: SymbolDecl, index: Option<StatementTree>, assignment: StatementTree, span: Span, scope: usize) {
         self.scopes[scope].symbols.push(
             SymbolEntry::Assignment(
                 AssignmentInfo {
                     var: var.get_id(),
                     index,
                     assignment,
+                    span,
                 }
             )
         );
@@ -242,6 +294,138 @@ impl SymbolTable {
             SymbolEntry::BuiltInFunction(func)
         );
     }
+
+    /**
+     * Get the statements of the program (with their statement trees) in the order they appear.
+     */
+    pub fn statements_to_log_message(&self) -> String {
+        let mut msg = String::new();
+        self.log_scope_statements(GLOBAL_SCOPE, 0, &mut msg);
+        msg
+    }
+
+    /**
+     * Get every statement tree of the program in the order they appear, with what they belong to
+     * (e.g. `assign x0`, `index of x0`, `while condition`), prefixed by their function's name.
+     */
+    pub fn get_statement_trees(&self) -> Vec<(String, &StatementTree)> {
+        let mut trees = Vec::new();
+        self.add_scope_statement_trees(GLOBAL_SCOPE, "", &mut trees);
+        trees
+    }
+
+    fn add_scope_statement_trees<'a>(&'a self, scope: usize, prefix: &str, trees: &mut Vec<(String, &'a StatementTree)>) {
+        for symbol in self.scopes[scope].symbols.iter() {
+            match symbol {
+                SymbolEntry::Decl(decl_id) => {
+                    if let Some(decl) = self.find_decl_by_id(decl_id) {
+                        if let BasicType::Function(func) = &decl.var_type {
+                            let prefix = format!("{}{}: ", prefix, decl_id.0);
+                            self.add_scope_statement_trees(func.body_scope, &prefix, trees);
+                        }
+                    }
+                },
+                SymbolEntry::Parameter(_) => {},
+                SymbolEntry::Scope(new_scope) => self.add_scope_statement_trees(*new_scope, prefix, trees),
+                SymbolEntry::StatementTree(tree) => trees.push((format!("{}expr", prefix), tree)),
+                SymbolEntry::Assignment(assignment_info) => {
+                    let var = to_var_name(&assignment_info.var);
+                    if let Some(index) = &assignment_info.index {
+                        trees.push((format!("{}index of {}", prefix, var), index));
+                    }
+                    trees.push((format!("{}assign {}", prefix, var), &assignment_info.assignment));
+                },
+                SymbolEntry::ConditionalStatement(cond) => {
+                    let name = match cond.statement_type {
+                        ConditionalStatementType::If => "if",
+                        ConditionalStatementType::Else => "else",
+                        ConditionalStatementType::While => "while",
+                    };
+                    if let Some(condition) = &cond.condition {
+                        trees.push((format!("{}{} condition", prefix, name), condition));
+                    }
+                    self.add_scope_statement_trees(cond.body_scope, prefix, trees);
+                },
+                SymbolEntry::BuiltInFunction(builtin_func) => {
+                    let name = match builtin_func.func_type {
+                        BuiltInFuncType::Print => "print",
+                        BuiltInFuncType::Return => "return",
+                    };
+                    trees.push((format!("{}{}", prefix, name), &builtin_func.statement));
+                },
+            }
+        }
+    }
+
+    fn log_scope_statements(&self, scope: usize, tabs: usize, msg: &mut String) {
+        let indent = "\t".repeat(tabs);
+        let log_tree = |tree: &StatementTree, msg: &mut String| {
+            for line in tree.to_log_message().lines() {
+                msg.push_str(&format!("{}\t{}\n", indent, line));
+            }
+        };
+
+        let symbols = &self.scopes[scope].symbols;
+        for (i, symbol) in symbols.iter().enumerate() {
+            match symbol {
+                SymbolEntry::Decl(decl_id) => {
+                    if let Some(decl) = self.find_decl_by_id(decl_id) {
+                        if let BasicType::Function(func) = &decl.var_type {
+                            msg.push_str(&format!("{}def {}\n", indent, to_var_name(decl_id)));
+                            self.log_scope_statements(func.body_scope, tabs + 1, msg);
+                            msg.push_str(&format!("{}fed\n", indent));
+                        }
+                    }
+                },
+                SymbolEntry::Parameter(decl_id) => {
+                    msg.push_str(&format!("{}param {}\n", indent, to_var_name(decl_id)));
+                },
+                SymbolEntry::Scope(new_scope) => {
+                    self.log_scope_statements(*new_scope, tabs, msg);
+                },
+                SymbolEntry::StatementTree(tree) => {
+                    msg.push_str(&format!("{}expr\n", indent));
+                    log_tree(tree, msg);
+                },
+                SymbolEntry::Assignment(assignment_info) => {
+                    msg.push_str(&format!("{}assign {}\n", indent, to_var_name(&assignment_info.var)));
+                    if let Some(index) = &assignment_info.index {
+                        msg.push_str(&format!("{}index\n", indent));
+                        log_tree(index, msg);
+                    }
+                    log_tree(&assignment_info.assignment, msg);
+                },
+                SymbolEntry::ConditionalStatement(cond) => {
+                    // An if followed by an else is closed by the else
+                    let has_else = matches!(
+                        symbols.get(i + 1),
+                        Some(SymbolEntry::ConditionalStatement(ConditionalStatement { statement_type: ConditionalStatementType::Else, .. }))
+                    );
+                    let (start, end) = match cond.statement_type {
+                        ConditionalStatementType::If => ("if", if has_else { None } else { Some("fi") }),
+                        ConditionalStatementType::Else => ("else", Some("fi")),
+                        ConditionalStatementType::While => ("while", Some("od")),
+                    };
+                    msg.push_str(&format!("{}{}\n", indent, start));
+                    if let Some(condition) = &cond.condition {
+                        log_tree(condition, msg);
+                    }
+                    self.log_scope_statements(cond.body_scope, tabs + 1, msg);
+                    if let Some(end) = end {
+                        msg.push_str(&format!("{}{}\n", indent, end));
+                    }
+                },
+                SymbolEntry::BuiltInFunction(builtin_func) => {
+                    let name = match builtin_func.func_type {
+                        BuiltInFuncType::Print => "print",
+                        BuiltInFuncType::Return => "return",
+                    };
+                    msg.push_str(&format!("{}{}\n", indent, name));
+                    log_tree(&builtin_func.statement, msg);
+                },
+            }
+        }
+    }
 }
 
 impl Loggable for SymbolTable {
@@ -1,36 +1,178 @@
-use crate::{lexical_analysis::Token, logger::Loggable};
+use crate::{
+    diagnostics::{Diagnostic, ToDiagnostic},
+    lexical_analysis::Token,
+    logger::Loggable,
+    source_map::Span,
+};
+use super::non_terminals::NonTerminal;
+use super::productions::{Production, ProductionType};
 
 #[derive(Debug)]
 pub enum SyntaxErrorType {
-    ExpectedToken(Token, Token),
-    UnexpectedToken(Token),
+    // A token is missing or wrong: the tokens that could have come next, the token found and where it was found
+    ExpectedToken {
+        expected: Vec<Token>,
+        found: Token,
+        context: Option<String>,
+    },
+    // A token that doesn't belong, and was skipped
+    UnexpectedToken {
+        expected: Vec<Token>,
+        found: Token,
+        context: Option<String>,
+    },
     UnexpectedEndOfFile,
 }
 
+#[derive(Debug)]
 pub struct SyntaxError {
     error_type: SyntaxErrorType,
-    line: usize,
+    span: Span,
 }
 
 impl SyntaxError {
-    pub fn new(error_type: SyntaxErrorType, line: usize) -> Self {
+    pub fn new(error_type: SyntaxErrorType, span: Span) -> Self {
         SyntaxError {
             error_type,
-            line,
+            span,
         }
     }
+
+    pub fn get_error_type(&self) -> &SyntaxErrorType {
+        &self.error_type
+    }
+
+    pub fn get_span(&self) -> &Span {
+        &self.span
+    }
+
+    pub fn get_line(&self) -> usize {
+        self.span.line
+    }
 }
 
 impl Loggable for SyntaxError {
     fn to_log_message(&self) -> String {
         match &self.error_type {
-            SyntaxErrorType::ExpectedToken(expected, found) => format!("Expected token {:?} but found {:?} on line {}", expected, found, self.line),
-            SyntaxErrorType::UnexpectedToken(token) => format!("Unexpected token {:?} on line {}", token, self.line),
-            SyntaxErrorType::UnexpectedEndOfFile => format!("Unexpected end of file on line {}", self.line),
+            SyntaxErrorType::ExpectedToken { .. } | SyntaxErrorType::UnexpectedToken { .. } => {
+                format!("{} on line {}, column {}", self.get_message(), self.span.line, self.span.column)
+            },
+            SyntaxErrorType::UnexpectedEndOfFile => format!("Unexpected end of file on line {}, column {}", self.span.line, self.span.column),
+        }
+    }
+}
+
+impl SyntaxError {
+    /**
+     * Describe the error in human terms, e.g. "expected `then` after if-condition, found `do`".
+     */
+    pub fn get_message(&self) -> String {
+        match &self.error_type {
+            SyntaxErrorType::ExpectedToken { expected, found, context } | SyntaxErrorType::UnexpectedToken { expected, found, context } => {
+                let context = match context {
+                    Some(context) => format!(" {}", context),
+                    None => String::new(),
+                };
+                if expected.is_empty() {
+                    format!("unexpected token `{}`{}", found, context)
+                } else {
+                    format!("expected {}{}, found `{}`", describe_tokens(expected), context, found)
+                }
+            },
+            SyntaxErrorType::UnexpectedEndOfFile => String::from("unexpected end of file"),
+        }
+    }
+}
+
+impl ToDiagnostic for SyntaxError {
+    fn to_diagnostic(&self) -> Diagnostic {
+        match &self.error_type {
+            SyntaxErrorType::ExpectedToken { expected, .. } => Diagnostic::error(
+                "E0101",
+                self.get_message(),
+                Some(self.span),
+            ).with_label(format!("expected {}", describe_tokens(expected))),
+            SyntaxErrorType::UnexpectedToken { .. } => Diagnostic::error(
+                "E0102",
+                self.get_message(),
+                Some(self.span),
+            ).with_label(String::from("unexpected token")),
+            SyntaxErrorType::UnexpectedEndOfFile => Diagnostic::error(
+                "E0103",
+                String::from("unexpected end of file"),
+                Some(self.span),
+            ).with_note(String::from("programs end with `.`"), None),
         }
     }
 }
 
+/**
+ * Describe a token that is expected, e.g. "`then`" or "an identifier".
+ */
+pub fn describe_token(token: &Token) -> String {
+    match token {
+        Token::Identifier(_) => String::from("an identifier"),
+        Token::Tint(_) => String::from("an integer"),
+        Token::Tdouble(_) => String::from("a double"),
+        token => format!("`{}`", token),
+    }
+}
+
+/**
+ * Describe a set of expected tokens, e.g. "one of `then`, `(` or an operator".
+ * When several operators are expected, they are grouped as "an operator".
+ */
+pub fn describe_tokens(tokens: &[Token]) -> String {
+    let is_operator = |token: &Token| matches!(
+        token,
+        Token::Oplus | Token::Ominus | Token::Omultiply | Token::Odivide | Token::Omod
+            | Token::Oequal | Token::Olt | Token::Olte | Token::Ogt | Token::Ogte | Token::Onot
+            | Token::Kand | Token::Kor
+    );
+    let operator_count = tokens.iter().filter(|token| is_operator(token)).count();
+
+    let mut descriptions: Vec<String> = tokens.iter()
+        .filter(|token| operator_count < 2 || !is_operator(token))
+        .map(describe_token)
+        .collect();
+    if operator_count >= 2 {
+        descriptions.push(String::from("an operator"));
+    }
+
+    match descriptions.as_slice() {
+        [] => String::from("nothing"),
+        [description] => description.clone(),
+        [rest @ .., last] if rest.len() == 1 => format!("{} or {}", rest[0], last),
+        [rest @ .., last] => format!("one of {} or {}", rest.join(", "), last),
+    }
+}
+
+/**
+ * Describe where an element of a production is, from what comes before it, e.g. "after if-condition".
+ */
+pub fn get_context(production: &Production, position: usize) -> Option<String> {
+    let prev = production.right[..position].iter()
+        .rev()
+        .find(|elem| !matches!(elem, ProductionType::Action(_)))?;
+
+    let context = match prev {
+        ProductionType::Terminal(token) => format!("after {}", describe_token(token)),
+        ProductionType::NonTerminal(non_terminal) => match (production.left, non_terminal) {
+            (NonTerminal::If, NonTerminal::Bexpr) => String::from("after if-condition"),
+            (NonTerminal::Statement, NonTerminal::Bexpr) => String::from("after while-condition"),
+            (NonTerminal::Factor, NonTerminal::Bexpr) => String::from("after parenthesized expression"),
+            (NonTerminal::Var2, NonTerminal::Bexpr) => String::from("after array index"),
+            (NonTerminal::If, NonTerminal::Else) => String::from("at the end of the if statement"),
+            (NonTerminal::Statement, NonTerminal::StatementSeq) => String::from("at the end of the while loop"),
+            (NonTerminal::Fdec, NonTerminal::DeclarationsSeq) => String::from("at the end of the function body"),
+            (NonTerminal::Program, NonTerminal::DeclarationsSeq) => String::from("at the end of the program"),
+            (_, non_terminal) => format!("after {}", non_terminal.get_description()),
+        },
+        ProductionType::Action(_) => unreachable!(),
+    };
+    Some(context)
+}
+
 impl Loggable for Box<[SyntaxError]> {
     fn to_log_message(&self) -> String {
         let mut msg = String::new();
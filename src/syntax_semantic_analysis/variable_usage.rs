@@ -0,0 +1,246 @@
+use std::collections::HashSet;
+
+use super::semantic_analysis::{SemanticWarning, SemanticWarningType};
+use super::statement_tree::{StatementSymbol, StatementTree};
+use super::symbol_declaration::{BasicType, DeclId};
+use super::symbol_table::{BuiltInFuncType, ConditionalStatementType, SymbolEntry, SymbolTable, GLOBAL_SCOPE};
+
+/**
+ * Warn about variables that are read before being assigned, and about variables, parameters and functions
+ * that are never used. Names starting with `_` are never reported as unused.
+ *
+ * Assignment is tracked for the int and double variables of the main program and of every function, following
+ * the statements in order: a variable assigned in both branches of an if/else is assigned after it, while one
+ * assigned in a `while` body may not be. Globals read from a function and array elements are not tracked.
+ */
+pub fn check_variable_usage(table: &SymbolTable) -> Vec<SemanticWarning> {
+    let mut checker = VariableUsageChecker {
+        table,
+        curr_func: None,
+        tracked: HashSet::new(),
+        reported: HashSet::new(),
+        reads: HashSet::new(),
+        writes: HashSet::new(),
+        calls: HashSet::new(),
+        warnings: Vec::new(),
+    };
+
+    let functions: Vec<(DeclId, usize)> = get_decls(table, GLOBAL_SCOPE)
+        .filter_map(|decl_id| match table.find_decl_by_id(decl_id).map(|decl| &decl.var_type) {
+            Some(BasicType::Function(func_info)) => Some((decl_id.clone(), func_info.body_scope)),
+            _ => None,
+        })
+        .collect();
+
+    checker.check_body(GLOBAL_SCOPE, None);
+    for (func_id, body_scope) in functions.iter() {
+        checker.check_body(*body_scope, Some(func_id.clone()));
+    }
+    checker.check_unused(&functions);
+    checker.warnings
+}
+
+/**
+ * The variables assigned at some point of a body.
+ */
+#[derive(Clone, Default)]
+struct AssignmentState {
+    // On every path to the point
+    definite: HashSet<DeclId>,
+    // On at least one path to the point
+    maybe: HashSet<DeclId>,
+}
+
+impl AssignmentState {
+    fn assign(&mut self, var: &DeclId) {
+        self.definite.insert(var.clone());
+        self.maybe.insert(var.clone());
+    }
+
+    /**
+     * Join the states at the end of two branches, where a branch that returned doesn't reach the join.
+     */
+    fn join(first: AssignmentState, first_returns: bool, second: AssignmentState, second_returns: bool) -> AssignmentState {
+        match (first_returns, second_returns) {
+            (true, false) => second,
+            (false, true) => first,
+            _ => AssignmentState {
+                definite: first.definite.intersection(&second.definite).cloned().collect(),
+                maybe: first.maybe.union(&second.maybe).cloned().collect(),
+            },
+        }
+    }
+}
+
+struct VariableUsageChecker<'a> {
+    table: &'a SymbolTable,
+    // The function whose body is checked, `None` for the main program
+    curr_func: Option<DeclId>,
+    // The variables whose assignment is tracked in the current body
+    tracked: HashSet<DeclId>,
+    // Variables already reported as read before being assigned
+    reported: HashSet<DeclId>,
+    reads: HashSet<DeclId>,
+    writes: HashSet<DeclId>,
+    // Functions called from anywhere but their own body
+    calls: HashSet<DeclId>,
+    warnings: Vec<SemanticWarning>,
+}
+
+impl VariableUsageChecker<'_> {
+    fn check_body(&mut self, scope: usize, func: Option<DeclId>) {
+        self.curr_func = func;
+        self.tracked = get_decls(self.table, scope)
+            .filter(|decl_id| matches!(
+                self.table.find_decl_by_id(decl_id).map(|decl| &decl.var_type),
+                Some(BasicType::Int | BasicType::Double)
+            ))
+            .cloned()
+            .collect();
+        self.check_scope(scope, &mut AssignmentState::default());
+    }
+
+    /**
+     * Follow the statements of a scope, updating which variables are assigned.
+     * Returns whether every path through the scope returns.
+     */
+    fn check_scope(&mut self, scope: usize, state: &mut AssignmentState) -> bool {
+        let symbols = self.table.get_scope(scope).get_symbols();
+        let mut returns = false;
+
+        let mut i = 0;
+        while i < symbols.len() {
+            match &symbols[i] {
+                SymbolEntry::Assignment(assignment) => {
+                    if let Some(index) = &assignment.index {
+                        self.check_reads(index, state);
+                    }
+                    self.check_reads(&assignment.assignment, state);
+                    self.writes.insert(assignment.var.clone());
+                    state.assign(&assignment.var);
+                },
+                SymbolEntry::BuiltInFunction(func) => {
+                    self.check_reads(&func.statement, state);
+                    if matches!(func.func_type, BuiltInFuncType::Return) {
+                        returns = true;
+                    }
+                },
+                SymbolEntry::StatementTree(tree) => self.check_reads(tree, state),
+                SymbolEntry::ConditionalStatement(statement) => {
+                    if let Some(condition) = &statement.condition {
+                        self.check_reads(condition, state);
+                    }
+                    let mut body_state = state.clone();
+                    let body_returns = self.check_scope(statement.body_scope, &mut body_state);
+
+                    match statement.statement_type {
+                        ConditionalStatementType::If => {
+                            // The `else` comes right after its `if`, otherwise the `if` may be skipped
+                            let (else_state, else_returns) = match symbols.get(i + 1) {
+                                Some(SymbolEntry::ConditionalStatement(else_statement))
+                                    if matches!(else_statement.statement_type, ConditionalStatementType::Else) => {
+                                    i += 1;
+                                    let mut else_state = state.clone();
+                                    let else_returns = self.check_scope(else_statement.body_scope, &mut else_state);
+                                    (else_state, else_returns)
+                                },
+                                _ => (state.clone(), false),
+                            };
+                            returns |= body_returns && else_returns;
+                            *state = AssignmentState::join(body_state, body_returns, else_state, else_returns);
+                        },
+                        // The body of a loop may not run
+                        _ => *state = AssignmentState::join(body_state, body_returns, state.clone(), false),
+                    }
+                },
+                SymbolEntry::Decl(_) | SymbolEntry::Scope(_) | SymbolEntry::Parameter(_) => {},
+            }
+            i += 1;
+        }
+        returns
+    }
+
+    /**
+     * Record the variables read and the functions called by a statement tree,
+     * warning about tracked variables that aren't assigned yet.
+     */
+    fn check_reads(&mut self, tree: &StatementTree, state: &AssignmentState) {
+        for node in tree.nodes.iter() {
+            match &node.symbol {
+                StatementSymbol::Decl(var) => {
+                    self.reads.insert(var.clone());
+                    if self.tracked.contains(var) && !state.definite.contains(var) && self.reported.insert(var.clone()) {
+                        let warning_type = if state.maybe.contains(var) {
+                            SemanticWarningType::MaybeUninitializedVariable(var.0.clone())
+                        } else {
+                            SemanticWarningType::UninitializedVariable(var.0.clone())
+                        };
+                        let mut warning = SemanticWarning::new(warning_type, node.span);
+                        if let Some(decl) = self.table.find_decl_by_id(var) {
+                            warning = warning.with_note(&format!("`{}` declared here", decl.name), decl.span);
+                        }
+                        self.warnings.push(warning);
+                    }
+                },
+                StatementSymbol::ArrayAccess(arr, index) => {
+                    self.reads.insert(arr.clone());
+                    self.check_reads(index, state);
+                },
+                StatementSymbol::FunctionCall(func, args) => {
+                    if self.curr_func.as_ref() != Some(func) {
+                        self.calls.insert(func.clone());
+                    }
+                    for arg in args.iter() {
+                        self.check_reads(arg, state);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /**
+     * Warn about the variables and parameters never read and the functions never called.
+     */
+    fn check_unused(&mut self, functions: &[(DeclId, usize)]) {
+        let mut scopes = vec![GLOBAL_SCOPE];
+        scopes.extend(functions.iter().map(|(_, body_scope)| *body_scope));
+
+        for scope in scopes {
+            for symbol in self.table.get_scope(scope).get_symbols() {
+                let (decl_id, is_param) = match symbol {
+                    SymbolEntry::Decl(decl_id) => (decl_id, false),
+                    SymbolEntry::Parameter(decl_id) => (decl_id, true),
+                    _ => continue,
+                };
+                let decl = match self.table.find_decl_by_id(decl_id) {
+                    Some(decl) if !decl.name.starts_with('_') => decl,
+                    _ => continue,
+                };
+
+                let warning_type = match &decl.var_type {
+                    BasicType::Function(_) if !self.calls.contains(decl_id) => SemanticWarningType::UnusedFunction(decl.name.clone()),
+                    BasicType::Function(_) => continue,
+                    _ if self.reads.contains(decl_id) => continue,
+                    _ if is_param => SemanticWarningType::UnusedParameter(decl.name.clone()),
+                    _ if self.writes.contains(decl_id) => SemanticWarningType::UnreadVariable(decl.name.clone()),
+                    _ => SemanticWarningType::UnusedVariable(decl.name.clone()),
+                };
+                self.warnings.push(
+                    SemanticWarning::new(warning_type, decl.span)
+                        .with_help(format!("if this is intentional, prefix it with an underscore: `_{}`", decl.name))
+                );
+            }
+        }
+    }
+}
+
+/**
+ * Get the declarations of a scope, without its parameters.
+ */
+fn get_decls(table: &SymbolTable, scope: usize) -> impl Iterator<Item = &DeclId> {
+    table.get_scope(scope).get_symbols().iter().filter_map(|symbol| match symbol {
+        SymbolEntry::Decl(decl_id) => Some(decl_id),
+        _ => None,
+    })
+}